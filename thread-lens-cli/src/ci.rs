@@ -0,0 +1,260 @@
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use thread_lens::analyzer::blockers::join_threads;
+use thread_lens::analyzer::gate::{GateCheck, GateResult};
+use thread_lens::analyzer::report::AnalysisReport;
+use thread_lens::antipatterns::Severity;
+use thread_lens::classifier::qualified_frame;
+use thread_lens::export::xml_escape;
+
+const TOOL_NAME: &str = "thread-lens";
+
+fn check_class(check: GateCheck) -> &'static str {
+    match check {
+        GateCheck::Deadlocks => "thread-lens.deadlocks",
+        GateCheck::ChronicBlocking => "thread-lens.chronic-blocking",
+        GateCheck::Findings => "thread-lens.findings",
+    }
+}
+
+/// One test case per enabled check, or one per failure of a check that failed.
+pub fn junit_xml(result: &GateResult) -> String {
+    let mut cases = Vec::new();
+    for &check in &result.checks {
+        let failures: Vec<_> = result.failures_of(check).collect();
+        if failures.is_empty() {
+            cases.push(format!(
+                "    <testcase classname=\"{}\" name=\"{}\"/>",
+                check_class(check),
+                xml_escape(check.label())
+            ));
+        }
+        for failure in failures {
+            cases.push(format!(
+                "    <testcase classname=\"{}\" name=\"{}\">\n      <failure type=\"{}\" message=\"{}\"/>\n    </testcase>",
+                check_class(check),
+                xml_escape(&failure.rule_id),
+                xml_escape(&failure.rule_id),
+                xml_escape(&failure.message)
+            ));
+        }
+    }
+    let tests = cases.len();
+    let failures = result.failures.len();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"{tool}\" tests=\"{tests}\" failures=\"{failures}\">\n  <testsuite name=\"{tool} check\" tests=\"{tests}\" failures=\"{failures}\" errors=\"0\" skipped=\"0\">\n{cases}\n  </testsuite>\n</testsuites>\n",
+        tool = TOOL_NAME,
+        tests = tests,
+        failures = failures,
+        cases = cases.join("\n")
+    )
+}
+
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "note",
+    }
+}
+
+/// A relative URI reference for the name a dump was loaded from. Everything
+/// but unreserved characters and `/` is percent-encoded, so `<stdin>`, spaces
+/// and the `!` and `#` of archive members and multi-dump files stay valid.
+fn artifact_uri(name: &str) -> String {
+    let mut uri = String::with_capacity(name.len());
+    for byte in name.replace('\\', "/").bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            let _ = write!(uri, "%{:02X}", byte);
+        }
+    }
+    uri
+}
+
+/// `at java.base@17/java.lang.Object.wait(Native Method)` -> `java.lang.Object.wait`
+fn frame_function(line: &str) -> &str {
+    let frame = qualified_frame(line);
+    frame.split('(').next().unwrap_or(frame)
+}
+
+/// A SARIF 2.1.0 log with every deadlock, chronically blocked thread and
+/// anti-pattern finding of the report. Results that fail the check are
+/// errors; the others keep the level of their severity.
+///
+/// `sources` are the names the dumps were loaded from, in capture order.
+/// Every result is located in the latest dump, which shows the state the
+/// series ended in, with the threads and frames involved as logical locations.
+pub fn sarif(report: &AnalysisReport, result: &GateResult, sources: &[String]) -> Value {
+    let failed = |check: GateCheck, rule_id: &str| result.failures_of(check).any(|failure| failure.rule_id == rule_id);
+    let mut rules: BTreeMap<String, Value> = BTreeMap::new();
+    let mut results = Vec::new();
+    let locations = |logical: Vec<Value>| -> Value {
+        let mut location = json!({ "logicalLocations": logical });
+        if let Some(latest) = sources.len().checked_sub(1) {
+            location["physicalLocation"] = json!({
+                "artifactLocation": { "uri": artifact_uri(&sources[latest]), "index": latest },
+            });
+        }
+        json!([location])
+    };
+    let thread_location = |name: String| json!({ "name": name, "kind": "object" });
+
+    if !report.deadlocks.is_empty() {
+        rules.insert(
+            "deadlock".to_string(),
+            json!({
+                "id": "deadlock",
+                "shortDescription": { "text": "Deadlock" },
+                "fullDescription": { "text": "Threads wait for locks held by each other and can never proceed." },
+                "defaultConfiguration": { "level": "error" },
+            }),
+        );
+    }
+    for deadlock in &report.deadlocks {
+        results.push(json!({
+            "ruleId": "deadlock",
+            "level": "error",
+            "message": {
+                "text": format!(
+                    "Deadlock between {} (in {} of {} dumps)",
+                    join_threads(&deadlock.threads),
+                    deadlock.dumps,
+                    report.dumps.len()
+                )
+            },
+            "locations": locations(deadlock.threads.iter().map(|thread| thread_location(thread.to_string())).collect()),
+        }));
+    }
+
+    if !report.chronically_blocked.is_empty() {
+        rules.insert(
+            "chronic-blocking".to_string(),
+            json!({
+                "id": "chronic-blocking",
+                "shortDescription": { "text": "Chronically blocked thread" },
+                "fullDescription": { "text": "The thread was blocked in several dumps of the series." },
+                "defaultConfiguration": { "level": "warning" },
+            }),
+        );
+    }
+    let chronic_level = if failed(GateCheck::ChronicBlocking, "chronic-blocking") { "error" } else { "warning" };
+    for blocked in &report.chronically_blocked {
+        let top = blocked.thread.frames.first();
+        let frame = top.map_or("(no frames)", |frame| qualified_frame(&frame.line));
+        let mut logical = vec![thread_location(blocked.thread_ref().to_string())];
+        logical.extend(top.map(|frame| json!({ "fullyQualifiedName": frame_function(&frame.line), "kind": "function" })));
+        results.push(json!({
+            "ruleId": "chronic-blocking",
+            "level": chronic_level,
+            "message": {
+                "text": format!(
+                    "Thread '{}' was blocked in {} of {} dumps at {}",
+                    blocked.thread_ref(), blocked.blocked_dumps, blocked.total_dumps, frame
+                )
+            },
+            "locations": locations(logical),
+        }));
+    }
+
+    for finding in &report.findings {
+        let mut rule = json!({
+            "id": finding.rule_id,
+            "shortDescription": { "text": finding.title },
+            "fullDescription": { "text": finding.explanation },
+            "defaultConfiguration": { "level": sarif_level(finding.severity) },
+        });
+        if finding.remediation.starts_with("http") {
+            rule["helpUri"] = json!(finding.remediation);
+        }
+        rules.insert(finding.rule_id.clone(), rule);
+        let level = if failed(GateCheck::Findings, &finding.rule_id) { "error" } else { sarif_level(finding.severity) };
+        results.push(json!({
+            "ruleId": finding.rule_id,
+            "level": level,
+            "message": {
+                "text": format!(
+                    "{}: {} (in {} of {} dumps)",
+                    finding.title,
+                    finding.threads.join(", "),
+                    finding.dumps,
+                    finding.total_dumps
+                )
+            },
+            "locations": locations(finding.threads.iter().map(|name| thread_location(name.clone())).collect()),
+        }));
+    }
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": TOOL_NAME,
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules.into_values().collect::<Vec<_>>(),
+                }
+            },
+            "artifacts": sources
+                .iter()
+                .map(|source| json!({ "location": { "uri": artifact_uri(source) } }))
+                .collect::<Vec<_>>(),
+            "results": results,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use thread_lens::analyzer::gate::{check_report, GateFailure};
+    use thread_lens::analyzer::report::analyze;
+    use thread_lens::antipatterns::RuleEngine;
+    use thread_lens::parser::parse_jstack_output;
+
+    #[test]
+    fn test_junit_xml() {
+        let result = GateResult {
+            checks: vec![GateCheck::Deadlocks, GateCheck::ChronicBlocking],
+            failures: vec![GateFailure {
+                check: GateCheck::Deadlocks,
+                rule_id: "deadlock".to_string(),
+                message: "Deadlock between A & B <x>".to_string(),
+            }],
+        };
+        let xml = junit_xml(&result);
+        assert!(xml.contains("<testsuites name=\"thread-lens\" tests=\"2\" failures=\"1\">"));
+        assert!(xml.contains("<failure type=\"deadlock\" message=\"Deadlock between A &amp; B &lt;x&gt;\"/>"));
+        assert!(xml.contains("<testcase classname=\"thread-lens.chronic-blocking\""));
+    }
+
+    #[test]
+    fn test_sarif_results_have_locations() {
+        let dir = "../thread-lens/src/test_data/java-app-openjdk11_20250921085315_deadlock";
+        let mut paths: Vec<_> = fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        paths.sort();
+        let dumps: Vec<_> = paths
+            .iter()
+            .map(|path| parse_jstack_output(&fs::read_to_string(path).unwrap()).unwrap())
+            .collect();
+        let mut sources: Vec<String> = paths.iter().map(|path| path.display().to_string()).collect();
+        *sources.last_mut().unwrap() = "dumps/all in one.txt#10".to_string();
+
+        let report = analyze(&dumps, &RuleEngine::default(), &Default::default()).unwrap();
+        let log = sarif(&report, &check_report(&report, &Default::default()), &sources);
+        let run = &log["runs"][0];
+        assert_eq!(run["artifacts"].as_array().unwrap().len(), sources.len());
+
+        let results = run["results"].as_array().unwrap();
+        assert!(results.iter().any(|result| result["ruleId"] == "deadlock" && result["level"] == "error"));
+        for result in results {
+            let location = &result["locations"][0];
+            assert_eq!(location["physicalLocation"]["artifactLocation"]["uri"], "dumps/all%20in%20one.txt%2310");
+            assert!(!location["logicalLocations"].as_array().unwrap().is_empty());
+        }
+    }
+}
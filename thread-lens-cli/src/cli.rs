@@ -1,8 +1,31 @@
 use clap::{Parser, Subcommand, ValueEnum};
+use thread_lens::analyzer::chronic::ChronicBlockingOptions;
+use thread_lens::analyzer::filter::ThreadFilter;
+use thread_lens::analyzer::groups::SignatureOptions;
+use thread_lens::antipatterns::Severity;
+use thread_lens::export::flame::FlameWeight;
+use thread_lens::models::ThreadCategory;
+use thread_lens::query::Query;
+
+use crate::markdown::MIN_MAX_CHARS;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
+    /// Frame classification rules (TOML or YAML). Defaults to ./.thread-lens.toml,
+    /// then $XDG_CONFIG_HOME/thread-lens/config.toml
+    #[arg(long, global = true, value_name = "FILE")]
+    pub rules: Option<String>,
+
+    /// Package holding application code, e.g. com.mycorp (repeatable). Other
+    /// non-JDK frames are then treated as library code
+    #[arg(long = "app-package", global = true, value_name = "PACKAGE")]
+    pub app_packages: Vec<String>,
+
+    /// Don't guess application packages when none are configured
+    #[arg(long, global = true)]
+    pub no_infer_packages: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -11,15 +34,259 @@ pub struct Args {
 pub enum Commands {
     /// Analyze a directory of thread dumps for chronically blocked threads
     Analyze {
-        /// Path to a directory containing jstack files
-        #[arg(short, long)]
-        path: String,
+        /// Thread dump files, directories (searched recursively), glob patterns
+        /// or zip/tar/tar.gz archives. Reads standard input for `-` or when omitted
+        #[arg(short, long = "path", value_name = "PATH", num_args = 1..)]
+        paths: Vec<String>,
+
+        #[command(flatten)]
+        grouping: GroupingArgs,
+
+        #[command(flatten)]
+        chronic: ChronicArgs,
+
+        /// Known maximum size of a thread pool, as NAME=SIZE (repeatable)
+        #[arg(long = "pool-max", value_name = "NAME=SIZE", value_parser = parse_pool_max)]
+        pool_max: Vec<(String, usize)>,
+
+        /// Extra anti-pattern rules (TOML or YAML), added to the starter rules
+        #[arg(long, value_name = "FILE")]
+        patterns: Option<String>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = ReportFormat::Text)]
+        output: ReportFormat,
+
+        #[command(flatten)]
+        markdown: MarkdownArgs,
+    },
+    /// One-screen health verdict for a thread dump or a directory of them
+    Summary {
+        /// Thread dump files, directories (searched recursively), glob patterns
+        /// or zip/tar/tar.gz archives. Reads standard input for `-` or when omitted
+        #[arg(short, long = "path", value_name = "PATH", num_args = 1..)]
+        paths: Vec<String>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = DocumentFormat::Text)]
+        output: DocumentFormat,
+
+        /// Print the summary as JSON (same as --output json)
+        #[arg(long, conflicts_with = "output")]
+        json: bool,
+
+        #[command(flatten)]
+        markdown: MarkdownArgs,
+
+        /// Extra anti-pattern rules (TOML or YAML), added to the starter rules
+        #[arg(long, value_name = "FILE")]
+        patterns: Option<String>,
+    },
+    /// Save a baseline profile of a healthy dump series, or compare against one
+    Baseline {
+        #[command(subcommand)]
+        action: BaselineCommand,
     },
     /// View a single thread dump in a normalized format
     View {
-        /// Path to a single jstack file
+        /// Path to a single jstack file. Reads standard input for `-` or when omitted
         #[arg(short, long)]
-        path: String,
+        path: Option<String>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = DocumentFormat::Text)]
+        output: DocumentFormat,
+
+        #[command(flatten)]
+        markdown: MarkdownArgs,
+
+        #[command(flatten)]
+        grouping: GroupingArgs,
+
+        /// Show per-pool statistics instead of individual threads
+        #[arg(long, conflicts_with = "group")]
+        pools: bool,
+
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+    /// Show how each thread's state changed across a directory of thread dumps
+    Timeline {
+        /// Thread dump files, directories (searched recursively), glob patterns
+        /// or zip/tar/tar.gz archives. Reads standard input for `-` or when omitted
+        #[arg(short, long = "path", value_name = "PATH", num_args = 1..)]
+        paths: Vec<String>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+
+        /// Only show threads whose state changed during the series
+        #[arg(long)]
+        changed_only: bool,
+    },
+    /// Compare two thread dumps. With a single file, or none (standard input),
+    /// compares the first and last dump of the stream
+    Diff {
+        /// The earlier jstack file
+        before: Option<String>,
+
+        /// The later jstack file
+        after: Option<String>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = DocumentFormat::Text)]
+        output: DocumentFormat,
+
+        #[command(flatten)]
+        markdown: MarkdownArgs,
+    },
+    /// Fail (exit code 1) when a series has deadlocks, too many chronically
+    /// blocked threads or severe anti-patterns, for use in CI pipelines
+    Check {
+        /// Thread dump files, directories (searched recursively), glob patterns
+        /// or zip/tar/tar.gz archives. Reads standard input for `-` or when omitted
+        #[arg(short, long = "path", value_name = "PATH", num_args = 1..)]
+        paths: Vec<String>,
+
+        #[command(flatten)]
+        chronic: ChronicArgs,
+
+        /// Extra anti-pattern rules (TOML or YAML), added to the starter rules
+        #[arg(long, value_name = "FILE")]
+        patterns: Option<String>,
+
+        /// Do not fail when a deadlock is found
+        #[arg(long)]
+        allow_deadlocks: bool,
+
+        /// Fail when more than N threads are chronically blocked
+        #[arg(long, value_name = "N")]
+        max_chronic: Option<usize>,
+
+        /// Fail on anti-pattern findings at or above this severity
+        #[arg(long, value_enum, value_name = "SEVERITY")]
+        fail_on_severity: Option<SeverityArg>,
+
+        /// Also write the results as JUnit XML to this file
+        #[arg(long, value_name = "FILE")]
+        junit: Option<String>,
+
+        /// Also write the results as SARIF to this file
+        #[arg(long, value_name = "FILE")]
+        sarif: Option<String>,
+    },
+    /// Render a self-contained HTML report for postmortems and sharing
+    Report {
+        /// Thread dump files, directories (searched recursively), glob patterns
+        /// or zip/tar/tar.gz archives. Reads standard input for `-` or when omitted
+        #[arg(short, long = "path", value_name = "PATH", num_args = 1..)]
+        paths: Vec<String>,
+
+        #[command(flatten)]
+        chronic: ChronicArgs,
+
+        /// Extra anti-pattern rules (TOML or YAML), added to the starter rules
+        #[arg(long, value_name = "FILE")]
+        patterns: Option<String>,
+
+        /// Write the report to this file instead of standard output
+        #[arg(long = "out", value_name = "FILE")]
+        out: Option<String>,
+    },
+    /// Export the stacks of all threads and dumps as a flame graph
+    Flame {
+        /// Thread dump files, directories (searched recursively), glob patterns
+        /// or zip/tar/tar.gz archives. Reads standard input for `-` or when omitted
+        #[arg(short, long = "path", value_name = "PATH", num_args = 1..)]
+        paths: Vec<String>,
+
+        /// Output format: collapsed stacks (flamegraph.pl, inferno), speedscope JSON or an SVG
+        #[arg(short, long, value_enum, default_value_t = FlameFormat::Collapsed)]
+        output: FlameFormat,
+
+        /// Weight stacks by occurrences, or by CPU time used since the previous dump
+        #[arg(long, value_enum, default_value_t = FlameWeightArg::Occurrences)]
+        weight: FlameWeightArg,
+
+        /// Put each thread's state at the root of its stack
+        #[arg(long)]
+        by_state: bool,
+
+        /// Only threads of this category (repeatable)
+        #[arg(long = "category", value_enum)]
+        categories: Vec<CategoryArg>,
+
+        /// Write to this file instead of standard output
+        #[arg(long = "out", value_name = "FILE")]
+        out: Option<String>,
+    },
+    /// Export the lock wait-for graph as Graphviz DOT or Mermaid
+    Graph {
+        /// Thread dump files, directories (searched recursively), glob patterns
+        /// or zip/tar/tar.gz archives. Reads standard input for `-` or when omitted
+        #[arg(short, long = "path", value_name = "PATH", num_args = 1..)]
+        paths: Vec<String>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = GraphFormat::Dot)]
+        output: GraphFormat,
+
+        /// Only graph the latest dump instead of merging the whole series
+        #[arg(long)]
+        latest: bool,
+
+        /// Write to this file instead of standard output
+        #[arg(long = "out", value_name = "FILE")]
+        out: Option<String>,
+    },
+    /// Search threads with a query, e.g. 'state = BLOCKED and frame ~ mycorp count by pool'
+    Query {
+        /// The query; see the README for fields and operators
+        #[arg(value_parser = parse_query)]
+        query: Query,
+
+        /// Thread dump files, directories (searched recursively), glob patterns
+        /// or zip/tar/tar.gz archives. Reads standard input for `-` or when omitted
+        paths: Vec<String>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BaselineCommand {
+    /// Profile a known-good series and write it as a baseline file
+    Save {
+        /// Thread dump files, directories (searched recursively), glob patterns
+        /// or zip/tar/tar.gz archives. Reads standard input for `-` or when omitted
+        #[arg(short, long = "path", value_name = "PATH", num_args = 1..)]
+        paths: Vec<String>,
+
+        /// File to write the baseline (JSON) to
+        #[arg(long, value_name = "FILE")]
+        baseline: String,
+    },
+    /// Report significant deviations of a series from a saved baseline
+    Compare {
+        /// Thread dump files, directories (searched recursively), glob patterns
+        /// or zip/tar/tar.gz archives. Reads standard input for `-` or when omitted
+        #[arg(short, long = "path", value_name = "PATH", num_args = 1..)]
+        paths: Vec<String>,
+
+        /// Baseline file written by `baseline save`
+        #[arg(long, value_name = "FILE")]
+        baseline: String,
+
+        /// Minimum change, in baseline standard deviations
+        #[arg(long, value_name = "Z", default_value_t = 3.0)]
+        min_z_score: f64,
+
+        /// Minimum change, in threads per dump
+        #[arg(long, value_name = "THREADS", default_value_t = 2.0)]
+        min_change: f64,
 
         /// Output format
         #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
@@ -27,9 +294,275 @@ pub enum Commands {
     },
 }
 
+#[derive(clap::Args, Debug)]
+pub struct GroupingArgs {
+    /// Collapse threads with identical stacks into one entry
+    #[arg(short, long)]
+    pub group: bool,
+
+    /// Ignore line numbers when comparing stacks (with --group)
+    #[arg(long, requires = "group")]
+    pub ignore_lines: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct MarkdownArgs {
+    /// Cut Markdown output to at most N characters, e.g. 4000 for a chat message
+    #[arg(long, value_name = "N", value_parser = parse_max_chars)]
+    pub max_chars: Option<usize>,
+}
+
+/// Which threads `view` shows. Filters combine: a thread must pass all of them.
+#[derive(clap::Args, Debug)]
+pub struct FilterArgs {
+    /// Only threads in this state (repeatable)
+    #[arg(long = "state", value_name = "STATE")]
+    pub states: Vec<String>,
+
+    /// Only threads of this category (repeatable)
+    #[arg(long = "category", value_enum)]
+    pub categories: Vec<CategoryArg>,
+
+    /// Only threads whose name matches this regex (repeatable)
+    #[arg(long = "name", value_name = "REGEX", value_parser = parse_regex)]
+    pub names: Vec<String>,
+
+    /// Only workers of this thread pool, as listed by --pools (repeatable)
+    #[arg(long = "pool", id = "pool", value_name = "POOL")]
+    pub pools: Vec<String>,
+
+    /// Only threads with a frame matching this regex, e.g. HikariPool (repeatable; all must match)
+    #[arg(long = "frame", value_name = "REGEX", value_parser = parse_regex)]
+    pub frames: Vec<String>,
+
+    /// Only threads holding a lock whose address or class matches this regex
+    #[arg(long, value_name = "LOCK", value_parser = parse_regex)]
+    pub holding: Vec<String>,
+
+    /// Only threads waiting for a lock whose address or class matches this regex
+    #[arg(long, value_name = "LOCK", value_parser = parse_regex)]
+    pub waiting_on: Vec<String>,
+
+    /// Show at most this many frames per thread
+    #[arg(long, value_name = "N")]
+    pub max_depth: Option<usize>,
+}
+
+/// Which threads count as chronically blocked.
+#[derive(clap::Args, Debug)]
+pub struct ChronicArgs {
+    /// Minimum number of dumps a thread must be blocked in
+    #[arg(long, value_name = "N", default_value_t = 2)]
+    pub min_blocked_dumps: usize,
+
+    /// Minimum share of dumps (0.0-1.0) a thread must be blocked in
+    #[arg(long, value_name = "RATIO", default_value_t = 0.0, value_parser = parse_ratio)]
+    pub min_ratio: f64,
+
+    /// Minimum number of consecutive dumps a thread must stay blocked
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    pub min_consecutive: usize,
+
+    /// Minimum capture time covered by the longest blocked streak, e.g. 30s, 5m, 1h
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration_secs)]
+    pub min_duration: Option<i64>,
+
+    /// Thread category to include (repeatable)
+    #[arg(long = "category", value_enum, default_value = "application")]
+    pub categories: Vec<CategoryArg>,
+
+    /// Thread state counted as blocked (repeatable)
+    #[arg(long = "state", default_value = "BLOCKED")]
+    pub states: Vec<String>,
+
+    /// Only consider threads whose name matches this regex (repeatable)
+    #[arg(long = "include-name", value_name = "REGEX", value_parser = parse_regex)]
+    pub include_names: Vec<String>,
+
+    /// Skip threads whose name matches this regex (repeatable)
+    #[arg(long = "exclude-name", value_name = "REGEX", value_parser = parse_regex)]
+    pub exclude_names: Vec<String>,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+pub enum CategoryArg {
+    Application,
+    Framework,
+    Jvm,
+    Unknown,
+}
+
+impl CategoryArg {
+    pub fn thread_category(&self) -> ThreadCategory {
+        match self {
+            CategoryArg::Application => ThreadCategory::Application,
+            CategoryArg::Framework => ThreadCategory::Framework,
+            CategoryArg::Jvm => ThreadCategory::Jvm,
+            CategoryArg::Unknown => ThreadCategory::Unknown,
+        }
+    }
+}
+
 #[derive(ValueEnum, Clone, Debug)]
 pub enum OutputFormat {
     Text,
     Json,
     Yaml,
 }
+
+/// Formats of the commands that can also render Markdown for tickets and chat.
+#[derive(ValueEnum, Clone, Debug)]
+pub enum DocumentFormat {
+    Text,
+    Json,
+    Yaml,
+    Markdown,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+pub enum FlameFormat {
+    Collapsed,
+    Speedscope,
+    Svg,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+pub enum FlameWeightArg {
+    Occurrences,
+    Cpu,
+}
+
+impl FlameWeightArg {
+    pub fn flame_weight(&self) -> FlameWeight {
+        match self {
+            FlameWeightArg::Occurrences => FlameWeight::Occurrences,
+            FlameWeightArg::Cpu => FlameWeight::CpuDelta,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+pub enum SeverityArg {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl SeverityArg {
+    pub fn severity(&self) -> Severity {
+        match self {
+            SeverityArg::Info => Severity::Info,
+            SeverityArg::Warning => Severity::Warning,
+            SeverityArg::Critical => Severity::Critical,
+        }
+    }
+}
+
+/// Formats of the `analyze` report, which can also be streamed as one JSON
+/// record per line.
+#[derive(ValueEnum, Clone, Debug)]
+pub enum ReportFormat {
+    Text,
+    Json,
+    Yaml,
+    Ndjson,
+    Markdown,
+}
+
+impl GroupingArgs {
+    pub fn signature_options(&self) -> SignatureOptions {
+        SignatureOptions {
+            ignore_line_numbers: self.ignore_lines,
+            ..Default::default()
+        }
+    }
+}
+
+impl FilterArgs {
+    pub fn thread_filter(&self) -> ThreadFilter {
+        ThreadFilter {
+            states: self.states.clone(),
+            categories: self.categories.iter().map(CategoryArg::thread_category).collect(),
+            names: self.names.clone(),
+            pools: self.pools.clone(),
+            frames: self.frames.clone(),
+            holding: self.holding.clone(),
+            waiting_on: self.waiting_on.clone(),
+            max_depth: self.max_depth,
+        }
+    }
+}
+
+impl ChronicArgs {
+    pub fn chronic_options(&self) -> ChronicBlockingOptions {
+        ChronicBlockingOptions {
+            min_blocked_dumps: self.min_blocked_dumps,
+            min_ratio: self.min_ratio,
+            min_consecutive: self.min_consecutive,
+            min_duration_secs: self.min_duration.unwrap_or(0),
+            categories: self.categories.iter().map(CategoryArg::thread_category).collect(),
+            states: self.states.iter().map(|state| state.to_uppercase()).collect(),
+            include_names: self.include_names.clone(),
+            exclude_names: self.exclude_names.clone(),
+        }
+    }
+}
+
+fn parse_ratio(value: &str) -> Result<f64, String> {
+    let ratio: f64 = value.parse().map_err(|e| format!("invalid ratio '{}': {}", value, e))?;
+    if (0.0..=1.0).contains(&ratio) {
+        Ok(ratio)
+    } else {
+        Err(format!("ratio must be between 0.0 and 1.0, got {}", ratio))
+    }
+}
+
+fn parse_max_chars(value: &str) -> Result<usize, String> {
+    let max_chars: usize = value.parse().map_err(|e| format!("invalid character count '{}': {}", value, e))?;
+    if max_chars >= MIN_MAX_CHARS {
+        Ok(max_chars)
+    } else {
+        Err(format!("must be at least {} to leave room for the truncation notice", MIN_MAX_CHARS))
+    }
+}
+
+fn parse_duration_secs(value: &str) -> Result<i64, String> {
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| format!("expected a duration like 30s, 5m or 1h, got '{}'", value))?;
+    match unit {
+        "" | "s" => Ok(amount),
+        "m" => Ok(amount * 60),
+        "h" => Ok(amount * 3600),
+        _ => Err(format!("unknown duration unit '{}' (use s, m or h)", unit)),
+    }
+}
+
+fn parse_query(value: &str) -> Result<Query, String> {
+    Query::parse(value)
+}
+
+fn parse_regex(value: &str) -> Result<String, String> {
+    regex::Regex::new(value)
+        .map(|_| value.to_string())
+        .map_err(|e| e.to_string())
+}
+
+fn parse_pool_max(value: &str) -> Result<(String, usize), String> {
+    let (name, size) = value
+        .rsplit_once('=')
+        .ok_or_else(|| format!("expected NAME=SIZE, got '{}'", value))?;
+    let size = size
+        .parse()
+        .map_err(|e| format!("invalid pool size '{}': {}", size, e))?;
+    Ok((name.to_string(), size))
+}
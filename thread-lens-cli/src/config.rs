@@ -0,0 +1,99 @@
+use std::env;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use thread_lens::analyzer::packages::infer_app_packages;
+use thread_lens::antipatterns::RuleEngine;
+use thread_lens::classifier::Classifier;
+use thread_lens::models::ThreadDump;
+
+const LOCAL_CONFIG_FILES: &[&str] = &[".thread-lens.toml", ".thread-lens.yaml", ".thread-lens.yml"];
+const USER_CONFIG_FILES: &[&str] = &["config.toml", "config.yaml", "config.yml"];
+
+/// The first rules file found in the working directory, then under
+/// `$XDG_CONFIG_HOME/thread-lens/` (or `~/.config/thread-lens/`).
+fn find_config_file() -> Option<PathBuf> {
+    let user_dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .map(|dir| dir.join("thread-lens"));
+
+    let local = LOCAL_CONFIG_FILES.iter().map(PathBuf::from);
+    let user = user_dir
+        .into_iter()
+        .flat_map(|dir| USER_CONFIG_FILES.iter().map(move |name| dir.join(name)));
+    local.chain(user).find(|path| path.is_file())
+}
+
+/// The classifier used for parsing, and whether application packages may be
+/// inferred from the dumps when none are configured.
+pub struct Classification {
+    pub classifier: Classifier,
+    pub infer_app_packages: bool,
+}
+
+impl Classification {
+    /// Infers application packages from the parsed dumps, prints them so they
+    /// can be pinned in the config, and classifies the dumps again with them.
+    pub fn apply_inferred_app_packages(&self, dumps: &mut [ThreadDump]) {
+        if !self.infer_app_packages || !self.classifier.app_packages().is_empty() {
+            return;
+        }
+        let packages = infer_app_packages(dumps);
+        if packages.is_empty() {
+            return;
+        }
+        eprintln!(
+            "Inferred application packages: {} (pin with --app-package or app_packages in .thread-lens.toml)",
+            packages.join(", ")
+        );
+        let classifier = self.classifier.clone().with_app_packages(packages);
+        for dump in dumps.iter_mut() {
+            classifier.reclassify(dump);
+        }
+    }
+}
+
+/// Builds the frame classifier from `--rules`, a discovered config file, or the
+/// built-in rules when there is neither, plus any `--app-package` prefixes.
+pub fn load_classifier(rules: Option<&str>, app_packages: Vec<String>) -> std::io::Result<Classifier> {
+    let classifier = match rules.map(PathBuf::from).or_else(find_config_file) {
+        Some(path) => read_rules_file(&path)?,
+        None => Classifier::default(),
+    };
+    Ok(classifier.with_app_packages(app_packages))
+}
+
+fn read_rules_file(path: &Path) -> std::io::Result<Classifier> {
+    log::info!("Loading classification rules from {}", path.display());
+
+    let content = fs::read_to_string(path)?;
+    let is_yaml = path.extension().is_some_and(|ext| ext == "yaml" || ext == "yml");
+    let classifier = if is_yaml {
+        Classifier::from_yaml(&content)
+    } else {
+        Classifier::from_toml(&content)
+    };
+    classifier.map_err(|e| Error::new(ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))
+}
+
+/// The starter anti-pattern rules, plus those in `--patterns` if given.
+pub fn load_rule_engine(patterns: Option<&str>) -> std::io::Result<RuleEngine> {
+    let engine = RuleEngine::default();
+    let Some(path) = patterns.map(Path::new) else {
+        return Ok(engine);
+    };
+    log::info!("Loading anti-pattern rules from {}", path.display());
+
+    let content = fs::read_to_string(path)?;
+    let is_yaml = path.extension().is_some_and(|ext| ext == "yaml" || ext == "yml");
+    let extra = if is_yaml {
+        RuleEngine::from_yaml(&content)
+    } else {
+        RuleEngine::from_toml(&content)
+    };
+    extra
+        .map(|extra| engine.with_rules(extra))
+        .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))
+}
@@ -0,0 +1,590 @@
+use std::collections::HashSet;
+use std::f64::consts::PI;
+use std::fmt::Write;
+use thread_lens::analyzer::blockers::{build_wait_for_graph, join_threads, Deadlock, ThreadRef, WaitForEdge};
+use thread_lens::analyzer::groups::{group_by_stack, SignatureOptions};
+use thread_lens::analyzer::{in_time_order, thread_ids};
+use thread_lens::analyzer::leaks::{GrowthScope, LeakReport};
+use thread_lens::analyzer::report::AnalysisReport;
+use thread_lens::analyzer::summary::Summary;
+use thread_lens::analyzer::timeline::Timeline;
+use thread_lens::export::xml_escape;
+use thread_lens::models::{CategorizedFrame, FrameCategory, NormalizedThread, ThreadDump};
+
+use crate::output::describe_growth;
+
+/// Graphs with more threads than this are cut down to the most waited-on ones.
+const MAX_GRAPH_NODES: usize = 40;
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 0 auto; max-width: 1200px; padding: 1em 2em; color: #222; }
+h1 { margin-bottom: 0.2em; }
+h2 { border-bottom: 1px solid #ddd; padding-bottom: 0.2em; margin-top: 1.6em; }
+.muted { color: #777; }
+table { border-collapse: collapse; margin: 0.5em 0; }
+th, td { border: 1px solid #ddd; padding: 0.2em 0.6em; text-align: left; vertical-align: top; }
+th { background: #f5f5f5; }
+pre { background: #f7f7f7; padding: 0.5em; overflow-x: auto; margin: 0.3em 0; }
+.app { font-weight: bold; color: #0b5394; }
+.sev-Critical { color: #b00020; font-weight: bold; }
+.sev-Warning { color: #b36b00; font-weight: bold; }
+.sev-Info { color: #555; font-weight: bold; }
+.score { font-size: 2em; font-weight: bold; }
+details { margin: 0.2em 0; }
+summary { cursor: pointer; }
+.timeline td.cell { width: 1.4em; text-align: center; font-family: monospace; padding: 0.1em; }
+.s-R { background: #b7e1b0; } .s-B { background: #f4a3a3; } .s-W { background: #f9d98c; }
+.s-T { background: #c9daf8; } .s-other { background: #ddd; } .s-absent { background: #fff; color: #bbb; }
+#search { width: 100%; padding: 0.4em; font-size: 1em; box-sizing: border-box; }
+.hidden { display: none; }
+svg text { font-size: 11px; }
+"#;
+
+const SCRIPT: &str = r#"
+(function () {
+  var input = document.getElementById('search');
+  var count = document.getElementById('search-count');
+  var items = document.querySelectorAll('.searchable');
+  input.addEventListener('input', function () {
+    var query = input.value.trim().toLowerCase();
+    var shown = 0;
+    items.forEach(function (item) {
+      var match = !query || item.textContent.toLowerCase().indexOf(query) >= 0;
+      item.classList.toggle('hidden', !match);
+      item.open = !!query && match;
+      if (match) { shown++; }
+    });
+    count.textContent = query ? shown + ' of ' + items.length + ' stack groups and threads match' : '';
+  });
+})();
+"#;
+
+fn frames_html(frames: &[CategorizedFrame]) -> String {
+    let lines: Vec<String> = frames
+        .iter()
+        .map(|frame| {
+            let line = xml_escape(frame.line.trim());
+            if frame.category == FrameCategory::Application {
+                format!("<span class=\"app\">{}</span>", line)
+            } else {
+                line
+            }
+        })
+        .collect();
+    format!("<pre>{}</pre>", lines.join("\n"))
+}
+
+/// A self-contained HTML page for the series: styles and the search script are
+/// inline, and the graph is drawn as inline SVG. The sections show the same
+/// results as `summary`, `analyze`, `timeline` and `group`.
+pub fn render_html(dumps: &[ThreadDump], report: &AnalysisReport, summary: &Summary, timeline: &Timeline) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>thread-lens report</title>\n");
+    let _ = writeln!(html, "<style>{}</style>\n</head>\n<body>", STYLE);
+    html.push_str("<h1>Thread Dump Report</h1>\n");
+    let _ = writeln!(
+        html,
+        "<p class=\"muted\">{} dumps{} &middot; generated by thread-lens {}</p>",
+        report.dumps.len(),
+        match (report.dumps.first(), report.dumps.last()) {
+            (Some(first), Some(last)) => format!(" from {} to {}", first.timestamp, last.timestamp),
+            _ => String::new(),
+        },
+        env!("CARGO_PKG_VERSION")
+    );
+
+    write_summary(&mut html, summary);
+    write_findings(&mut html, report);
+    write_blocking(&mut html, report, dumps);
+    write_leaks(&mut html, &report.leaks);
+    write_timeline(&mut html, timeline);
+
+    html.push_str("<h2>Search</h2>\n<input id=\"search\" type=\"search\" placeholder=\"Search frames, thread names, states or locks\">\n<p id=\"search-count\" class=\"muted\"></p>\n");
+    if let Some(latest) = in_time_order(dumps).last() {
+        write_groups(&mut html, latest);
+        write_threads(&mut html, latest);
+    }
+
+    let _ = writeln!(html, "<script>{}</script>\n</body>\n</html>", SCRIPT);
+    html
+}
+
+fn write_summary(html: &mut String, summary: &Summary) {
+    html.push_str("<h2>Summary</h2>\n");
+    let _ = writeln!(html, "<p><span class=\"score\">{}/100</span> health</p>", summary.health_score);
+    html.push_str("<table>\n");
+    let _ = writeln!(html, "<tr><th>Dumps</th><td>{}</td></tr>", summary.dumps);
+    let _ = writeln!(html, "<tr><th>Deadlock</th><td>{}</td></tr>", if summary.deadlocked { "YES" } else { "no" });
+    let states: Vec<String> = summary.states.iter().map(|(state, count)| format!("{}={}", state, count)).collect();
+    let _ = writeln!(html, "<tr><th>Threads</th><td>{} ({})</td></tr>", summary.threads, xml_escape(&states.join(", ")));
+    if summary.thread_counts.len() > 1 {
+        let counts: Vec<String> = summary.thread_counts.iter().map(|count| count.to_string()).collect();
+        let _ = writeln!(
+            html,
+            "<tr><th>Thread count trend</th><td>{} ({:+.1} per dump)</td></tr>",
+            counts.join(" &rarr; "),
+            summary.thread_trend_per_dump
+        );
+    }
+    if !summary.saturated_pools.is_empty() {
+        let _ = writeln!(html, "<tr><th>Saturated pools</th><td>{}</td></tr>", xml_escape(&summary.saturated_pools.join(", ")));
+    }
+    html.push_str("</table>\n");
+
+    if summary.findings.is_empty() {
+        html.push_str("<p>No problems found.</p>\n");
+        return;
+    }
+    html.push_str("<ol>\n");
+    for finding in &summary.findings {
+        let _ = writeln!(
+            html,
+            "<li><span class=\"sev-{severity:?}\">[{severity:?}]</span> {} <span class=\"muted\">&mdash; see {}</span></li>",
+            xml_escape(&finding.title),
+            xml_escape(finding.section.label()),
+            severity = finding.severity
+        );
+    }
+    html.push_str("</ol>\n");
+}
+
+fn write_findings(html: &mut String, report: &AnalysisReport) {
+    html.push_str("<h2>Known Anti-Patterns</h2>\n");
+    if report.findings.is_empty() {
+        html.push_str("<p>No known anti-patterns found.</p>\n");
+        return;
+    }
+    for finding in &report.findings {
+        let _ = writeln!(
+            html,
+            "<h3><span class=\"sev-{severity:?}\">[{severity:?}]</span> {} <span class=\"muted\">({}, in {} of {} dumps)</span></h3>",
+            xml_escape(&finding.title),
+            xml_escape(&finding.rule_id),
+            finding.dumps,
+            finding.total_dumps,
+            severity = finding.severity
+        );
+        let _ = writeln!(html, "<p>{}</p>", xml_escape(&finding.explanation));
+        let _ = writeln!(html, "<p><b>Threads:</b> {}</p>", xml_escape(&finding.threads.join(", ")));
+        let remediation = xml_escape(&finding.remediation);
+        if finding.remediation.starts_with("http") {
+            let _ = writeln!(html, "<p><b>See:</b> <a href=\"{0}\">{0}</a></p>", remediation);
+        } else {
+            let _ = writeln!(html, "<p><b>See:</b> {}</p>", remediation);
+        }
+    }
+}
+
+fn write_blocking(html: &mut String, report: &AnalysisReport, dumps: &[ThreadDump]) {
+    let total = report.dumps.len();
+    html.push_str("<h2>Blocking</h2>\n");
+    if report.deadlocks.is_empty() {
+        html.push_str("<p>No deadlocks found.</p>\n");
+    } else {
+        html.push_str("<ul>\n");
+        for deadlock in &report.deadlocks {
+            let _ = writeln!(
+                html,
+                "<li><b>Deadlock</b> between {} (in {} of {} dumps){}</li>",
+                xml_escape(&join_threads(&deadlock.threads)),
+                deadlock.dumps,
+                total,
+                if deadlock.blocked_threads.is_empty() {
+                    String::new()
+                } else {
+                    format!("; also blocked: {}", xml_escape(&join_threads(&deadlock.blocked_threads)))
+                }
+            );
+        }
+        html.push_str("</ul>\n");
+    }
+    if let Some(graph) = graph_dump(dumps, &report.deadlocks) {
+        let _ = writeln!(
+            html,
+            "<h3>Wait-for graph</h3>\n<p class=\"muted\">Dump at {}. An arrow points from a waiting thread to the thread holding the lock; deadlocked threads are red.</p>",
+            graph.dump.timestamp
+        );
+        html.push_str(&wait_for_svg(&graph));
+    }
+
+    if report.root_blockers.is_empty() {
+        html.push_str("<p>No blocked application threads could be traced to a lock holder.</p>\n");
+    } else {
+        html.push_str("<h3>Root blockers</h3>\n<table>\n<tr><th>Blocked</th><th>Root blocker</th><th>Frame</th><th>Dumps</th><th>Blocked threads</th></tr>\n");
+        for blocker in &report.root_blockers {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}{}{}</td><td><code>{}</code></td><td>{} of {}</td><td>{}</td></tr>",
+                blocker.blocked_threads.len(),
+                xml_escape(&blocker.thread.to_string()),
+                if blocker.deadlocked { " <b class=\"sev-Critical\">[DEADLOCK]</b>" } else { "" },
+                blocker
+                    .unresolved_note()
+                    .map(|note| format!("<br><span class=\"muted\">{}; the thread that releases it is not in the dump</span>", xml_escape(&note)))
+                    .unwrap_or_default(),
+                xml_escape(blocker.frame.as_deref().unwrap_or("(no frames)")),
+                blocker.dumps,
+                total,
+                xml_escape(&join_threads(&blocker.blocked_threads))
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    if !report.saturated_pools.is_empty() {
+        html.push_str("<h3>Saturated thread pools</h3>\n<table>\n<tr><th>Pool</th><th>Saturated in</th><th>Max size</th><th>Max busy</th></tr>\n");
+        for pool in &report.saturated_pools {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{} of {} dumps</td><td>{}</td><td>{}</td></tr>",
+                xml_escape(&pool.name),
+                pool.saturated_dumps,
+                pool.dumps_seen,
+                pool.max_size,
+                pool.max_busy
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    if report.chronically_blocked.is_empty() {
+        html.push_str("<p>No chronically blocked application threads found.</p>\n");
+        return;
+    }
+    let _ = writeln!(html, "<h3>{} chronically blocked application threads</h3>", report.chronically_blocked.len());
+    for blocked in &report.chronically_blocked {
+        let _ = writeln!(
+            html,
+            "<details><summary>{} <span class=\"muted\">(blocked in {} of {} dumps, {} in a row over {}s, score {:.2})</span></summary>\n{}</details>",
+            xml_escape(&blocked.thread_ref().to_string()),
+            blocked.blocked_dumps,
+            blocked.total_dumps,
+            blocked.longest_streak,
+            blocked.longest_streak_secs,
+            blocked.score,
+            frames_html(&blocked.thread.frames)
+        );
+    }
+}
+
+fn write_leaks(html: &mut String, leaks: &LeakReport) {
+    html.push_str("<h2>Thread Growth</h2>\n");
+    let _ = writeln!(
+        html,
+        "<p>Total threads: {}{}</p>",
+        describe_growth(&leaks.total),
+        if leaks.total.steady { " <b class=\"sev-Warning\">[GROWING]</b>" } else { "" }
+    );
+    if leaks.suspects.is_empty() {
+        html.push_str("<p>No steadily growing pools or creation stacks found.</p>\n");
+        return;
+    }
+    html.push_str("<ul>\n");
+    for growth in &leaks.suspects {
+        let scope = match &growth.scope {
+            GrowthScope::Total => continue,
+            GrowthScope::Pool(name) => format!("Pool '{}'", name),
+            GrowthScope::CreationStack(entry) => format!("Threads started from {}", entry),
+        };
+        let _ = write!(html, "<li>{}: {}", xml_escape(&scope), describe_growth(growth));
+        if let Some(stack) = &growth.sample_stack {
+            let lines: Vec<String> = stack.iter().take(10).map(|frame| xml_escape(frame)).collect();
+            let _ = write!(html, "<details><summary>Sample stack</summary><pre>{}</pre></details>", lines.join("\n"));
+        }
+        html.push_str("</li>\n");
+    }
+    html.push_str("</ul>\n");
+}
+
+/// The threads of `dump` that are in one of the `deadlocks` found by the
+/// analysis, by index.
+fn deadlocked_threads(dump: &ThreadDump, deadlocks: &[Deadlock]) -> HashSet<usize> {
+    thread_ids(dump)
+        .into_iter()
+        .enumerate()
+        .filter(|(index, id)| {
+            let thread = ThreadRef {
+                name: dump.threads[*index].name.clone(),
+                id: id.clone(),
+            };
+            deadlocks.iter().any(|deadlock| deadlock.threads.contains(&thread))
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// The lock waits of one dump, and which of its threads are deadlocked.
+struct WaitForGraph<'a> {
+    dump: &'a ThreadDump,
+    edges: Vec<WaitForEdge>,
+    deadlocked: HashSet<usize>,
+}
+
+/// The latest dump in which a deadlocked thread waits, or else the latest dump
+/// with any lock waits.
+fn graph_dump<'a>(dumps: &'a [ThreadDump], deadlocks: &[Deadlock]) -> Option<WaitForGraph<'a>> {
+    let graphs: Vec<WaitForGraph> = in_time_order(dumps)
+        .into_iter()
+        .rev()
+        .map(|dump| WaitForGraph {
+            dump,
+            edges: build_wait_for_graph(dump),
+            deadlocked: deadlocked_threads(dump, deadlocks),
+        })
+        .filter(|graph| !graph.edges.is_empty())
+        .collect();
+    let cyclic = graphs
+        .iter()
+        .position(|graph| graph.edges.iter().any(|edge| graph.deadlocked.contains(&edge.waiter)));
+    graphs.into_iter().nth(cyclic.unwrap_or(0))
+}
+
+fn wait_for_svg(graph: &WaitForGraph) -> String {
+    let (dump, edges, cyclic) = (graph.dump, &graph.edges, &graph.deadlocked);
+    // Keep the deadlocked threads, then the most waited-on holders and their waiters.
+    let mut nodes: Vec<usize> = Vec::new();
+    for edge in edges {
+        if cyclic.contains(&edge.waiter) && !nodes.contains(&edge.waiter) {
+            nodes.push(edge.waiter);
+        }
+    }
+    let mut holders: Vec<usize> = edges.iter().map(|edge| edge.holder).collect();
+    holders.sort_unstable();
+    holders.dedup();
+    holders.sort_by_key(|&holder| std::cmp::Reverse(edges.iter().filter(|edge| edge.holder == holder).count()));
+    for holder in holders {
+        for node in std::iter::once(holder).chain(edges.iter().filter(|edge| edge.holder == holder).map(|edge| edge.waiter)) {
+            if nodes.len() < MAX_GRAPH_NODES && !nodes.contains(&node) {
+                nodes.push(node);
+            }
+        }
+    }
+    let size = 640.0;
+    let center = size / 2.0;
+    let radius = if nodes.len() > 1 { center - 110.0 } else { 0.0 };
+    let position = |slot: usize| {
+        let angle = 2.0 * PI * slot as f64 / nodes.len() as f64 - PI / 2.0;
+        (center + radius * angle.cos(), center + radius * angle.sin())
+    };
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">",
+        size
+    );
+    svg.push_str("<defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" markerWidth=\"8\" markerHeight=\"8\" orient=\"auto-start-reverse\"><path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"#555\"/></marker></defs>\n");
+    let node_radius = 8.0;
+    for edge in edges {
+        let (Some(from), Some(to)) = (
+            nodes.iter().position(|&node| node == edge.waiter),
+            nodes.iter().position(|&node| node == edge.holder),
+        ) else {
+            continue;
+        };
+        let ((x1, y1), (x2, y2)) = (position(from), position(to));
+        let length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt().max(1.0);
+        let (dx, dy) = ((x2 - x1) / length * node_radius, (y2 - y1) / length * node_radius);
+        let colour = if cyclic.contains(&edge.waiter) && cyclic.contains(&edge.holder) { "#b00020" } else { "#555" };
+        let _ = writeln!(
+            svg,
+            "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\" stroke-width=\"1.5\" marker-end=\"url(#arrow)\"><title>waits for &lt;{}&gt; (a {})</title></line>",
+            x1 + dx,
+            y1 + dy,
+            x2 - dx,
+            y2 - dy,
+            colour,
+            xml_escape(&edge.lock.address),
+            xml_escape(&edge.lock.class_name)
+        );
+    }
+    for (slot, &node) in nodes.iter().enumerate() {
+        let thread = &dump.threads[node];
+        // Tell apart threads that share a name, e.g. `worker #2`.
+        let same_name: Vec<usize> = nodes.iter().copied().filter(|&other| dump.threads[other].name == thread.name).collect();
+        let label = match same_name.iter().position(|&other| other == node) {
+            Some(position) if same_name.len() > 1 => format!("{} #{}", thread.name, position + 1),
+            _ => thread.name.clone(),
+        };
+        let (x, y) = position(slot);
+        let fill = if cyclic.contains(&node) { "#f4a3a3" } else if thread.state == "BLOCKED" { "#f9d98c" } else { "#b7e1b0" };
+        let anchor = if x < center - 1.0 { "end" } else if x > center + 1.0 { "start" } else { "middle" };
+        let offset = match anchor {
+            "end" => -12.0,
+            "start" => 12.0,
+            _ => 0.0,
+        };
+        let _ = writeln!(
+            svg,
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{}\" fill=\"{}\" stroke=\"#333\"><title>{} ({}, tid {})</title></circle>\n<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"{}\">{}</text>",
+            x,
+            y,
+            node_radius,
+            fill,
+            xml_escape(&thread.name),
+            xml_escape(&thread.state),
+            xml_escape(thread.id.as_deref().unwrap_or("unknown")),
+            x + offset,
+            y + if anchor == "middle" { -12.0 } else { 4.0 },
+            anchor,
+            xml_escape(&label)
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn write_timeline(html: &mut String, timeline: &Timeline) {
+    html.push_str("<h2>State Timeline</h2>\n");
+    if timeline.timestamps.is_empty() {
+        html.push_str("<p>No dumps found.</p>\n");
+        return;
+    }
+    html.push_str("<p class=\"muted\">Legend: R=RUNNABLE B=BLOCKED W=WAITING T=TIMED_WAITING ?=other .=absent. Hover a cell for the top application frame.</p>\n");
+    html.push_str("<table class=\"timeline\">\n<tr><th>Thread</th>");
+    for (index, timestamp) in timeline.timestamps.iter().enumerate() {
+        let _ = write!(html, "<th title=\"{}\">{}</th>", timestamp, index + 1);
+    }
+    html.push_str("</tr>\n");
+    for thread in &timeline.threads {
+        let _ = write!(html, "<tr><td>{}</td>", xml_escape(&thread.thread));
+        for (step, code) in thread.steps.iter().zip(thread.state_strip().chars()) {
+            let class = match code {
+                'R' | 'B' | 'W' | 'T' => format!("s-{}", code),
+                '.' => "s-absent".to_string(),
+                _ => "s-other".to_string(),
+            };
+            let title = match (&step.state, &step.top_app_frame) {
+                (Some(state), Some(frame)) => format!("{} {}", state, frame.trim()),
+                (Some(state), None) => state.clone(),
+                (None, _) => "absent".to_string(),
+            };
+            let _ = write!(html, "<td class=\"cell {}\" title=\"{}\">{}</td>", class, xml_escape(&title), code);
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+}
+
+fn write_groups(html: &mut String, dump: &ThreadDump) {
+    let _ = writeln!(html, "<h2>Stack Groups</h2>\n<p class=\"muted\">Latest dump, taken at {}.</p>", dump.timestamp);
+    for group in group_by_stack(dump, &SignatureOptions::default()) {
+        let states: Vec<String> = group.states.iter().map(|(state, count)| format!("{}={}", state, count)).collect();
+        let _ = writeln!(
+            html,
+            "<details class=\"searchable\"><summary><b>{} threads</b> {} <span class=\"muted\">({})</span></summary>\n{}</details>",
+            group.count,
+            xml_escape(&group.name_summary()),
+            xml_escape(&states.join(", ")),
+            frames_html(&group.frames)
+        );
+    }
+}
+
+fn write_thread(html: &mut String, thread: &NormalizedThread) {
+    let _ = writeln!(
+        html,
+        "<details class=\"searchable\"><summary>{} <span class=\"muted\">{} &middot; {:?}</span></summary>",
+        xml_escape(&thread.name),
+        xml_escape(&thread.state),
+        thread.category
+    );
+    if !thread.locks.is_empty() {
+        html.push_str("<ul>\n");
+        for lock in &thread.locks {
+            let _ = writeln!(
+                html,
+                "<li>{:?} &lt;{}&gt; (a {})</li>",
+                lock.action,
+                xml_escape(&lock.address),
+                xml_escape(&lock.class_name)
+            );
+        }
+        html.push_str("</ul>\n");
+    }
+    html.push_str(&frames_html(&thread.frames));
+    html.push_str("</details>\n");
+}
+
+fn write_threads(html: &mut String, dump: &ThreadDump) {
+    let _ = writeln!(html, "<h2>Threads</h2>\n<p class=\"muted\">{} threads in the latest dump.</p>", dump.threads.len());
+    for thread in &dump.threads {
+        write_thread(html, thread);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use thread_lens::analyzer::report::analyze;
+    use thread_lens::analyzer::summary::summarize;
+    use thread_lens::analyzer::timeline::build_timeline;
+    use thread_lens::antipatterns::RuleEngine;
+    use thread_lens::parser::parse_jstack_output;
+
+    fn render_deadlock_series() -> (Vec<ThreadDump>, String) {
+        let dir = "../thread-lens/src/test_data/java-app-openjdk11_20250921085315_deadlock";
+        let mut paths: Vec<_> = fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        paths.sort();
+        let mut dumps: Vec<ThreadDump> = paths
+            .iter()
+            .map(|path| parse_jstack_output(&fs::read_to_string(path).unwrap()).unwrap())
+            .collect();
+        // A name that needs escaping, in the threads, the graph and the findings
+        for thread in dumps.iter_mut().flat_map(|dump| dump.threads.iter_mut()) {
+            if thread.name == "DeadlockThread-1" {
+                thread.name = "Deadlock<Thread>&1".to_string();
+            }
+        }
+
+        let engine = RuleEngine::default();
+        let report = analyze(&dumps, &engine, &Default::default()).unwrap();
+        let html = render_html(&dumps, &report, &summarize(&dumps, &engine), &build_timeline(&dumps));
+        (dumps, html)
+    }
+
+    #[test]
+    fn test_render_html_is_self_contained_and_escaped() {
+        let (_, html) = render_deadlock_series();
+        assert!(!html.contains("<script src"));
+        assert!(!html.contains("<link"));
+        assert!(!html.contains("src="));
+        assert!(!html.contains("@import"));
+        // Only the SVG arrow marker, defined in the page itself
+        assert_eq!(html.matches("url(").count(), html.matches("url(#arrow)").count());
+        assert_eq!(html.matches("<script>").count(), 1);
+        assert!(html.contains(SCRIPT));
+
+        assert!(!html.contains("Deadlock<Thread>&1"));
+        assert!(html.contains("Deadlock&lt;Thread&gt;&amp;1"));
+        assert!(html.contains("[Critical]</span> Deadlock between Deadlock&lt;Thread&gt;&amp;1 (0x0000e7a05c004000), "));
+    }
+
+    #[test]
+    fn test_render_html_highlights_deadlock_and_lists_every_thread() {
+        let (dumps, html) = render_deadlock_series();
+
+        let svg = &html[html.find("<svg").unwrap()..html.find("</svg>").unwrap()];
+        let deadlocked: Vec<&str> = svg
+            .split("<circle ")
+            .filter(|node| node.contains("fill=\"#f4a3a3\""))
+            .map(|node| &node[node.find("<title>").unwrap() + 7..node.find("</title>").unwrap()])
+            .collect();
+        assert_eq!(
+            deadlocked,
+            vec![
+                "Deadlock&lt;Thread&gt;&amp;1 (BLOCKED, tid 0x0000e7a05c004000)",
+                "DeadlockThread-2 (BLOCKED, tid 0x0000e7a05c006000)"
+            ]
+        );
+        assert_eq!(svg.matches("stroke=\"#b00020\"").count(), 2);
+
+        let latest = in_time_order(&dumps).into_iter().last().unwrap();
+        let threads = &html[html.find("<h2>Threads</h2>").unwrap()..];
+        assert_eq!(threads.matches("<details class=\"searchable\">").count(), latest.threads.len());
+        for thread in &latest.threads {
+            assert!(threads.contains(&format!("<details class=\"searchable\"><summary>{} <span", xml_escape(&thread.name))));
+        }
+    }
+}
@@ -1,24 +1,375 @@
-use thread_lens::models::ThreadDump;
-use thread_lens::parser::parse_jstack_output;
+use flate2::read::GzDecoder;
 use std::fs;
+use std::io::{Cursor, IsTerminal, Read};
 use std::path::Path;
+use thread_lens::classifier::Classifier;
+use thread_lens::models::ThreadDump;
+use thread_lens::parser::{looks_like_thread_dump, parse_jstack_output_with, split_dumps};
+
+/// The path that stands for standard input.
+pub const STDIN: &str = "-";
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+fn is_tar(bytes: &[u8]) -> bool {
+    bytes.get(257..262) == Some(b"ustar".as_slice())
+}
+
+/// What happened to one input file or archive member.
+enum Outcome {
+    Loaded,
+    Skipped(String),
+    Failed(String),
+}
+
+/// Collects dumps from files, directories, globs and archives, recording the
+/// outcome for every file it looks at. Files are recognised by their content,
+/// not their name.
+struct Loader<'a> {
+    classifier: &'a Classifier,
+    /// Read for `-`.
+    stdin: Box<dyn Read + 'a>,
+    /// Each dump with the name of the file (or archive member) it came from.
+    dumps: Vec<(String, ThreadDump)>,
+    report: Vec<(String, Outcome)>,
+}
+
+impl Loader<'_> {
+    fn load_input(&mut self, input: &str) {
+        if input == STDIN {
+            let mut bytes = Vec::new();
+            match self.stdin.read_to_end(&mut bytes) {
+                Ok(_) => self.load_bytes("<stdin>".to_string(), bytes),
+                Err(e) => self.report.push(("<stdin>".to_string(), Outcome::Failed(e.to_string()))),
+            }
+            return;
+        }
+        if !input.contains(['*', '?', '[']) {
+            self.load_path(Path::new(input));
+            return;
+        }
+        match glob::glob(input) {
+            Ok(paths) => {
+                let mut matched = false;
+                for path in paths {
+                    matched = true;
+                    match path {
+                        Ok(path) => self.load_path(&path),
+                        Err(e) => self.report.push((e.path().display().to_string(), Outcome::Failed(e.error().to_string()))),
+                    }
+                }
+                if !matched {
+                    self.report.push((input.to_string(), Outcome::Failed("no files match".to_string())));
+                }
+            }
+            Err(e) => self.report.push((input.to_string(), Outcome::Failed(format!("invalid pattern: {}", e)))),
+        }
+    }
 
-pub fn read_dumps_from_directory(dir_path: String) -> std::io::Result<Vec<ThreadDump>> {
-    let mut dumps = Vec::new();
-    let path = Path::new(&dir_path);
-
-    if path.is_dir() {
-        for entry in fs::read_dir(path)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_file() && path.extension().map_or(false, |ext| ext == "jstack") {
-                let content = fs::read_to_string(&path)?;
-                match parse_jstack_output(&content) {
-                    Ok(dump) => dumps.push(dump),
-                    Err(e) => eprintln!("Error parsing file {}: {}", path.display(), e),
+    /// Directories are read recursively, in name order.
+    fn load_path(&mut self, path: &Path) {
+        let name = path.display().to_string();
+        if path.is_dir() {
+            let entries = fs::read_dir(path).and_then(|entries| entries.map(|entry| entry.map(|e| e.path())).collect::<Result<Vec<_>, _>>());
+            match entries {
+                Ok(mut paths) => {
+                    paths.sort();
+                    for path in paths {
+                        self.load_path(&path);
+                    }
                 }
+                Err(e) => self.report.push((name, Outcome::Failed(e.to_string()))),
             }
+            return;
+        }
+        match fs::read(path) {
+            Ok(bytes) => self.load_bytes(name, bytes),
+            Err(e) => self.report.push((name, Outcome::Failed(e.to_string()))),
         }
     }
-    Ok(dumps)
+
+    fn load_bytes(&mut self, name: String, bytes: Vec<u8>) {
+        if bytes.starts_with(ZIP_MAGIC) {
+            self.load_zip(name, bytes);
+        } else if bytes.starts_with(GZIP_MAGIC) {
+            let mut decompressed = Vec::new();
+            match GzDecoder::new(bytes.as_slice()).read_to_end(&mut decompressed) {
+                Ok(_) => self.load_bytes(name, decompressed),
+                Err(e) => self.report.push((name, Outcome::Failed(format!("invalid gzip data: {}", e)))),
+            }
+        } else if is_tar(&bytes) {
+            self.load_tar(name, bytes);
+        } else {
+            self.load_text(name, &String::from_utf8_lossy(&bytes));
+        }
+    }
+
+    fn load_zip(&mut self, name: String, bytes: Vec<u8>) {
+        let mut archive = match zip::ZipArchive::new(Cursor::new(bytes)) {
+            Ok(archive) => archive,
+            Err(e) => return self.report.push((name, Outcome::Failed(format!("invalid zip archive: {}", e)))),
+        };
+        for index in 0..archive.len() {
+            let mut member = match archive.by_index(index) {
+                Ok(member) => member,
+                Err(e) => {
+                    self.report.push((format!("{}!#{}", name, index), Outcome::Failed(e.to_string())));
+                    continue;
+                }
+            };
+            if !member.is_file() {
+                continue;
+            }
+            let member_name = format!("{}!{}", name, member.name());
+            let mut content = Vec::new();
+            match member.read_to_end(&mut content) {
+                Ok(_) => self.load_bytes(member_name, content),
+                Err(e) => self.report.push((member_name, Outcome::Failed(e.to_string()))),
+            }
+        }
+    }
+
+    fn load_tar(&mut self, name: String, bytes: Vec<u8>) {
+        let mut archive = tar::Archive::new(bytes.as_slice());
+        let entries = match archive.entries() {
+            Ok(entries) => entries,
+            Err(e) => return self.report.push((name, Outcome::Failed(format!("invalid tar archive: {}", e)))),
+        };
+        for entry in entries {
+            let mut entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    self.report.push((name.clone(), Outcome::Failed(format!("invalid tar archive: {}", e))));
+                    break;
+                }
+            };
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let member_name = match entry.path() {
+                Ok(path) => format!("{}!{}", name, path.display()),
+                Err(_) => format!("{}!?", name),
+            };
+            let mut content = Vec::new();
+            match entry.read_to_end(&mut content) {
+                Ok(_) => self.load_bytes(member_name, content),
+                Err(e) => self.report.push((member_name, Outcome::Failed(e.to_string()))),
+            }
+        }
+    }
+
+    /// A file or stream may hold several dumps one after the other; each is
+    /// reported as `name#1`, `name#2`, ...
+    fn load_text(&mut self, name: String, content: &str) {
+        if !looks_like_thread_dump(content) {
+            self.report.push((name, Outcome::Skipped("not a thread dump".to_string())));
+            return;
+        }
+        let parts = split_dumps(content);
+        for (index, part) in parts.iter().enumerate() {
+            let name = if parts.len() > 1 { format!("{}#{}", name, index + 1) } else { name.clone() };
+            match parse_jstack_output_with(part, self.classifier) {
+                Ok(dump) => {
+                    self.dumps.push((name.clone(), dump));
+                    self.report.push((name, Outcome::Loaded));
+                }
+                Err(e) => self.report.push((name, Outcome::Failed(e))),
+            }
+        }
+    }
+
+    /// The dumps in capture order; dumps taken at the same time keep the order
+    /// they were loaded in.
+    fn into_dumps(self) -> Vec<(String, ThreadDump)> {
+        let mut dumps = self.dumps;
+        dumps.sort_by_key(|(_, dump)| dump.timestamp);
+        dumps
+    }
+
+    /// Prints what was loaded, skipped and failed to stderr, unless a single
+    /// input was loaded without trouble.
+    fn print_report(&self) {
+        if let [(_, Outcome::Loaded)] = self.report.as_slice() {
+            return;
+        }
+        let count = |wanted: fn(&Outcome) -> bool| self.report.iter().filter(|(_, outcome)| wanted(outcome)).count();
+        eprintln!(
+            "Loaded {} dumps from {} files ({} skipped, {} failed):",
+            self.dumps.len(),
+            self.report.len(),
+            count(|outcome| matches!(outcome, Outcome::Skipped(_))),
+            count(|outcome| matches!(outcome, Outcome::Failed(_)))
+        );
+        for (name, outcome) in &self.report {
+            match outcome {
+                Outcome::Loaded => eprintln!("  loaded   {}", name),
+                Outcome::Skipped(reason) => eprintln!("  skipped  {} ({})", name, reason),
+                Outcome::Failed(error) => eprintln!("  failed   {} ({})", name, error),
+            }
+        }
+    }
+}
+
+/// Reads every thread dump found under the given paths: files, directories
+/// (recursively), glob patterns and zip, tar, tar.gz or gzip'd files, or
+/// standard input for `-` or when no path is given. Dumps are returned in
+/// capture order, and a per-file report is printed to stderr.
+pub fn read_dumps(inputs: &[String], classifier: &Classifier) -> std::io::Result<Vec<ThreadDump>> {
+    Ok(read_named_dumps(inputs, classifier)?.into_iter().map(|(_, dump)| dump).collect())
+}
+
+/// Like [`read_dumps`], with the name each dump was loaded from: a path,
+/// `archive.zip!member`, `<stdin>`, and `#n` for the n-th dump of a file
+/// holding several.
+pub fn read_named_dumps(inputs: &[String], classifier: &Classifier) -> std::io::Result<Vec<(String, ThreadDump)>> {
+    let stdin = [STDIN.to_string()];
+    let inputs = if inputs.is_empty() { &stdin[..] } else { inputs };
+    if inputs.iter().filter(|input| *input == STDIN).count() > 1 {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "standard input ('-') can only be read once"));
+    }
+    if inputs.iter().any(|input| input == STDIN) && std::io::stdin().is_terminal() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "no --path given and nothing piped to standard input",
+        ));
+    }
+
+    let mut loader = Loader {
+        classifier,
+        stdin: Box::new(std::io::stdin()),
+        dumps: Vec::new(),
+        report: Vec::new(),
+    };
+    for input in inputs {
+        loader.load_input(input);
+    }
+    loader.print_report();
+    Ok(loader.into_dumps())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    const SERIES: &str = "../thread-lens/src/test_data/java-app-openjdk11_20250921085315_deadlock";
+
+    /// A directory under the system temp dir, removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("thread-lens-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// The first dumps of the series, in capture order.
+    fn series_dumps(count: usize) -> Vec<Vec<u8>> {
+        let mut paths: Vec<_> = fs::read_dir(SERIES).unwrap().map(|entry| entry.unwrap().path()).collect();
+        paths.sort();
+        paths.iter().take(count).map(|path| fs::read(path).unwrap()).collect()
+    }
+
+    fn loader<'a>(classifier: &'a Classifier, stdin: &'a [u8]) -> Loader<'a> {
+        Loader {
+            classifier,
+            stdin: Box::new(stdin),
+            dumps: Vec::new(),
+            report: Vec::new(),
+        }
+    }
+
+    fn skipped(loader: &Loader) -> Vec<String> {
+        loader
+            .report
+            .iter()
+            .filter(|(_, outcome)| matches!(outcome, Outcome::Skipped(_)))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_loader_reads_archives_and_directories() {
+        let dumps = series_dumps(3);
+        let temp = TempDir::new("io-archives");
+        let dir = temp.0.display().to_string();
+
+        fs::create_dir_all(temp.0.join("archives")).unwrap();
+        let mut zip = zip::ZipWriter::new(fs::File::create(temp.0.join("archives/dumps.zip")).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.add_directory("node-1/2025/", options).unwrap();
+        zip.start_file("node-1/2025/first.jstack", options).unwrap();
+        zip.write_all(&dumps[0]).unwrap();
+        zip.start_file("node-1/notes.txt", options).unwrap();
+        zip.write_all(b"collected after the outage\n").unwrap();
+        zip.finish().unwrap();
+
+        let mut tar = tar::Builder::new(GzEncoder::new(
+            fs::File::create(temp.0.join("archives/more.tar.gz")).unwrap(),
+            Compression::default(),
+        ));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(dumps[2].len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, "series/late/third.jstack", dumps[2].as_slice()).unwrap();
+        tar.into_inner().unwrap().finish().unwrap();
+
+        fs::create_dir_all(temp.0.join("loose/deeper")).unwrap();
+        let mut gzip = GzEncoder::new(fs::File::create(temp.0.join("loose/deeper/second.jstack.gz")).unwrap(), Compression::default());
+        gzip.write_all(&dumps[1]).unwrap();
+        gzip.finish().unwrap();
+
+        let classifier = Classifier::default();
+        let mut all = loader(&classifier, b"");
+        all.load_input(&dir);
+        assert_eq!(skipped(&all), vec![format!("{}/archives/dumps.zip!node-1/notes.txt", dir)]);
+        assert!(!all.report.iter().any(|(_, outcome)| matches!(outcome, Outcome::Failed(_))));
+        let names: Vec<String> = all.into_dumps().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(
+            names,
+            vec![
+                format!("{}/archives/dumps.zip!node-1/2025/first.jstack", dir),
+                format!("{}/loose/deeper/second.jstack.gz", dir),
+                format!("{}/archives/more.tar.gz!series/late/third.jstack", dir),
+            ]
+        );
+
+        let mut globbed = loader(&classifier, b"");
+        globbed.load_input(&format!("{}/archives/*.tar.gz", dir));
+        globbed.load_input(&format!("{}/*.jstack", dir));
+        assert_eq!(globbed.dumps.len(), 1);
+        assert!(matches!(globbed.report.last(), Some((_, Outcome::Failed(error))) if error == "no files match"));
+    }
+
+    #[test]
+    fn test_loader_splits_concatenated_dumps_on_stdin() {
+        let dumps = series_dumps(2);
+        // The later dump first, as `cat` of badly named files would give
+        let piped = [dumps[1].as_slice(), b"\n", dumps[0].as_slice()].concat();
+
+        let classifier = Classifier::default();
+        let mut stdin = loader(&classifier, &piped);
+        stdin.load_input(STDIN);
+        assert_eq!(stdin.report.len(), 2);
+        assert!(stdin.report.iter().all(|(_, outcome)| matches!(outcome, Outcome::Loaded)));
+
+        let loaded = stdin.into_dumps();
+        let names: Vec<&str> = loaded.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["<stdin>#2", "<stdin>#1"]);
+        assert!(loaded[0].1.timestamp < loaded[1].1.timestamp);
+    }
 }
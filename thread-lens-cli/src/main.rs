@@ -1,61 +1,413 @@
 use clap::Parser;
-use thread_lens::analyzer::find_chronically_blocked_threads;
+use std::process::ExitCode;
+use thread_lens::analyzer::baseline::{build_baseline, compare_to_baseline, Baseline, BaselineOptions};
+use thread_lens::analyzer::diff::diff_dumps;
+use thread_lens::analyzer::gate::{check_report, GateOptions};
+use thread_lens::analyzer::groups::group_by_stack;
+use thread_lens::analyzer::pools::pool_stats;
+use thread_lens::analyzer::report::{analyze, AnalysisOptions};
+use thread_lens::analyzer::summary::summarize;
+use thread_lens::analyzer::timeline::build_timeline;
+use thread_lens::export::flame::{build_flame_graph, FlameOptions};
+use thread_lens::export::graph::build_lock_graph;
+use thread_lens::models::ThreadDump;
+use thread_lens::query::Query;
 
+mod ci;
 mod cli;
+mod config;
+mod html;
 mod io;
+mod markdown;
 mod output;
 
-use cli::{Args, Commands, OutputFormat};
-use io::read_dumps_from_directory;
-use output::{print_json_view, print_text_view, print_yaml_view};
+use cli::{Args, BaselineCommand, ChronicArgs, Commands, DocumentFormat, FilterArgs, FlameFormat, GraphFormat, GroupingArgs, MarkdownArgs, OutputFormat, ReportFormat};
+use config::{load_classifier, load_rule_engine, Classification};
+use io::{read_dumps, read_named_dumps};
+use output::{
+    print_analysis_text_view, print_deviations_text_view, print_diff_text_view, print_gate_text_view, print_grouped_text_view, print_json_view, print_pools_text_view, print_query_text_view, print_summary_text_view,
+    print_text_view, print_timeline_text_view, print_yaml_view,
+};
 
-fn main() -> std::io::Result<()> {
+/// Exits with 0 on success, 1 when `check` fails and 2 on errors (2 is also
+/// what clap uses for invalid arguments).
+fn main() -> ExitCode {
     env_logger::init();
-    let args = Args::parse();
+    match run(Args::parse()) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(args: Args) -> std::io::Result<ExitCode> {
+    let classification = Classification {
+        classifier: load_classifier(args.rules.as_deref(), args.app_packages)?,
+        infer_app_packages: !args.no_infer_packages,
+    };
 
     match args.command {
-        Commands::Analyze { path } => handle_analyze(path)?,
-        Commands::View { path, output } => handle_view(path, output)?,
+        Commands::Analyze { paths, grouping, chronic, pool_max, patterns, output, markdown } => {
+            let options = AnalysisOptions {
+                chronic: chronic.chronic_options(),
+                pool_max_sizes: pool_max.into_iter().collect(),
+            };
+            handle_analyze(paths, grouping, options, patterns, output, markdown, &classification)?
+        }
+        Commands::Summary { paths, output, json, markdown, patterns } => {
+            let output = if json { DocumentFormat::Json } else { output };
+            handle_summary(paths, output, markdown, patterns, &classification)?
+        }
+        Commands::Baseline { action } => handle_baseline(action, &classification)?,
+        Commands::View { path, output, markdown, grouping, pools, filter } => {
+            handle_view(path, output, markdown, grouping, pools, filter, &classification)?
+        }
+        Commands::Timeline { paths, output, changed_only } => handle_timeline(paths, output, changed_only, &classification)?,
+        Commands::Diff { before, after, output, markdown } => handle_diff(before, after, output, markdown, &classification)?,
+        Commands::Report { paths, chronic, patterns, out } => handle_report(paths, chronic, patterns, out, &classification)?,
+        Commands::Flame { paths, output, weight, by_state, categories, out } => {
+            let options = FlameOptions {
+                weight: weight.flame_weight(),
+                split_by_state: by_state,
+                categories: categories.iter().map(|category| category.thread_category()).collect(),
+            };
+            handle_flame(paths, output, options, out, &classification)?
+        }
+        Commands::Graph { paths, output, latest, out } => handle_graph(paths, output, latest, out, &classification)?,
+        Commands::Query { query, paths, output } => handle_query(query, paths, output, &classification)?,
+        Commands::Check { paths, chronic, patterns, allow_deadlocks, max_chronic, fail_on_severity, junit, sarif } => {
+            let options = GateOptions {
+                fail_on_deadlock: !allow_deadlocks,
+                max_chronically_blocked: max_chronic,
+                fail_on_severity: fail_on_severity.map(|severity| severity.severity()),
+            };
+            return handle_check(paths, chronic, patterns, options, junit, sarif, &classification);
+        }
     }
 
+    Ok(ExitCode::SUCCESS)
+}
+
+fn handle_analyze(
+    paths: Vec<String>,
+    grouping: GroupingArgs,
+    options: AnalysisOptions,
+    patterns: Option<String>,
+    output: ReportFormat,
+    markdown: MarkdownArgs,
+    classification: &Classification,
+) -> std::io::Result<()> {
+    let engine = load_rule_engine(patterns.as_deref())?;
+    let mut dumps = read_dumps(&paths, &classification.classifier)?;
+    classification.apply_inferred_app_packages(&mut dumps);
+
+    let report = analyze(&dumps, &engine, &options).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    match output {
+        ReportFormat::Text => print_analysis_text_view(&report, grouping.group.then(|| grouping.signature_options())),
+        ReportFormat::Json => print_json_view(&report),
+        ReportFormat::Yaml => print_yaml_view(&report),
+        ReportFormat::Ndjson => print!("{}", report.to_ndjson().map_err(std::io::Error::other)?),
+        ReportFormat::Markdown => {
+            let doc = markdown::analysis_markdown(&report, grouping.group.then(|| grouping.signature_options()));
+            print!("{}", doc.render(markdown.max_chars))
+        }
+    }
     Ok(())
 }
 
-fn handle_analyze(path: String) -> std::io::Result<()> {
-    let dumps = read_dumps_from_directory(path)?;
+fn handle_check(
+    paths: Vec<String>,
+    chronic: ChronicArgs,
+    patterns: Option<String>,
+    options: GateOptions,
+    junit: Option<String>,
+    sarif: Option<String>,
+    classification: &Classification,
+) -> std::io::Result<ExitCode> {
+    let engine = load_rule_engine(patterns.as_deref())?;
+    let (sources, mut dumps): (Vec<String>, Vec<ThreadDump>) = read_named_dumps(&paths, &classification.classifier)?.into_iter().unzip();
+    classification.apply_inferred_app_packages(&mut dumps);
+    if dumps.is_empty() {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "no thread dump found"));
+    }
 
-    println!("--- Analysis Report ---");
-    println!("Found {} thread dumps to analyze.", dumps.len());
+    let analysis = AnalysisOptions {
+        chronic: chronic.chronic_options(),
+        ..Default::default()
+    };
+    let report = analyze(&dumps, &engine, &analysis).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    let result = check_report(&report, &options);
+    if let Some(path) = junit {
+        std::fs::write(path, ci::junit_xml(&result))?;
+    }
+    if let Some(path) = sarif {
+        let log = serde_json::to_string_pretty(&ci::sarif(&report, &result, &sources)).map_err(std::io::Error::other)?;
+        std::fs::write(path, log)?;
+    }
+    print_gate_text_view(&result);
+    Ok(if result.passed() { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+}
+
+fn handle_report(
+    paths: Vec<String>,
+    chronic: ChronicArgs,
+    patterns: Option<String>,
+    out: Option<String>,
+    classification: &Classification,
+) -> std::io::Result<()> {
+    let engine = load_rule_engine(patterns.as_deref())?;
+    let mut dumps = read_dumps(&paths, &classification.classifier)?;
+    classification.apply_inferred_app_packages(&mut dumps);
+    if dumps.is_empty() {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "no thread dump found"));
+    }
+
+    let options = AnalysisOptions {
+        chronic: chronic.chronic_options(),
+        ..Default::default()
+    };
+    let report = analyze(&dumps, &engine, &options).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    let page = html::render_html(&dumps, &report, &summarize(&dumps, &engine), &build_timeline(&dumps));
+    match out {
+        Some(path) => {
+            std::fs::write(&path, page)?;
+            eprintln!("Wrote report to {}", path);
+        }
+        None => print!("{}", page),
+    }
+    Ok(())
+}
 
-    let chronically_blocked = find_chronically_blocked_threads(&dumps);
+fn handle_flame(
+    paths: Vec<String>,
+    output: FlameFormat,
+    options: FlameOptions,
+    out: Option<String>,
+    classification: &Classification,
+) -> std::io::Result<()> {
+    let mut dumps = read_dumps(&paths, &classification.classifier)?;
+    classification.apply_inferred_app_packages(&mut dumps);
+    let graph = build_flame_graph(&dumps, &options);
+    if graph.stacks.is_empty() {
+        eprintln!("No stacks to export; --weight cpu needs at least two dumps with cpu= times");
+    }
+    let title = format!("Thread stacks across {} dumps", dumps.len());
+    let exported = match output {
+        FlameFormat::Collapsed => graph.to_collapsed(),
+        FlameFormat::Speedscope => serde_json::to_string(&graph.to_speedscope(&title)).map_err(std::io::Error::other)? + "\n",
+        FlameFormat::Svg => graph.to_svg(&title),
+    };
+    match out {
+        Some(path) => {
+            std::fs::write(&path, exported)?;
+            eprintln!("Wrote flame graph to {}", path);
+        }
+        None => print!("{}", exported),
+    }
+    Ok(())
+}
 
-    if chronically_blocked.is_empty() {
-        println!("\nNo chronically blocked application threads found.");
+fn handle_graph(paths: Vec<String>, output: GraphFormat, latest: bool, out: Option<String>, classification: &Classification) -> std::io::Result<()> {
+    let mut dumps = read_dumps(&paths, &classification.classifier)?;
+    classification.apply_inferred_app_packages(&mut dumps);
+    if dumps.is_empty() {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "no thread dump found"));
+    }
+    if latest {
+        dumps.sort_by_key(|dump| dump.timestamp);
+        dumps.drain(..dumps.len() - 1);
+    }
+    let graph = build_lock_graph(&dumps);
+    if graph.edges.is_empty() {
+        eprintln!("No thread waits or parks on a lock held by another thread");
+    }
+    let exported = match output {
+        GraphFormat::Dot => graph.to_dot(),
+        GraphFormat::Mermaid => graph.to_mermaid(),
+        GraphFormat::Json => serde_json::to_string_pretty(&graph).map_err(std::io::Error::other)? + "\n",
+    };
+    match out {
+        Some(path) => {
+            std::fs::write(&path, exported)?;
+            eprintln!("Wrote lock graph to {}", path);
+        }
+        None => print!("{}", exported),
+    }
+    Ok(())
+}
+
+fn handle_summary(
+    paths: Vec<String>,
+    output: DocumentFormat,
+    markdown: MarkdownArgs,
+    patterns: Option<String>,
+    classification: &Classification,
+) -> std::io::Result<()> {
+    let engine = load_rule_engine(patterns.as_deref())?;
+    let mut dumps = read_dumps(&paths, &classification.classifier)?;
+    classification.apply_inferred_app_packages(&mut dumps);
+    let summary = summarize(&dumps, &engine);
+    match output {
+        DocumentFormat::Text => print_summary_text_view(&summary),
+        DocumentFormat::Json => print_json_view(&summary),
+        DocumentFormat::Yaml => print_yaml_view(&summary),
+        DocumentFormat::Markdown => print!("{}", markdown::summary_markdown(&summary).render(markdown.max_chars)),
+    }
+    Ok(())
+}
+
+fn handle_baseline(action: BaselineCommand, classification: &Classification) -> std::io::Result<()> {
+    match action {
+        BaselineCommand::Save { paths, baseline } => {
+            let mut dumps = read_dumps(&paths, &classification.classifier)?;
+            classification.apply_inferred_app_packages(&mut dumps);
+            let profile = build_baseline(&dumps);
+            let json = serde_json::to_string_pretty(&profile).map_err(std::io::Error::other)?;
+            std::fs::write(&baseline, json)?;
+            println!(
+                "Saved baseline of {} dumps ({} stacks, {} pools) to {}",
+                profile.dumps,
+                profile.stacks.len(),
+                profile.pools.len(),
+                baseline
+            );
+        }
+        BaselineCommand::Compare { paths, baseline, min_z_score, min_change, output } => {
+            let profile = Baseline::from_json(&std::fs::read_to_string(&baseline)?)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: {}", baseline, e)))?;
+            let mut dumps = read_dumps(&paths, &classification.classifier)?;
+            classification.apply_inferred_app_packages(&mut dumps);
+            let options = BaselineOptions { min_z_score, min_change };
+            let deviations = compare_to_baseline(&profile, &dumps, &options);
+            match output {
+                OutputFormat::Text => print_deviations_text_view(&profile, &deviations),
+                OutputFormat::Json => print_json_view(&deviations),
+                OutputFormat::Yaml => print_yaml_view(&deviations),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn handle_view(
+    path: Option<String>,
+    output: DocumentFormat,
+    markdown: MarkdownArgs,
+    grouping: GroupingArgs,
+    pools: bool,
+    filter: FilterArgs,
+    classification: &Classification,
+) -> std::io::Result<()> {
+    let mut dumps = read_dumps(&path.into_iter().collect::<Vec<_>>(), &classification.classifier)?;
+    classification.apply_inferred_app_packages(&mut dumps);
+    let Some(mut dump) = dumps.pop() else {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "no thread dump found"));
+    };
+    if !dumps.is_empty() {
+        eprintln!("Found {} dumps, showing the latest", dumps.len() + 1);
+    }
+    let filter = filter.thread_filter();
+    if !filter.is_empty() {
+        let total = dump.threads.len();
+        dump = filter.apply(&dump).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        eprintln!("{} of {} threads match the filters", dump.threads.len(), total);
+    }
+    if pools {
+        let stats = pool_stats(&dump);
+        match output {
+            DocumentFormat::Text => print_pools_text_view(&dump, &stats),
+            DocumentFormat::Json => print_json_view(&stats),
+            DocumentFormat::Yaml => print_yaml_view(&stats),
+            DocumentFormat::Markdown => print!("{}", markdown::pools_view_markdown(&dump, &stats).render(markdown.max_chars)),
+        }
+    } else if grouping.group {
+        let groups = group_by_stack(&dump, &grouping.signature_options());
+        match output {
+            DocumentFormat::Text => print_grouped_text_view(&dump, &groups),
+            DocumentFormat::Json => print_json_view(&groups),
+            DocumentFormat::Yaml => print_yaml_view(&groups),
+            DocumentFormat::Markdown => print!("{}", markdown::grouped_view_markdown(&dump, &groups).render(markdown.max_chars)),
+        }
     } else {
-        println!("\nFound {} chronically blocked application threads:", chronically_blocked.len());
-        for (name, (thread, count)) in chronically_blocked {
-            println!("  - Thread: '{}' (blocked in {} dumps)", name, count);
-            println!("    State: {}", thread.state);
-            println!("    Category: {:?}", thread.category);
-            println!("    Stack Trace:");
-            for frame in thread.frames.iter().take(5) {
-                println!("      [{:?}] {}", frame.category, frame.line);
+        match output {
+            DocumentFormat::Text => print_text_view(&dump),
+            DocumentFormat::Json => print_json_view(&dump),
+            DocumentFormat::Yaml => print_yaml_view(&dump),
+            DocumentFormat::Markdown => print!("{}", markdown::view_markdown(&dump).render(markdown.max_chars)),
+        }
+    }
+    Ok(())
+}
+
+/// Reads the latest dump of one input.
+fn read_latest_dump(path: &str, classification: &Classification) -> std::io::Result<ThreadDump> {
+    read_dumps(&[path.to_string()], &classification.classifier)?
+        .pop()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: no thread dump found", path)))
+}
+
+fn handle_diff(
+    before: Option<String>,
+    after: Option<String>,
+    output: DocumentFormat,
+    markdown: MarkdownArgs,
+    classification: &Classification,
+) -> std::io::Result<()> {
+    let mut dumps = match (before, after) {
+        (Some(before), Some(after)) => vec![read_latest_dump(&before, classification)?, read_latest_dump(&after, classification)?],
+        (before, _) => {
+            let mut dumps = read_dumps(&before.into_iter().collect::<Vec<_>>(), &classification.classifier)?;
+            if dumps.len() < 2 {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("need two dumps to compare, found {}", dumps.len()),
+                ));
             }
+            let last = dumps.pop().unwrap();
+            vec![dumps.swap_remove(0), last]
         }
+    };
+    classification.apply_inferred_app_packages(&mut dumps);
+    let diff = diff_dumps(&dumps[0], &dumps[1]);
+    match output {
+        DocumentFormat::Text => print_diff_text_view(&diff),
+        DocumentFormat::Json => print_json_view(&diff),
+        DocumentFormat::Yaml => print_yaml_view(&diff),
+        DocumentFormat::Markdown => print!("{}", markdown::diff_markdown(&diff).render(markdown.max_chars)),
     }
+    Ok(())
+}
 
+fn handle_timeline(
+    paths: Vec<String>,
+    output: OutputFormat,
+    changed_only: bool,
+    classification: &Classification,
+) -> std::io::Result<()> {
+    let mut dumps = read_dumps(&paths, &classification.classifier)?;
+    classification.apply_inferred_app_packages(&mut dumps);
+    let mut timeline = build_timeline(&dumps);
+    if changed_only {
+        timeline.threads.retain(|thread| thread.changes_state());
+    }
+    match output {
+        OutputFormat::Text => print_timeline_text_view(&timeline),
+        OutputFormat::Json => print_json_view(&timeline),
+        OutputFormat::Yaml => print_yaml_view(&timeline),
+    }
     Ok(())
 }
 
-fn handle_view(path: String, output: OutputFormat) -> std::io::Result<()> {
-    let content = std::fs::read_to_string(path)?;
-    match thread_lens::parser::parse_jstack_output(&content) {
-        Ok(dump) => match output {
-            OutputFormat::Text => print_text_view(&dump),
-            OutputFormat::Json => print_json_view(&dump),
-            OutputFormat::Yaml => print_yaml_view(&dump),
-        },
-        Err(e) => eprintln!("Error parsing file: {}", e),
+fn handle_query(query: Query, paths: Vec<String>, output: OutputFormat, classification: &Classification) -> std::io::Result<()> {
+    let mut dumps = read_dumps(&paths, &classification.classifier)?;
+    classification.apply_inferred_app_packages(&mut dumps);
+    let result = query.run(&dumps);
+    match output {
+        OutputFormat::Text => print_query_text_view(&result),
+        OutputFormat::Json => print_json_view(&result),
+        OutputFormat::Yaml => print_yaml_view(&result),
     }
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,657 @@
+use std::collections::BTreeMap;
+use thread_lens::analyzer::blockers::join_threads;
+use thread_lens::analyzer::diff::DumpDiff;
+use thread_lens::analyzer::groups::{group_by_stack, group_threads, SignatureOptions, StackGroup};
+use thread_lens::analyzer::leaks::GrowthScope;
+use thread_lens::analyzer::pools::PoolStats;
+use thread_lens::analyzer::report::AnalysisReport;
+use thread_lens::analyzer::summary::Summary;
+use thread_lens::export::xml_escape;
+use thread_lens::models::{CategorizedFrame, FrameCategory, NormalizedThread, ThreadDump};
+
+use crate::output::describe_growth;
+
+/// Stacks shown inline are cut to this many frames; full stacks go in `<details>`.
+const INLINE_FRAMES: usize = 15;
+/// Stack groups listed inline by `view` without `--group`.
+const TOP_GROUPS: usize = 5;
+/// The smallest size budget that leaves room for the truncation notice.
+pub const MIN_MAX_CHARS: usize = 100;
+
+/// Escapes characters that Markdown would otherwise read as formatting, HTML
+/// or a table cell boundary. Underscores inside words, as in `TIMED_WAITING`,
+/// are left alone since GitHub, GitLab and Slack do not emphasize them.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '`' | '*' | '<' | '>' | '|' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' | '\r' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// `code` as an inline code span, with a fence longer than any backtick run in it.
+fn code(text: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest + 1);
+    let padding = if text.starts_with('`') || text.ends_with('`') { " " } else { "" };
+    format!("{fence}{padding}{}{padding}{fence}", text.replace('\n', " "))
+}
+
+fn char_count(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// A self-contained piece of the document. Blocks are only ever cut in ways
+/// that keep the Markdown valid: tables lose rows, code blocks lose lines and
+/// keep their closing fence, and `<details>` sections keep their closing tag.
+#[derive(Debug, Clone)]
+enum Block {
+    /// Never the last block of a cut document, since it introduces the next one.
+    Heading(String),
+    Text(String),
+    Table { header: Vec<String>, rows: Vec<Vec<String>> },
+    /// Lines of a fenced `diff` block; application frames start with `+`.
+    Code(Vec<String>),
+    Details { summary: String, lines: Vec<String> },
+}
+
+fn code_fence(lines: &[String]) -> String {
+    let longest = lines
+        .iter()
+        .flat_map(|line| line.split(|c| c != '`'))
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn table_row(cells: &[String]) -> String {
+    format!("| {} |", cells.join(" | "))
+}
+
+impl Block {
+    fn render(&self) -> String {
+        self.render_lines(usize::MAX).0
+    }
+
+    /// Renders the block with at most `keep` table rows or code lines, and
+    /// says how many were left out.
+    fn render_lines(&self, keep: usize) -> (String, usize) {
+        match self {
+            Block::Heading(text) | Block::Text(text) => (text.clone(), 0),
+            Block::Table { header, rows } => {
+                let shown = keep.min(rows.len());
+                let mut lines = vec![table_row(header), table_row(&vec!["---".to_string(); header.len()])];
+                lines.extend(rows[..shown].iter().map(|row| table_row(row)));
+                (lines.join("\n"), rows.len() - shown)
+            }
+            Block::Code(lines) => {
+                let shown = keep.min(lines.len());
+                let fence = code_fence(lines);
+                (format!("{fence}diff\n{}\n{fence}", lines[..shown].join("\n")), lines.len() - shown)
+            }
+            Block::Details { summary, lines } => {
+                let shown = keep.min(lines.len());
+                let fence = code_fence(lines);
+                (
+                    format!(
+                        "<details><summary>{}</summary>\n\n{fence}diff\n{}\n{fence}\n\n</details>",
+                        summary,
+                        lines[..shown].join("\n")
+                    ),
+                    lines.len() - shown,
+                )
+            }
+        }
+    }
+
+    fn line_count(&self) -> usize {
+        match self {
+            Block::Heading(_) | Block::Text(_) => 0,
+            Block::Table { rows, .. } => rows.len(),
+            Block::Code(lines) | Block::Details { lines, .. } => lines.len(),
+        }
+    }
+
+    fn line_unit(&self) -> &'static str {
+        match self {
+            Block::Table { .. } => "rows",
+            _ => "lines",
+        }
+    }
+
+    /// The largest cut of the block that fits in `budget` characters, if any.
+    fn fit(&self, budget: usize) -> Option<String> {
+        let full = self.render();
+        if char_count(&full) <= budget {
+            return Some(full);
+        }
+        // Binary search for the most rows or lines that still fit.
+        let (mut low, mut high) = (0, self.line_count());
+        let mut best = None;
+        while low < high {
+            let keep = (low + high).div_ceil(2);
+            let (text, omitted) = self.render_lines(keep);
+            let text = format!("{}\n\n_{} more {} omitted._", text, omitted, self.line_unit());
+            if char_count(&text) <= budget {
+                best = Some(text);
+                low = keep;
+            } else {
+                high = keep - 1;
+            }
+        }
+        best
+    }
+}
+
+/// A Markdown document built from blocks, so it can be cut to a size budget
+/// without breaking tables, code fences or `<details>` sections.
+#[derive(Debug, Default)]
+pub struct Markdown {
+    blocks: Vec<Block>,
+}
+
+impl Markdown {
+    fn heading(&mut self, level: usize, text: &str) {
+        self.blocks.push(Block::Heading(format!("{} {}", "#".repeat(level), text)));
+    }
+
+    fn text(&mut self, text: String) {
+        self.blocks.push(Block::Text(text));
+    }
+
+    fn table(&mut self, header: &[&str], rows: Vec<Vec<String>>) {
+        self.blocks.push(Block::Table {
+            header: header.iter().map(|cell| cell.to_string()).collect(),
+            rows,
+        });
+    }
+
+    fn count_table(&mut self, header: &[&str], counts: &BTreeMap<String, usize>) {
+        let mut counts: Vec<(&String, &usize)> = counts.iter().collect();
+        counts.sort_by_key(|&(_, count)| std::cmp::Reverse(*count));
+        let rows = counts.into_iter().map(|(key, count)| vec![escape(key), count.to_string()]).collect();
+        self.table(header, rows);
+    }
+
+    /// The first frames of a stack as a fenced block; the rest are counted.
+    fn stack(&mut self, frames: &[CategorizedFrame], limit: usize) {
+        let mut lines = stack_lines(&frames[..frames.len().min(limit)]);
+        if frames.len() > limit {
+            lines.push(format!("  ... {} more frames", frames.len() - limit));
+        }
+        self.blocks.push(Block::Code(lines));
+    }
+
+    fn full_stack(&mut self, thread: &NormalizedThread) {
+        self.blocks.push(Block::Details {
+            // Inside HTML, Markdown escapes do not apply.
+            summary: format!(
+                "{} ({}, {:?})",
+                xml_escape(&thread.name),
+                thread.state,
+                thread.category
+            ),
+            lines: stack_lines(&thread.frames),
+        });
+    }
+
+    /// The document, cut at block boundaries to at most `max_chars` characters
+    /// when given. A note at the end says how much was left out; when even the
+    /// note does not fit (see [`MIN_MAX_CHARS`]), nothing is returned.
+    pub fn render(&self, max_chars: Option<usize>) -> String {
+        let rendered: Vec<String> = self.blocks.iter().map(Block::render).collect();
+        let full = rendered.join("\n\n") + "\n";
+        let Some(max_chars) = max_chars else {
+            return full;
+        };
+        if char_count(&full) <= max_chars {
+            return full;
+        }
+
+        let notice = |omitted: usize| format!("\n\n_Truncated to {} characters: {} of {} sections omitted._\n", max_chars, omitted, self.blocks.len());
+        // Reserve room for the notice with the widest possible count.
+        let reserve = char_count(&notice(self.blocks.len()));
+        if reserve > max_chars {
+            return String::new();
+        }
+        let budget = max_chars - reserve;
+        let mut pieces: Vec<(&Block, String)> = Vec::new();
+        let mut used = 0;
+        for (block, text) in self.blocks.iter().zip(&rendered) {
+            let separator = if pieces.is_empty() { 0 } else { 2 };
+            let remaining = budget.saturating_sub(used + separator);
+            let length = char_count(text);
+            let piece = if length <= remaining { Some(text.clone()) } else { block.fit(remaining) };
+            let Some(piece) = piece else {
+                break;
+            };
+            used += separator + char_count(&piece);
+            pieces.push((block, piece));
+            if length > remaining {
+                break;
+            }
+        }
+        while matches!(pieces.last(), Some((Block::Heading(_), _))) {
+            pieces.pop();
+        }
+        let omitted = self.blocks.len() - pieces.len();
+        let mut out = pieces.into_iter().map(|(_, piece)| piece).collect::<Vec<_>>().join("\n\n");
+        out.push_str(&notice(omitted));
+        out
+    }
+}
+
+/// Frames as diff lines: application frames are added lines (`+`), so they are
+/// highlighted wherever diff blocks are coloured.
+fn stack_lines(frames: &[CategorizedFrame]) -> Vec<String> {
+    if frames.is_empty() {
+        return vec!["  (no frames)".to_string()];
+    }
+    frames
+        .iter()
+        .map(|frame| {
+            let marker = if frame.category == FrameCategory::Application { '+' } else { ' ' };
+            format!("{} {}", marker, frame.line.trim())
+        })
+        .collect()
+}
+
+fn dump_header(doc: &mut Markdown, title: &str, dump: &ThreadDump) {
+    doc.heading(1, title);
+    doc.text(format!(
+        "**JVM:** {}  \n**Timestamp:** {}  \n**Threads:** {}",
+        escape(&dump.jvm_version),
+        dump.timestamp,
+        dump.threads.len()
+    ));
+}
+
+fn state_counts(threads: &[NormalizedThread]) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for thread in threads {
+        *counts.entry(thread.state.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn group_description(group: &StackGroup) -> String {
+    let states: Vec<String> = group.states.iter().map(|(state, count)| format!("{}={}", state, count)).collect();
+    format!("**{} threads** ({}): {}", group.count, states.join(", "), escape(&group.name_summary()))
+}
+
+fn push_groups(doc: &mut Markdown, groups: &[StackGroup], limit: usize) {
+    for group in groups {
+        doc.text(group_description(group));
+        doc.stack(&group.frames, limit);
+    }
+}
+
+fn push_full_stacks(doc: &mut Markdown, threads: &[NormalizedThread]) {
+    doc.heading(2, "Full stacks");
+    for thread in threads {
+        doc.full_stack(thread);
+    }
+}
+
+/// `view`: state and category counts, the most common stacks, and every
+/// thread's full stack in a collapsed section.
+pub fn view_markdown(dump: &ThreadDump) -> Markdown {
+    let mut doc = Markdown::default();
+    dump_header(&mut doc, "Thread Dump Analysis", dump);
+    doc.heading(2, "Thread states");
+    doc.count_table(&["State", "Threads"], &state_counts(&dump.threads));
+    let mut categories = BTreeMap::new();
+    for thread in &dump.threads {
+        *categories.entry(format!("{:?}", thread.category)).or_insert(0) += 1;
+    }
+    doc.heading(2, "Thread categories");
+    doc.count_table(&["Category", "Threads"], &categories);
+
+    let groups = group_by_stack(dump, &SignatureOptions::default());
+    doc.heading(2, &format!("Most common stacks ({} of {})", groups.len().min(TOP_GROUPS), groups.len()));
+    push_groups(&mut doc, &groups[..groups.len().min(TOP_GROUPS)], INLINE_FRAMES);
+    push_full_stacks(&mut doc, &dump.threads);
+    doc
+}
+
+/// `view --group`.
+pub fn grouped_view_markdown(dump: &ThreadDump, groups: &[StackGroup]) -> Markdown {
+    let mut doc = Markdown::default();
+    dump_header(&mut doc, "Thread Dump Analysis", dump);
+    doc.heading(2, "Thread states");
+    doc.count_table(&["State", "Threads"], &state_counts(&dump.threads));
+    doc.heading(2, &format!("Stack groups ({})", groups.len()));
+    push_groups(&mut doc, groups, usize::MAX);
+    doc
+}
+
+/// `view --pools`.
+pub fn pools_view_markdown(dump: &ThreadDump, pools: &[PoolStats]) -> Markdown {
+    let mut doc = Markdown::default();
+    dump_header(&mut doc, "Thread Dump Analysis", dump);
+    doc.heading(2, &format!("Thread pools ({})", pools.len()));
+    let rows = pools
+        .iter()
+        .map(|pool| {
+            vec![
+                escape(&pool.name),
+                pool.size.to_string(),
+                pool.idle.to_string(),
+                pool.busy.to_string(),
+                pool.blocked.to_string(),
+            ]
+        })
+        .collect();
+    doc.table(&["Pool", "Size", "Idle", "Busy", "Blocked"], rows);
+    for pool in pools.iter().filter(|pool| !pool.top_busy_stacks.is_empty()) {
+        doc.heading(3, &format!("Top busy stacks of {}", escape(&pool.name)));
+        push_groups(&mut doc, &pool.top_busy_stacks, INLINE_FRAMES);
+    }
+    doc
+}
+
+/// `analyze`. With `grouping`, chronically blocked threads with the same stack
+/// are collapsed into one entry, as in the text view.
+pub fn analysis_markdown(report: &AnalysisReport, grouping: Option<SignatureOptions>) -> Markdown {
+    let dumps = report.dumps.len();
+    let mut doc = Markdown::default();
+    doc.heading(1, "Analysis Report");
+    doc.text(format!("Found {} thread dumps to analyze.", dumps));
+
+    doc.heading(2, "Root blockers");
+    if report.root_blockers.is_empty() {
+        doc.text("No blocked application threads could be traced to a lock holder.".to_string());
+    } else {
+        let rows = report
+            .root_blockers
+            .iter()
+            .map(|blocker| {
+                vec![
+                    blocker.blocked_threads.len().to_string(),
+                    format!(
+                        "{}{}{}",
+                        escape(&blocker.thread.to_string()),
+                        if blocker.deadlocked { " **DEADLOCK**" } else { "" },
+                        blocker.unresolved_note().map(|note| format!(", {}", escape(&note))).unwrap_or_default()
+                    ),
+                    code(blocker.frame.as_deref().unwrap_or("(no frames)")),
+                    format!("{} of {}", blocker.dumps, dumps),
+                    escape(&join_threads(&blocker.blocked_threads)),
+                ]
+            })
+            .collect();
+        doc.table(&["Blocked", "Root blocker", "Frame", "Dumps", "Blocked threads"], rows);
+    }
+
+    doc.heading(2, "Saturated thread pools");
+    if report.saturated_pools.is_empty() {
+        doc.text("No saturated thread pools found.".to_string());
+    } else {
+        let rows = report
+            .saturated_pools
+            .iter()
+            .map(|pool| {
+                vec![
+                    escape(&pool.name),
+                    format!("{} of {}", pool.saturated_dumps, pool.dumps_seen),
+                    pool.max_size.to_string(),
+                    pool.max_busy.to_string(),
+                ]
+            })
+            .collect();
+        doc.table(&["Pool", "Saturated in", "Max size", "Max busy"], rows);
+    }
+
+    doc.heading(2, "Thread growth");
+    let leaks = &report.leaks;
+    doc.text(format!(
+        "Total threads: {}{}",
+        describe_growth(&leaks.total),
+        if leaks.total.steady { " **GROWING**" } else { "" }
+    ));
+    if leaks.suspects.is_empty() {
+        doc.text("No steadily growing pools or creation stacks found.".to_string());
+    }
+    for growth in &leaks.suspects {
+        let scope = match &growth.scope {
+            GrowthScope::Total => continue,
+            GrowthScope::Pool(name) => format!("Pool {}", code(name)),
+            GrowthScope::CreationStack(entry) => format!("Threads started from {}", code(entry)),
+        };
+        doc.text(format!("- {}: {}", scope, describe_growth(growth)));
+        if let Some(stack) = &growth.sample_stack {
+            doc.blocks.push(Block::Code(stack.iter().take(10).map(|frame| format!("  {}", frame.trim())).collect()));
+        }
+    }
+
+    doc.heading(2, "Known anti-patterns");
+    if report.findings.is_empty() {
+        doc.text("No known anti-patterns found.".to_string());
+    }
+    for finding in &report.findings {
+        doc.heading(
+            3,
+            &format!(
+                "[{:?}] {} ({}, in {} of {} dumps)",
+                finding.severity,
+                escape(&finding.title),
+                code(&finding.rule_id),
+                finding.dumps,
+                finding.total_dumps
+            ),
+        );
+        doc.text(format!(
+            "**Threads:** {}\n\n{}\n\n**See:** {}",
+            escape(&finding.threads.join(", ")),
+            escape(&finding.explanation),
+            finding.remediation
+        ));
+    }
+
+    doc.heading(2, "Chronically blocked threads");
+    let chronically_blocked = &report.chronically_blocked;
+    if chronically_blocked.is_empty() {
+        doc.text("No chronically blocked application threads found.".to_string());
+        return doc;
+    }
+    doc.text(format!("Found {} chronically blocked application threads.", chronically_blocked.len()));
+    if let Some(options) = grouping {
+        let threads = chronically_blocked.iter().map(|blocked| &blocked.thread);
+        push_groups(&mut doc, &group_threads(threads, &options), INLINE_FRAMES);
+        return doc;
+    }
+    for blocked in chronically_blocked {
+        doc.heading(3, &escape(&blocked.thread_ref().to_string()));
+        doc.text(format!(
+            "Blocked in {} of {} dumps, {} in a row over {}s, score {:.2}. State {}, category {:?}.",
+            blocked.blocked_dumps,
+            blocked.total_dumps,
+            blocked.longest_streak,
+            blocked.longest_streak_secs,
+            blocked.score,
+            blocked.thread.state,
+            blocked.thread.category
+        ));
+        doc.stack(&blocked.thread.frames, 5);
+        if blocked.thread.frames.len() > 5 {
+            doc.full_stack(&blocked.thread);
+        }
+    }
+    doc
+}
+
+/// `diff`.
+pub fn diff_markdown(diff: &DumpDiff) -> Markdown {
+    let mut doc = Markdown::default();
+    doc.heading(1, "Thread Dump Diff");
+    doc.text(format!("**Before:** {}  \n**After:** {}", diff.before_timestamp, diff.after_timestamp));
+    if diff.is_empty() {
+        doc.text("No differences found.".to_string());
+        return doc;
+    }
+    let names = |names: &[String]| names.iter().map(|name| vec![escape(name)]).collect();
+    if !diff.appeared.is_empty() {
+        doc.heading(2, &format!("Appeared ({})", diff.appeared.len()));
+        doc.table(&["Thread"], names(&diff.appeared));
+    }
+    if !diff.disappeared.is_empty() {
+        doc.heading(2, &format!("Disappeared ({})", diff.disappeared.len()));
+        doc.table(&["Thread"], names(&diff.disappeared));
+    }
+    if !diff.state_changes.is_empty() {
+        doc.heading(2, &format!("State changes ({})", diff.state_changes.len()));
+        let rows = diff
+            .state_changes
+            .iter()
+            .map(|change| vec![escape(&change.thread), change.from.clone(), change.to.clone()])
+            .collect();
+        doc.table(&["Thread", "Before", "After"], rows);
+    }
+    if !diff.stack_changes.is_empty() {
+        doc.heading(2, &format!("Stack changes ({})", diff.stack_changes.len()));
+        let rows = diff
+            .stack_changes
+            .iter()
+            .map(|change| {
+                vec![
+                    escape(&change.thread),
+                    code(change.frame_before.as_deref().unwrap_or("(no frame)")),
+                    code(change.frame_after.as_deref().unwrap_or("(no frame)")),
+                ]
+            })
+            .collect();
+        doc.table(&["Thread", "Before", "After"], rows);
+    }
+    if !diff.pool_changes.is_empty() {
+        doc.heading(2, &format!("Pool size changes ({})", diff.pool_changes.len()));
+        let rows = diff
+            .pool_changes
+            .iter()
+            .map(|change| vec![escape(&change.pool), change.before.to_string(), change.after.to_string()])
+            .collect();
+        doc.table(&["Pool", "Before", "After"], rows);
+    }
+    if !diff.new_contention.is_empty() {
+        doc.heading(2, &format!("New lock contention ({})", diff.new_contention.len()));
+        let rows = diff
+            .new_contention
+            .iter()
+            .map(|lock| {
+                vec![
+                    code(&format!("<{}> (a {})", lock.address, lock.class_name)),
+                    escape(&lock.holder.to_string()),
+                    escape(&join_threads(&lock.waiters)),
+                ]
+            })
+            .collect();
+        doc.table(&["Lock", "Held by", "Waited on by"], rows);
+    }
+    doc
+}
+
+/// `summary`.
+pub fn summary_markdown(summary: &Summary) -> Markdown {
+    let mut doc = Markdown::default();
+    doc.heading(1, &format!("Health: {}/100", summary.health_score));
+    let mut rows = vec![
+        vec!["Dumps".to_string(), summary.dumps.to_string()],
+        vec!["Deadlock".to_string(), if summary.deadlocked { "**YES**" } else { "no" }.to_string()],
+        vec!["Threads".to_string(), summary.threads.to_string()],
+        vec!["Blocked threads".to_string(), summary.blocked_threads.to_string()],
+    ];
+    if summary.thread_counts.len() > 1 {
+        let counts: Vec<String> = summary.thread_counts.iter().map(|count| count.to_string()).collect();
+        rows.push(vec![
+            "Thread count trend".to_string(),
+            format!("{} ({:+.1} per dump)", counts.join(" → "), summary.thread_trend_per_dump),
+        ]);
+    }
+    if !summary.saturated_pools.is_empty() {
+        rows.push(vec!["Saturated pools".to_string(), escape(&summary.saturated_pools.join(", "))]);
+    }
+    doc.table(&["", ""], rows);
+    doc.heading(2, "Thread states");
+    doc.count_table(&["State", "Threads"], &summary.states);
+
+    doc.heading(2, "Findings");
+    if summary.findings.is_empty() {
+        doc.text("No problems found.".to_string());
+        return doc;
+    }
+    let findings: Vec<String> = summary
+        .findings
+        .iter()
+        .enumerate()
+        .map(|(index, finding)| {
+            format!(
+                "{}. **[{:?}]** {} (see *{}* in `analyze`)",
+                index + 1,
+                finding.severity,
+                escape(&finding.title),
+                finding.section.label()
+            )
+        })
+        .collect();
+    doc.text(findings.join("\n"));
+    doc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use thread_lens::parser::parse_jstack_output;
+
+    fn block_sample() -> ThreadDump {
+        let dir = "../thread-lens/src/test_data/java-app-openjdk8_20250920201915_block";
+        let mut paths: Vec<_> = fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        paths.sort();
+        parse_jstack_output(&fs::read_to_string(&paths[0]).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_render_keeps_cut_documents_within_budget_and_valid() {
+        let mut dump = block_sample();
+        for thread in &mut dump.threads {
+            thread.name = format!("工作线程-Ü-{}", thread.name);
+        }
+        let doc = view_markdown(&dump);
+        let full = doc.render(None);
+        assert!(full.contains("工作线程-Ü-"));
+
+        for max_chars in (MIN_MAX_CHARS..char_count(&full)).step_by(53) {
+            let out = doc.render(Some(max_chars));
+            assert!(char_count(&out) <= max_chars, "{} characters for a budget of {}", char_count(&out), max_chars);
+            assert!(out.ends_with(" sections omitted._\n"));
+
+            let fences = out.lines().filter(|line| line.starts_with("```")).count();
+            assert_eq!(fences % 2, 0, "unclosed code fence at {}:\n{}", max_chars, out);
+            assert_eq!(out.matches("<details>").count(), out.matches("</details>").count());
+
+            let body = &out[..out.rfind("\n\n_Truncated").unwrap()];
+            let last_block = body.rsplit("\n\n").next().unwrap();
+            assert!(!last_block.starts_with('#'), "dangling heading at {}:\n{}", max_chars, out);
+        }
+    }
+
+    #[test]
+    fn test_render_cuts_tables_by_rows() {
+        let mut doc = Markdown::default();
+        doc.heading(1, "Pools");
+        doc.table(&["Pool", "Threads"], (0..50).map(|n| vec![format!("pool-{}", n), n.to_string()]).collect());
+        let out = doc.render(Some(300));
+        assert!(char_count(&out) <= 300);
+        assert!(out.contains(" more rows omitted._"));
+        assert!(out.starts_with("# Pools\n\n| Pool | Threads |"));
+
+        assert_eq!(doc.render(Some(10)), "");
+    }
+}
@@ -1,4 +1,26 @@
-use thread_lens::models::ThreadDump;
+use serde::Serialize;
+use thread_lens::analyzer::baseline::{Baseline, Deviation, DeviationKind};
+use thread_lens::analyzer::blockers::join_threads;
+use thread_lens::analyzer::diff::DumpDiff;
+use thread_lens::analyzer::gate::GateResult;
+use thread_lens::analyzer::groups::{group_threads, SignatureOptions, StackGroup};
+use thread_lens::analyzer::leaks::{GrowthScope, LeakReport, ThreadGrowth};
+use thread_lens::analyzer::pools::PoolStats;
+use thread_lens::analyzer::report::AnalysisReport;
+use thread_lens::analyzer::summary::Summary;
+use thread_lens::analyzer::timeline::Timeline;
+use thread_lens::antipatterns::Finding;
+use thread_lens::catalog::library_context;
+use thread_lens::models::{CategorizedFrame, ThreadDump};
+use thread_lens::query::QueryResult;
+
+/// `[Framework: HikariCP]` for catalogued library frames, `[Application]` otherwise.
+pub fn frame_label(frame: &CategorizedFrame) -> String {
+    match &frame.library {
+        Some(library) => format!("[{:?}: {}]", frame.category, library),
+        None => format!("[{:?}]", frame.category),
+    }
+}
 
 pub fn print_text_view(dump: &ThreadDump) {
     println!("--- Thread Dump Analysis ---");
@@ -9,23 +31,367 @@ pub fn print_text_view(dump: &ThreadDump) {
     for thread in &dump.threads {
         println!("\n- Name: {}", thread.name);
         println!("  State: {}", thread.state);
-        println!("  Category: {:?}", thread.category);
+        println!(
+            "  Category: {:?} (confidence {:.2}: {})",
+            thread.category, thread.category_confidence, thread.category_reason
+        );
+        if let Some(context) = library_context(&thread.frames) {
+            println!("  In: {}", context);
+        }
         println!("  Frames:");
         for frame in &thread.frames {
-            println!("    [{:?}] {}", frame.category, frame.line);
+            println!("    {} {}", frame_label(frame), frame.line);
+        }
+    }
+}
+
+pub fn print_text_groups(groups: &[StackGroup]) {
+    for group in groups {
+        let states: Vec<String> = group
+            .states
+            .iter()
+            .map(|(state, count)| format!("{}={}", state, count))
+            .collect();
+        println!(
+            "\n{} threads × this stack, states: {}, names: {}",
+            group.count,
+            states.join(", "),
+            group.name_summary()
+        );
+        if group.frames.is_empty() {
+            println!("    (no frames)");
+        }
+        for frame in &group.frames {
+            println!("    {} {}", frame_label(frame), frame.line);
+        }
+    }
+}
+
+pub fn print_grouped_text_view(dump: &ThreadDump, groups: &[StackGroup]) {
+    println!("--- Thread Dump Analysis ---");
+    println!("JVM Version: {}", dump.jvm_version);
+    println!("Timestamp: {}", dump.timestamp);
+    println!("Total Threads: {}", dump.threads.len());
+    println!("\n--- Stack Groups ({}) ---", groups.len());
+    print_text_groups(groups);
+}
+
+pub fn print_pools_text_view(dump: &ThreadDump, pools: &[PoolStats]) {
+    println!("--- Thread Dump Analysis ---");
+    println!("JVM Version: {}", dump.jvm_version);
+    println!("Timestamp: {}", dump.timestamp);
+    println!("Total Threads: {}", dump.threads.len());
+    println!("\n--- Thread Pools ({}) ---", pools.len());
+    for pool in pools {
+        println!(
+            "\n- Pool: {} (size={}, idle={}, busy={}, blocked={})",
+            pool.name, pool.size, pool.idle, pool.busy, pool.blocked
+        );
+        if !pool.top_busy_stacks.is_empty() {
+            println!("  Top busy stacks:");
+            print_text_groups(&pool.top_busy_stacks);
+        }
+    }
+}
+
+pub fn describe_growth(growth: &ThreadGrowth) -> String {
+    let counts: Vec<String> = growth.counts.iter().map(|count| count.to_string()).collect();
+    let rate = match growth.threads_per_minute {
+        Some(per_minute) => format!("{:+.1}/dump, {:+.2}/min", growth.slope_per_dump, per_minute),
+        None => format!("{:+.1}/dump", growth.slope_per_dump),
+    };
+    format!("{} ({})", counts.join(" -> "), rate)
+}
+
+/// The `analyze` report. With `grouping`, chronically blocked threads with the
+/// same stack are collapsed into one entry.
+pub fn print_analysis_text_view(report: &AnalysisReport, grouping: Option<SignatureOptions>) {
+    let dumps = report.dumps.len();
+    println!("--- Analysis Report ---");
+    println!("Found {} thread dumps to analyze.", dumps);
+
+    if report.root_blockers.is_empty() {
+        println!("\nNo blocked application threads could be traced to a lock holder.");
+    } else {
+        println!("\nRoot blockers:");
+        for blocker in &report.root_blockers {
+            println!(
+                "  - {} threads ultimately blocked by '{}' {} (in {} of {} dumps){}",
+                blocker.blocked_threads.len(),
+                blocker.thread,
+                blocker.frame.as_deref().unwrap_or("(no frames)"),
+                blocker.dumps,
+                dumps,
+                if blocker.deadlocked { " [DEADLOCK]" } else { "" }
+            );
+            if let Some(note) = blocker.unresolved_note() {
+                println!("    Unresolved: the root blocker is {}; the thread that releases it is not in the dump", note);
+            }
+            println!("    Blocked: {}", join_threads(&blocker.blocked_threads));
+        }
+    }
+
+    if report.saturated_pools.is_empty() {
+        println!("\nNo saturated thread pools found.");
+    } else {
+        println!("\nSaturated thread pools:");
+        for pool in &report.saturated_pools {
+            println!(
+                "  - Pool '{}' saturated in {} of {} dumps (max size {}, max busy {})",
+                pool.name, pool.saturated_dumps, pool.dumps_seen, pool.max_size, pool.max_busy
+            );
+        }
+    }
+
+    print_leak_report(&report.leaks);
+    print_findings(&report.findings);
+
+    let chronically_blocked = &report.chronically_blocked;
+    if chronically_blocked.is_empty() {
+        println!("\nNo chronically blocked application threads found.");
+        return;
+    }
+    println!("\nFound {} chronically blocked application threads:", chronically_blocked.len());
+    if let Some(options) = grouping {
+        let threads = chronically_blocked.iter().map(|blocked| &blocked.thread);
+        print_text_groups(&group_threads(threads, &options));
+        return;
+    }
+    for blocked in chronically_blocked {
+        println!(
+            "  - Thread: '{}' (blocked in {} of {} dumps, {} in a row over {}s, score {:.2})",
+            blocked.thread_ref(),
+            blocked.blocked_dumps,
+            blocked.total_dumps,
+            blocked.longest_streak,
+            blocked.longest_streak_secs,
+            blocked.score
+        );
+        println!("    State: {}", blocked.thread.state);
+        println!("    Category: {:?}", blocked.thread.category);
+        if let Some(context) = library_context(&blocked.thread.frames) {
+            println!("    Blocked in: {}", context);
+        }
+        println!("    Stack Trace:");
+        for frame in blocked.thread.frames.iter().take(5) {
+            println!("      {} {}", frame_label(frame), frame.line);
+        }
+    }
+}
+
+pub fn print_gate_text_view(result: &GateResult) {
+    for &check in &result.checks {
+        let failures: Vec<_> = result.failures_of(check).collect();
+        println!("{} {}", if failures.is_empty() { "PASS" } else { "FAIL" }, check.label());
+        for failure in failures {
+            println!("     - [{}] {}", failure.rule_id, failure.message);
+        }
+    }
+    if result.passed() {
+        println!("\nCheck passed.");
+    } else {
+        println!("\nCheck failed: {} problems found.", result.failures.len());
+    }
+}
+
+pub fn print_leak_report(report: &LeakReport) {
+    println!(
+        "\nTotal threads: {}{}",
+        describe_growth(&report.total),
+        if report.total.steady { " [GROWING]" } else { "" }
+    );
+    if report.suspects.is_empty() {
+        println!("No steadily growing pools or creation stacks found.");
+        return;
+    }
+    println!("Possible thread leaks:");
+    for growth in &report.suspects {
+        match &growth.scope {
+            GrowthScope::Total => continue,
+            GrowthScope::Pool(name) => println!("  - Pool '{}': {}", name, describe_growth(growth)),
+            GrowthScope::CreationStack(entry) => {
+                println!("  - Threads started from {}: {}", entry, describe_growth(growth))
+            }
+        }
+        if let Some(stack) = &growth.sample_stack {
+            println!("    Sample stack:");
+            for frame in stack.iter().take(10) {
+                println!("      {}", frame);
+            }
+        }
+    }
+}
+
+pub fn print_findings(findings: &[Finding]) {
+    if findings.is_empty() {
+        println!("\nNo known anti-patterns found.");
+        return;
+    }
+    println!("\nKnown anti-patterns:");
+    for finding in findings {
+        println!(
+            "  - [{:?}] {} ({}, in {} of {} dumps)",
+            finding.severity, finding.title, finding.rule_id, finding.dumps, finding.total_dumps
+        );
+        println!("    Threads: {}", finding.threads.join(", "));
+        println!("    {}", finding.explanation);
+        println!("    See: {}", finding.remediation);
+    }
+}
+
+pub fn print_summary_text_view(summary: &Summary) {
+    println!("--- Summary ---");
+    println!("Health: {}/100", summary.health_score);
+    println!("Dumps: {}", summary.dumps);
+    println!("Deadlock: {}", if summary.deadlocked { "YES" } else { "no" });
+    let states: Vec<String> = summary.states.iter().map(|(state, count)| format!("{}={}", state, count)).collect();
+    println!("Threads: {} ({})", summary.threads, states.join(", "));
+    if summary.thread_counts.len() > 1 {
+        let counts: Vec<String> = summary.thread_counts.iter().map(|count| count.to_string()).collect();
+        println!("Thread count trend: {} ({:+.1} per dump)", counts.join(" -> "), summary.thread_trend_per_dump);
+    }
+    if !summary.saturated_pools.is_empty() {
+        println!("Saturated pools: {}", summary.saturated_pools.join(", "));
+    }
+
+    if summary.findings.is_empty() {
+        println!("\nNo problems found.");
+        return;
+    }
+    println!("\nFindings:");
+    for (index, finding) in summary.findings.iter().enumerate() {
+        println!("  {}. [{:?}] {}", index + 1, finding.severity, finding.title);
+        println!("     -> see '{}' in `analyze`", finding.section.label());
+    }
+}
+
+pub fn print_deviations_text_view(baseline: &Baseline, deviations: &[Deviation]) {
+    println!("--- Baseline Comparison ---");
+    println!("Baseline: {} dumps", baseline.dumps);
+    if deviations.is_empty() {
+        println!("\nNo significant deviations from the baseline.");
+        return;
+    }
+    println!("\nSignificant deviations:");
+    for deviation in deviations {
+        let what = match deviation.kind {
+            DeviationKind::Stack => "Stack",
+            DeviationKind::Pool => "Pool",
+            DeviationKind::State => "State",
+        };
+        let mut lines = deviation.key.lines();
+        println!(
+            "  - {} {}: {:.1} -> {:.1} threads per dump (z = {:+.1})",
+            what,
+            lines.next().unwrap_or("").trim(),
+            deviation.baseline.mean,
+            deviation.observed.mean,
+            deviation.z_score
+        );
+        for line in lines.take(4) {
+            println!("      {}", line.trim());
+        }
+    }
+}
+
+pub fn print_query_text_view(result: &QueryResult) {
+    let across = if result.dumps > 1 { format!(" across {} dumps", result.dumps) } else { String::new() };
+    println!("{} of {} threads match{}", result.matched, result.total_threads, across);
+    if !result.by.is_empty() {
+        println!("\n{:>7}  {}", "count", result.by.join(" | "));
+        for group in &result.groups {
+            println!("{:>7}  {}", group.count, group.key.join(" | "));
+        }
+        return;
+    }
+    let several_dumps = result.dumps > 1;
+    for matched in &result.threads {
+        let thread = &matched.thread;
+        let when = if several_dumps { format!("{}  ", matched.timestamp) } else { String::new() };
+        println!("\n{}{} ({})", when, thread.name, thread.state);
+        for frame in &thread.frames {
+            println!("    {} {}", frame_label(frame), frame.line);
+        }
+    }
+}
+
+pub fn print_diff_text_view(diff: &DumpDiff) {
+    println!("--- Thread Dump Diff ---");
+    println!("Before: {}", diff.before_timestamp);
+    println!("After: {}", diff.after_timestamp);
+    if diff.is_empty() {
+        println!("\nNo differences found.");
+        return;
+    }
+    if !diff.appeared.is_empty() {
+        println!("\nAppeared ({}):", diff.appeared.len());
+        for name in &diff.appeared {
+            println!("  + {}", name);
+        }
+    }
+    if !diff.disappeared.is_empty() {
+        println!("\nDisappeared ({}):", diff.disappeared.len());
+        for name in &diff.disappeared {
+            println!("  - {}", name);
+        }
+    }
+    if !diff.state_changes.is_empty() {
+        println!("\nState changes ({}):", diff.state_changes.len());
+        for change in &diff.state_changes {
+            println!("  {}: {} -> {}", change.thread, change.from, change.to);
         }
     }
+    if !diff.stack_changes.is_empty() {
+        println!("\nStack changes ({}):", diff.stack_changes.len());
+        for change in &diff.stack_changes {
+            println!("  {}:", change.thread);
+            println!("    before: {}", change.frame_before.as_deref().unwrap_or("(no frame)"));
+            println!("    after:  {}", change.frame_after.as_deref().unwrap_or("(no frame)"));
+        }
+    }
+    if !diff.pool_changes.is_empty() {
+        println!("\nPool size changes ({}):", diff.pool_changes.len());
+        for change in &diff.pool_changes {
+            println!("  {}: {} -> {}", change.pool, change.before, change.after);
+        }
+    }
+    if !diff.new_contention.is_empty() {
+        println!("\nNew lock contention ({}):", diff.new_contention.len());
+        for lock in &diff.new_contention {
+            println!(
+                "  <{}> (a {}) held by '{}', waited on by: {}",
+                lock.address,
+                lock.class_name,
+                lock.holder,
+                join_threads(&lock.waiters)
+            );
+        }
+    }
+}
+
+pub fn print_timeline_text_view(timeline: &Timeline) {
+    println!("--- Thread State Timeline ---");
+    match (timeline.timestamps.first(), timeline.timestamps.last()) {
+        (Some(first), Some(last)) => {
+            println!("{} dumps from {} to {}", timeline.timestamps.len(), first, last)
+        }
+        _ => println!("No dumps found."),
+    }
+    println!("Legend: R=RUNNABLE B=BLOCKED W=WAITING T=TIMED_WAITING ?=other .=absent\n");
+    for thread in &timeline.threads {
+        println!("{}  {}", thread.state_strip(), thread.thread);
+    }
 }
 
-pub fn print_json_view(dump: &ThreadDump) {
-    match serde_json::to_string_pretty(dump) {
+pub fn print_json_view<T: Serialize + ?Sized>(value: &T) {
+    match serde_json::to_string_pretty(value) {
         Ok(json) => println!("{}", json),
         Err(e) => eprintln!("Error serializing to JSON: {}", e),
     }
 }
 
-pub fn print_yaml_view(dump: &ThreadDump) {
-    match serde_yaml::to_string(dump) {
+pub fn print_yaml_view<T: Serialize + ?Sized>(value: &T) {
+    match serde_yaml::to_string(value) {
         Ok(yaml) => println!("{}", yaml),
         Err(e) => eprintln!("Error serializing to YAML: {}", e),
     }
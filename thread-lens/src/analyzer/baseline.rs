@@ -0,0 +1,250 @@
+use crate::analyzer::groups::{stack_signature, SignatureOptions};
+use crate::analyzer::in_time_order;
+use crate::analyzer::pools::{pool_family, pool_stats};
+use crate::models::ThreadDump;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Version of the baseline file format written by [`build_baseline`].
+pub const BASELINE_VERSION: u32 = 1;
+
+/// How a count (threads per dump) was distributed over the baseline series.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CountStats {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: usize,
+    pub max: usize,
+}
+
+impl CountStats {
+    fn from_counts(counts: &[usize]) -> CountStats {
+        let n = counts.len().max(1) as f64;
+        let mean = counts.iter().sum::<usize>() as f64 / n;
+        let variance = counts.iter().map(|&count| (count as f64 - mean).powi(2)).sum::<f64>() / n;
+        CountStats {
+            mean,
+            std_dev: variance.sqrt(),
+            min: counts.iter().copied().min().unwrap_or(0),
+            max: counts.iter().copied().max().unwrap_or(0),
+        }
+    }
+}
+
+/// A profile of a healthy dump series: how many threads had each stack, were
+/// in each pool and were in each state, per dump.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Baseline {
+    pub version: u32,
+    pub dumps: usize,
+    pub first_dump: Option<DateTime<Utc>>,
+    pub last_dump: Option<DateTime<Utc>>,
+    /// Keyed by stack signature (frames without line numbers, one per line).
+    pub stacks: BTreeMap<String, CountStats>,
+    pub pools: BTreeMap<String, CountStats>,
+    pub states: BTreeMap<String, CountStats>,
+}
+
+impl Baseline {
+    /// Reads a baseline file, refusing versions this build does not understand.
+    pub fn from_json(content: &str) -> Result<Baseline, String> {
+        let baseline: Baseline = serde_json::from_str(content).map_err(|e| e.to_string())?;
+        if baseline.version != BASELINE_VERSION {
+            return Err(format!(
+                "unsupported baseline version {} (expected {})",
+                baseline.version, BASELINE_VERSION
+            ));
+        }
+        Ok(baseline)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DeviationKind {
+    Stack,
+    Pool,
+    State,
+}
+
+/// A count that moved away from the baseline by more than chance allows.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Deviation {
+    pub kind: DeviationKind,
+    pub key: String,
+    pub baseline: CountStats,
+    pub observed: CountStats,
+    /// Change in the mean, in standard deviations of the baseline.
+    pub z_score: f64,
+}
+
+/// When a change in a count is reported by [`compare_to_baseline`].
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct BaselineOptions {
+    /// Minimum change in the mean, in baseline standard deviations.
+    pub min_z_score: f64,
+    /// Minimum change in the mean, in threads per dump.
+    pub min_change: f64,
+}
+
+impl Default for BaselineOptions {
+    fn default() -> Self {
+        BaselineOptions {
+            min_z_score: 3.0,
+            min_change: 2.0,
+        }
+    }
+}
+
+type Profile = BTreeMap<String, Vec<usize>>;
+
+/// Per-dump counts of every stack, pool and state, with zeros for dumps
+/// where a key did not occur.
+fn profile(dumps: &[&ThreadDump]) -> (Profile, Profile, Profile) {
+    let options = SignatureOptions {
+        ignore_line_numbers: true,
+        ..Default::default()
+    };
+    let (mut stacks, mut pools, mut states) = (Profile::new(), Profile::new(), Profile::new());
+    for (index, dump) in dumps.iter().enumerate() {
+        let count = |profile: &mut Profile, key: String, amount: usize| {
+            let counts = profile.entry(key).or_insert_with(|| vec![0; dumps.len()]);
+            counts[index] += amount;
+        };
+        for thread in dump.threads.iter().filter(|thread| !thread.frames.is_empty()) {
+            count(&mut stacks, stack_signature(&thread.frames, &options), 1);
+        }
+        for thread in &dump.threads {
+            count(&mut states, thread.state.clone(), 1);
+        }
+        for pool in pool_stats(dump) {
+            count(&mut pools, pool_family(&pool.name), pool.size);
+        }
+    }
+    (stacks, pools, states)
+}
+
+fn stats(profile: Profile) -> BTreeMap<String, CountStats> {
+    profile
+        .into_iter()
+        .map(|(key, counts)| (key, CountStats::from_counts(&counts)))
+        .collect()
+}
+
+/// Profiles a known-good series.
+pub fn build_baseline(dumps: &[ThreadDump]) -> Baseline {
+    let ordered = in_time_order(dumps);
+    let (stacks, pools, states) = profile(&ordered);
+    Baseline {
+        version: BASELINE_VERSION,
+        dumps: ordered.len(),
+        first_dump: ordered.first().map(|dump| dump.timestamp),
+        last_dump: ordered.last().map(|dump| dump.timestamp),
+        stacks: stats(stacks),
+        pools: stats(pools),
+        states: stats(states),
+    }
+}
+
+/// Compares a series against a baseline and returns the counts whose mean
+/// changed significantly, largest change first. A baseline's spread is never
+/// taken to be below that of a Poisson count (the square root of the mean, and
+/// at least 1), so keys that were constant in a short baseline are not flagged
+/// for a change of one thread.
+pub fn compare_to_baseline(baseline: &Baseline, dumps: &[ThreadDump], options: &BaselineOptions) -> Vec<Deviation> {
+    let ordered = in_time_order(dumps);
+    let (stacks, pools, states) = profile(&ordered);
+    let absent = CountStats::from_counts(&vec![0; ordered.len()]);
+    let never_seen = CountStats::from_counts(&[0]);
+
+    let mut deviations = Vec::new();
+    for (kind, expected, observed) in [
+        (DeviationKind::Stack, &baseline.stacks, stats(stacks)),
+        (DeviationKind::Pool, &baseline.pools, stats(pools)),
+        (DeviationKind::State, &baseline.states, stats(states)),
+    ] {
+        let keys: BTreeSet<&String> = expected.keys().chain(observed.keys()).collect();
+        for key in keys {
+            let before = expected.get(key).unwrap_or(&never_seen);
+            let after = observed.get(key).unwrap_or(&absent);
+            let change = after.mean - before.mean;
+            let spread = before.std_dev.max(before.mean.sqrt()).max(1.0);
+            let z_score = change / spread;
+            if z_score.abs() >= options.min_z_score && change.abs() >= options.min_change {
+                deviations.push(Deviation {
+                    kind,
+                    key: key.clone(),
+                    baseline: before.clone(),
+                    observed: after.clone(),
+                    z_score,
+                });
+            }
+        }
+    }
+    deviations.sort_by(|a, b| b.z_score.abs().total_cmp(&a.z_score.abs()).then(a.kind.cmp(&b.kind)));
+    deviations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classifier::Classifier;
+    use crate::models::NormalizedThread;
+    use chrono::{Duration, TimeZone};
+
+    fn dump_at(minute: i64, threads: &[(&str, &str, &str, usize)]) -> ThreadDump {
+        let classifier = Classifier::default();
+        ThreadDump {
+            jvm_version: "17".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2025, 9, 21, 3, 0, 0).unwrap() + Duration::minutes(minute),
+            threads: threads
+                .iter()
+                .flat_map(|&(name, state, frame, count)| {
+                    let classifier = &classifier;
+                    (0..count).map(move |index| NormalizedThread {
+                        name: format!("{}-{}", name, index),
+                        state: state.to_string(),
+                        frames: vec![classifier.classify_frame(frame)],
+                        ..Default::default()
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    const LOCK: &str = "at com.mycorp.Registry.lookup(Registry.java:40)";
+    const IDLE: &str = "at java.util.concurrent.ThreadPoolExecutor.getTask(ThreadPoolExecutor.java:1074)";
+
+    #[test]
+    fn test_compare_to_baseline_reports_only_significant_changes() {
+        // 40 threads blocked on the registry is normal for this service.
+        let healthy: Vec<ThreadDump> = (0..4)
+            .map(|minute| dump_at(minute, &[("registry", "BLOCKED", LOCK, 40 + minute as usize % 2), ("pool-1-thread", "WAITING", IDLE, 8)]))
+            .collect();
+        let baseline = Baseline::from_json(&serde_json::to_string(&build_baseline(&healthy)).unwrap()).unwrap();
+        assert_eq!(baseline.dumps, 4);
+        assert_eq!(baseline.pools["pool"].mean, 8.0);
+
+        // After a restart the executor is numbered differently.
+        let same = vec![dump_at(10, &[("registry", "BLOCKED", LOCK, 41), ("pool-4-thread", "WAITING", IDLE, 8)])];
+        assert!(compare_to_baseline(&baseline, &same, &Default::default()).is_empty());
+
+        let worse = vec![dump_at(10, &[("registry", "BLOCKED", LOCK, 80), ("pool-1-thread", "WAITING", IDLE, 8)])];
+        let deviations = compare_to_baseline(&baseline, &worse, &Default::default());
+        let kinds: Vec<(DeviationKind, &str)> = deviations.iter().map(|d| (d.kind, d.key.as_str())).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (DeviationKind::Stack, "at com.mycorp.Registry.lookup(Registry.java)"),
+                (DeviationKind::Pool, "registry"),
+                (DeviationKind::State, "BLOCKED"),
+            ]
+        );
+        assert!(deviations[0].z_score > 3.0);
+
+        let mut future = serde_json::to_value(&baseline).unwrap();
+        future["version"] = serde_json::json!(BASELINE_VERSION + 1);
+        assert!(Baseline::from_json(&future.to_string()).is_err());
+    }
+}
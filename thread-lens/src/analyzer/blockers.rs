@@ -0,0 +1,554 @@
+use crate::analyzer::thread_ids;
+use crate::models::{FrameCategory, LockAction, LockInfo, NormalizedThread, ThreadCategory, ThreadDump};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+/// An edge of the wait-for graph: `waiter` cannot proceed until `holder`
+/// releases `lock`. Threads are referenced by their index in `ThreadDump::threads`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WaitForEdge {
+    pub waiter: usize,
+    pub holder: usize,
+    pub lock: LockInfo,
+}
+
+/// Threads queued on a lock that another thread holds.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LockContention {
+    pub address: String,
+    pub class_name: String,
+    pub holder: ThreadRef,
+    pub waiters: Vec<ThreadRef>,
+}
+
+/// A thread by name and id, so threads that share a name are told apart.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ThreadRef {
+    pub name: String,
+    /// The `tid` or `Id` of the thread, or `#n` for the n-th thread of that
+    /// name when the dump has no ids.
+    pub id: String,
+}
+
+impl fmt::Display for ThreadRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.id)
+    }
+}
+
+/// `A (0x1), B (0x2)`.
+pub fn join_threads(threads: &[ThreadRef]) -> String {
+    threads.iter().map(ThreadRef::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// The path from a blocked thread to the thread ultimately responsible for it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct BlockerChain {
+    pub thread: ThreadRef,
+    /// Threads from the blocked thread (first) to the root blocker (last).
+    pub chain: Vec<ThreadRef>,
+    pub root: ThreadRef,
+    pub root_frame: Option<String>,
+    /// What the root is itself waiting for when the dump does not say who will
+    /// release it: a `Condition`, a latch, `Object.wait()`, or a lock without a
+    /// listed owner. The chain is unresolved then, and the thread that signals
+    /// the root is the real culprit.
+    pub root_waiting_on: Option<LockInfo>,
+    /// True when the chain loops back on itself instead of reaching a free thread.
+    pub deadlocked: bool,
+}
+
+/// "N threads ultimately blocked by thread X at frame Y".
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RootBlocker {
+    pub thread: ThreadRef,
+    pub frame: Option<String>,
+    /// Set when the root blocker is itself waiting for a thread the dump does
+    /// not name; see [`BlockerChain::root_waiting_on`].
+    pub waiting_on: Option<LockInfo>,
+    pub blocked_threads: Vec<ThreadRef>,
+    /// Number of dumps in which this root blocker was seen.
+    pub dumps: usize,
+    /// True when the root blocker is itself part of a deadlock.
+    pub deadlocked: bool,
+}
+
+impl RootBlocker {
+    /// `itself waiting on <0x...> (a java.util.concurrent.CountDownLatch$Sync)`
+    /// for an unresolved root blocker.
+    pub fn unresolved_note(&self) -> Option<String> {
+        self.waiting_on
+            .as_ref()
+            .map(|lock| format!("itself waiting on <{}> (a {})", lock.address, lock.class_name))
+    }
+}
+
+/// Threads stuck in a lock cycle, with the threads queued behind them.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Deadlock {
+    /// The threads of the cycle.
+    pub threads: Vec<ThreadRef>,
+    /// Other threads waiting, directly or not, for a thread of the cycle.
+    pub blocked_threads: Vec<ThreadRef>,
+    /// Number of dumps in which these threads formed the cycle.
+    pub dumps: usize,
+}
+
+fn is_waiting_for_lock(action: &LockAction) -> bool {
+    matches!(
+        action,
+        LockAction::WaitingToLock | LockAction::ParkingToWaitFor | LockAction::WaitingToReLock
+    )
+}
+
+/// Builds the wait-for graph of a single dump from the lock annotations of its threads.
+///
+/// Lock owners are only known for monitors and ownable synchronizers (`jstack -l`);
+/// a thread parked on a `Condition` or latch has no edge, since the dump does not
+/// say who will signal it. [`find_blocker_chains`] reports such a thread as an
+/// unresolved root.
+pub fn build_wait_for_graph(dump: &ThreadDump) -> Vec<WaitForEdge> {
+    let mut holders: HashMap<&str, usize> = HashMap::new();
+    for (index, thread) in dump.threads.iter().enumerate() {
+        // A thread inside Object.wait() still reports the monitor as "locked"
+        // on the synchronized frame, but it has released it.
+        let released: HashSet<&str> = thread
+            .locks
+            .iter()
+            .filter(|lock| lock.action == LockAction::WaitingOn)
+            .map(|lock| lock.address.as_str())
+            .collect();
+        for lock in &thread.locks {
+            if lock.action == LockAction::Locked && !released.contains(lock.address.as_str()) {
+                holders.insert(lock.address.as_str(), index);
+            }
+        }
+    }
+
+    let mut edges = Vec::new();
+    for (index, thread) in dump.threads.iter().enumerate() {
+        let edge = thread
+            .locks
+            .iter()
+            .filter(|lock| is_waiting_for_lock(&lock.action))
+            .find_map(|lock| match holders.get(lock.address.as_str()) {
+                Some(&holder) if holder != index => Some(WaitForEdge {
+                    waiter: index,
+                    holder,
+                    lock: lock.clone(),
+                }),
+                _ => None,
+            });
+        edges.extend(edge);
+    }
+    edges
+}
+
+/// Locks with at least one waiter in a dump, most contended first.
+pub fn lock_contention(dump: &ThreadDump) -> Vec<LockContention> {
+    let ids = thread_ids(dump);
+    let thread_ref = |index: usize| ThreadRef {
+        name: dump.threads[index].name.clone(),
+        id: ids[index].clone(),
+    };
+    let mut by_lock: HashMap<String, LockContention> = HashMap::new();
+    for edge in build_wait_for_graph(dump) {
+        by_lock
+            .entry(edge.lock.address.clone())
+            .or_insert_with(|| LockContention {
+                address: edge.lock.address.clone(),
+                class_name: edge.lock.class_name.clone(),
+                holder: thread_ref(edge.holder),
+                waiters: Vec::new(),
+            })
+            .waiters
+            .push(thread_ref(edge.waiter));
+    }
+
+    let mut contention: Vec<LockContention> = by_lock.into_values().collect();
+    for lock in contention.iter_mut() {
+        lock.waiters.sort();
+    }
+    contention.sort_by(|a, b| b.waiters.len().cmp(&a.waiters.len()).then(a.address.cmp(&b.address)));
+    contention
+}
+
+/// The frame to blame on a root blocker: its innermost application frame,
+/// or its top frame when it is not running application code.
+fn blame_frame(thread: &NormalizedThread) -> Option<String> {
+    thread
+        .frames
+        .iter()
+        .find(|frame| frame.category == FrameCategory::Application)
+        .or_else(|| thread.frames.first())
+        .map(|frame| frame.line.clone())
+}
+
+/// What a thread is waiting for, whether or not the dump names the thread
+/// that will release it.
+fn waiting_on(thread: &NormalizedThread) -> Option<LockInfo> {
+    thread
+        .locks
+        .iter()
+        .find(|lock| lock.action == LockAction::WaitingOn || is_waiting_for_lock(&lock.action))
+        .cloned()
+}
+
+/// Follows the wait-for graph from every blocked application thread to its root blocker.
+pub fn find_blocker_chains(dump: &ThreadDump) -> Vec<BlockerChain> {
+    let ids = thread_ids(dump);
+    let thread_ref = |index: usize| ThreadRef {
+        name: dump.threads[index].name.clone(),
+        id: ids[index].clone(),
+    };
+    let waits_for: HashMap<usize, usize> = build_wait_for_graph(dump)
+        .into_iter()
+        .map(|edge| (edge.waiter, edge.holder))
+        .collect();
+
+    let mut starts: Vec<usize> = waits_for
+        .keys()
+        .copied()
+        .filter(|&index| dump.threads[index].category == ThreadCategory::Application)
+        .collect();
+    starts.sort_unstable();
+
+    starts
+        .into_iter()
+        .map(|start| {
+            let mut path = vec![start];
+            let mut seen = HashSet::from([start]);
+            let mut deadlocked = false;
+            let mut current = start;
+            while let Some(&next) = waits_for.get(&current) {
+                if !seen.insert(next) {
+                    deadlocked = true;
+                    break;
+                }
+                path.push(next);
+                current = next;
+            }
+            let root = &dump.threads[current];
+            BlockerChain {
+                thread: thread_ref(start),
+                chain: path.iter().map(|&index| thread_ref(index)).collect(),
+                root: thread_ref(current),
+                root_frame: blame_frame(root),
+                root_waiting_on: if deadlocked { None } else { waiting_on(root) },
+                deadlocked,
+            }
+        })
+        .collect()
+}
+
+/// Groups blocker chains by root blocker, most blocked threads first.
+pub fn summarize_root_blockers(chains: &[BlockerChain]) -> Vec<RootBlocker> {
+    let mut grouped: HashMap<(ThreadRef, Option<String>), RootBlocker> = HashMap::new();
+    for chain in chains {
+        let blocker = grouped
+            .entry((chain.root.clone(), chain.root_frame.clone()))
+            .or_insert_with(|| RootBlocker {
+                thread: chain.root.clone(),
+                frame: chain.root_frame.clone(),
+                waiting_on: chain.root_waiting_on.clone(),
+                blocked_threads: Vec::new(),
+                dumps: 1,
+                deadlocked: false,
+            });
+        blocker.blocked_threads.push(chain.thread.clone());
+        blocker.deadlocked |= chain.deadlocked;
+    }
+
+    let mut summary: Vec<RootBlocker> = grouped.into_values().collect();
+    sort_root_blockers(&mut summary);
+    summary
+}
+
+/// Root blockers across a series of dumps. Each root blocker reports the
+/// blocked threads of the dump where it held up the most threads.
+pub fn summarize_root_blockers_across(dumps: &[ThreadDump]) -> Vec<RootBlocker> {
+    let mut merged: HashMap<(ThreadRef, Option<String>), RootBlocker> = HashMap::new();
+    for dump in dumps {
+        for blocker in summarize_root_blockers(&find_blocker_chains(dump)) {
+            let key = (blocker.thread.clone(), blocker.frame.clone());
+            match merged.get_mut(&key) {
+                Some(existing) => {
+                    existing.dumps += 1;
+                    existing.deadlocked |= blocker.deadlocked;
+                    if blocker.blocked_threads.len() > existing.blocked_threads.len() {
+                        existing.blocked_threads = blocker.blocked_threads;
+                        existing.waiting_on = blocker.waiting_on;
+                    }
+                }
+                None => {
+                    merged.insert(key, blocker);
+                }
+            }
+        }
+    }
+
+    let mut summary: Vec<RootBlocker> = merged.into_values().collect();
+    sort_root_blockers(&mut summary);
+    summary
+}
+
+/// The lock cycles of one dump, each with the other threads waiting, directly
+/// or not, for a thread of the cycle. Threads are indices into `dump.threads`.
+fn dump_deadlocks(dump: &ThreadDump) -> Vec<(BTreeSet<usize>, BTreeSet<usize>)> {
+    let waits_for: HashMap<usize, usize> = build_wait_for_graph(dump)
+        .into_iter()
+        .map(|edge| (edge.waiter, edge.holder))
+        .collect();
+    let mut starts: Vec<usize> = waits_for.keys().copied().collect();
+    starts.sort_unstable();
+
+    let mut cycles: Vec<(BTreeSet<usize>, BTreeSet<usize>)> = Vec::new();
+    // The cycle each thread is part of or waiting for.
+    let mut reaches: HashMap<usize, usize> = HashMap::new();
+    for start in starts {
+        if reaches.contains_key(&start) {
+            continue;
+        }
+        let mut path = vec![start];
+        let mut current = start;
+        while let Some(&next) = waits_for.get(&current) {
+            if let Some(&cycle) = reaches.get(&next) {
+                for &thread in &path {
+                    reaches.insert(thread, cycle);
+                    cycles[cycle].1.insert(thread);
+                }
+                break;
+            }
+            if let Some(position) = path.iter().position(|&thread| thread == next) {
+                let cycle = cycles.len();
+                cycles.push((path[position..].iter().copied().collect(), path[..position].iter().copied().collect()));
+                for &thread in &path {
+                    reaches.insert(thread, cycle);
+                }
+                break;
+            }
+            path.push(next);
+            current = next;
+        }
+    }
+    cycles
+}
+
+/// Lock cycles across a series. Cycles are found in each dump from thread ids,
+/// so unrelated threads sharing a name are never merged, and a cycle seen in
+/// several dumps is reported once with the blocked threads of the dump where
+/// it held up the most threads. Largest deadlock first.
+pub fn find_deadlocks(dumps: &[ThreadDump]) -> Vec<Deadlock> {
+    let mut merged: HashMap<Vec<ThreadRef>, Deadlock> = HashMap::new();
+    for dump in dumps {
+        let ids = thread_ids(dump);
+        let thread_refs = |threads: &BTreeSet<usize>| -> Vec<ThreadRef> {
+            let mut refs: Vec<ThreadRef> = threads
+                .iter()
+                .map(|&index| ThreadRef {
+                    name: dump.threads[index].name.clone(),
+                    id: ids[index].clone(),
+                })
+                .collect();
+            refs.sort();
+            refs
+        };
+        for (cycle, blocked) in dump_deadlocks(dump) {
+            let threads = thread_refs(&cycle);
+            let blocked_threads = thread_refs(&blocked);
+            match merged.get_mut(&threads) {
+                Some(existing) => {
+                    existing.dumps += 1;
+                    if blocked_threads.len() > existing.blocked_threads.len() {
+                        existing.blocked_threads = blocked_threads;
+                    }
+                }
+                None => {
+                    merged.insert(
+                        threads.clone(),
+                        Deadlock {
+                            threads,
+                            blocked_threads,
+                            dumps: 1,
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    let mut deadlocks: Vec<Deadlock> = merged.into_values().collect();
+    deadlocks.sort_by(|a, b| {
+        b.threads
+            .len()
+            .cmp(&a.threads.len())
+            .then(b.dumps.cmp(&a.dumps))
+            .then(a.threads.cmp(&b.threads))
+    });
+    deadlocks
+}
+
+fn sort_root_blockers(summary: &mut [RootBlocker]) {
+    for blocker in summary.iter_mut() {
+        blocker.blocked_threads.sort();
+    }
+    summary.sort_by(|a, b| {
+        b.blocked_threads
+            .len()
+            .cmp(&a.blocked_threads.len())
+            .then(b.dumps.cmp(&a.dumps))
+            .then(a.thread.cmp(&b.thread))
+            .then(a.frame.cmp(&b.frame))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_jstack_output;
+    use std::fs;
+
+    #[test]
+    fn test_blocker_chain_ends_at_parked_lock_holder() {
+        let content = fs::read_to_string(
+            "src/test_data/java-app-openjdk17_20250920203546_block/java-app-openjdk17_20250921033632956.jstack",
+        )
+        .expect("Unable to read OpenJDK 17 block dump");
+        let dump = parse_jstack_output(&content).unwrap();
+
+        let chains = find_blocker_chains(&dump);
+        assert!(!chains.is_empty());
+        assert!(chains.iter().all(|chain| chain.root.name == "BlockerThread" && !chain.deadlocked));
+
+        let summary = summarize_root_blockers(&chains);
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary[0].thread.id, "0x0000eb211c0039a0");
+        let blocked: Vec<String> = summary[0].blocked_threads.iter().map(ThreadRef::to_string).collect();
+        assert_eq!(
+            blocked,
+            vec![
+                "BlockedThread (0x0000eb211c004ad0)",
+                "BlockedThread (0x0000eb211c006d00)",
+                "BlockerThread (0x0000eb211c005bc0)"
+            ]
+        );
+        assert!(summary[0]
+            .frame
+            .as_deref()
+            .unwrap()
+            .contains("ThreadAnalyzerApplication.lambda$main$1(ThreadAnalyzerApplication.java:83)"));
+        // The root holds the monitor while it waits for a latch nobody in the dump owns.
+        assert_eq!(summary[0].waiting_on.as_ref().unwrap().class_name, "java.util.concurrent.CountDownLatch$Sync");
+    }
+
+    #[test]
+    fn test_blocker_chain_with_parked_root_is_unresolved() {
+        let lock = |action: LockAction, address: &str, class_name: &str| LockInfo {
+            action,
+            address: address.to_string(),
+            class_name: class_name.to_string(),
+        };
+        let thread = |name: &str, locks: Vec<LockInfo>| NormalizedThread {
+            name: name.to_string(),
+            category: ThreadCategory::Application,
+            locks,
+            ..Default::default()
+        };
+        // A waits for a monitor held by B, and B is parked on a Condition that
+        // some other thread has to signal.
+        let dump = ThreadDump {
+            jvm_version: String::new(),
+            timestamp: Default::default(),
+            threads: vec![
+                thread("A", vec![lock(LockAction::WaitingToLock, "0x1", "java.lang.Object")]),
+                thread(
+                    "B",
+                    vec![
+                        lock(
+                            LockAction::ParkingToWaitFor,
+                            "0x2",
+                            "java.util.concurrent.locks.AbstractQueuedSynchronizer$ConditionObject",
+                        ),
+                        lock(LockAction::Locked, "0x1", "java.lang.Object"),
+                    ],
+                ),
+                thread("C", Vec::new()),
+            ],
+        };
+
+        let chains = find_blocker_chains(&dump);
+        assert_eq!(chains.len(), 1);
+        assert_eq!(chains[0].root.name, "B");
+        assert!(!chains[0].deadlocked);
+        assert_eq!(chains[0].root_waiting_on.as_ref().unwrap().address, "0x2");
+
+        let summary = summarize_root_blockers(&chains);
+        assert_eq!(
+            summary[0].unresolved_note().unwrap(),
+            "itself waiting on <0x2> (a java.util.concurrent.locks.AbstractQueuedSynchronizer$ConditionObject)"
+        );
+    }
+
+    #[test]
+    fn test_blocker_chain_detects_deadlock() {
+        let content = fs::read_to_string(
+            "src/test_data/java-app-openjdk11_20250921085315_deadlock/java-app-openjdk11_20250921155503576.jstack",
+        )
+        .expect("Unable to read OpenJDK 11 deadlock dump");
+        let dump = parse_jstack_output(&content).unwrap();
+
+        let chains = find_blocker_chains(&dump);
+        assert!(chains.iter().any(|chain| chain.deadlocked));
+        assert!(chains.iter().all(|chain| chain.root.name.starts_with("DeadlockThread-")));
+        assert!(chains.iter().all(|chain| chain.root_waiting_on.is_none()));
+        let summary = summarize_root_blockers(&chains);
+        assert!(summary.iter().all(|blocker| blocker.deadlocked));
+
+        // One cycle of two threads, with the six threads started after it
+        // (under the same two names) queued behind it.
+        let deadlocks = find_deadlocks(std::slice::from_ref(&dump));
+        assert_eq!(deadlocks.len(), 1);
+        assert_eq!(
+            join_threads(&deadlocks[0].threads),
+            "DeadlockThread-1 (0x0000e7a05c004000), DeadlockThread-2 (0x0000e7a05c006000)"
+        );
+        assert_eq!(deadlocks[0].blocked_threads.len(), 6);
+    }
+
+    #[test]
+    fn test_find_deadlocks_keeps_same_named_cycles_apart() {
+        let lock = |action: LockAction, address: &str| LockInfo {
+            action,
+            address: address.to_string(),
+            class_name: "java.lang.Object".to_string(),
+        };
+        let worker = |name: &str, id: &str, holds: &str, wants: &str| NormalizedThread {
+            name: name.to_string(),
+            id: Some(id.to_string()),
+            category: ThreadCategory::Application,
+            locks: vec![lock(LockAction::WaitingToLock, wants), lock(LockAction::Locked, holds)],
+            ..Default::default()
+        };
+        let dump = |threads: Vec<NormalizedThread>| ThreadDump {
+            jvm_version: String::new(),
+            timestamp: Default::default(),
+            threads,
+        };
+        // Two independent cycles between threads named Worker-a and Worker-b;
+        // only the first is still there in the second dump.
+        let first = dump(vec![
+            worker("Worker-a", "0x1", "0xa", "0xb"),
+            worker("Worker-b", "0x2", "0xb", "0xa"),
+            worker("Worker-a", "0x3", "0xc", "0xd"),
+            worker("Worker-b", "0x4", "0xd", "0xc"),
+        ]);
+        let second = dump(vec![worker("Worker-a", "0x1", "0xa", "0xb"), worker("Worker-b", "0x2", "0xb", "0xa")]);
+
+        let deadlocks = find_deadlocks(&[first, second]);
+        assert_eq!(deadlocks.len(), 2);
+        assert_eq!(join_threads(&deadlocks[0].threads), "Worker-a (0x1), Worker-b (0x2)");
+        assert_eq!(deadlocks[0].dumps, 2);
+        assert_eq!(deadlocks[1].dumps, 1);
+    }
+}
@@ -0,0 +1,257 @@
+use crate::analyzer::blockers::ThreadRef;
+use crate::analyzer::{in_time_order, thread_ids};
+use crate::models::{NormalizedThread, ThreadCategory, ThreadDump};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Thresholds and filters for [`find_chronically_blocked_threads`]. Every
+/// threshold must be met for a thread to be reported.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ChronicBlockingOptions {
+    /// Minimum number of dumps the thread was blocked in.
+    pub min_blocked_dumps: usize,
+    /// Minimum share (0.0-1.0) of the series' dumps the thread was blocked in.
+    pub min_ratio: f64,
+    /// Minimum number of consecutive dumps the thread stayed blocked.
+    pub min_consecutive: usize,
+    /// Minimum capture time, in seconds, covered by the longest blocked streak.
+    pub min_duration_secs: i64,
+    pub categories: Vec<ThreadCategory>,
+    /// Thread states counted as blocked.
+    pub states: Vec<String>,
+    /// When non-empty, only threads whose name matches one of these regexes.
+    pub include_names: Vec<String>,
+    /// Threads whose name matches any of these regexes are skipped.
+    pub exclude_names: Vec<String>,
+}
+
+impl Default for ChronicBlockingOptions {
+    fn default() -> Self {
+        ChronicBlockingOptions {
+            min_blocked_dumps: 2,
+            min_ratio: 0.0,
+            min_consecutive: 1,
+            min_duration_secs: 0,
+            categories: vec![ThreadCategory::Application],
+            states: vec!["BLOCKED".to_string()],
+            include_names: Vec::new(),
+            exclude_names: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChronicallyBlockedThread {
+    pub name: String,
+    /// The `tid` of the thread, or `#n` for the n-th thread of that name when
+    /// the dumps have no ids.
+    #[serde(default)]
+    pub id: String,
+    /// The thread as it was in the latest dump it was blocked in.
+    pub thread: NormalizedThread,
+    pub blocked_dumps: usize,
+    pub total_dumps: usize,
+    pub longest_streak: usize,
+    /// Capture time between the first and last dump of the longest streak.
+    pub longest_streak_secs: i64,
+    /// Longest streak as a share of the series: 1.0 when blocked in every dump,
+    /// lower when the blocking was intermittent or short-lived.
+    pub score: f64,
+}
+
+impl ChronicallyBlockedThread {
+    pub fn thread_ref(&self) -> ThreadRef {
+        ThreadRef {
+            name: self.name.clone(),
+            id: self.id.clone(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct BlockedHistory {
+    blocked: Vec<bool>,
+    latest: Option<NormalizedThread>,
+}
+
+fn compile(patterns: &[String]) -> Result<Vec<Regex>, regex::Error> {
+    patterns.iter().map(|pattern| Regex::new(pattern)).collect()
+}
+
+/// Longest run of `true`, as (length, index of its first element).
+fn longest_streak(blocked: &[bool]) -> (usize, usize) {
+    let (mut best, mut best_start, mut run) = (0, 0, 0);
+    for (index, &is_blocked) in blocked.iter().enumerate() {
+        run = if is_blocked { run + 1 } else { 0 };
+        if run > best {
+            best = run;
+            best_start = index + 1 - run;
+        }
+    }
+    (best, best_start)
+}
+
+/// Finds threads that stay blocked across a series of dumps, highest score
+/// first. Threads are matched across dumps by name and id (see
+/// [`thread_ids`]), so threads sharing a name are kept apart. Fails only when
+/// one of the name patterns is not a valid regex.
+pub fn find_chronically_blocked_threads(
+    dumps: &[ThreadDump],
+    options: &ChronicBlockingOptions,
+) -> Result<Vec<ChronicallyBlockedThread>, regex::Error> {
+    let include = compile(&options.include_names)?;
+    let exclude = compile(&options.exclude_names)?;
+    let ordered = in_time_order(dumps);
+
+    let mut histories: BTreeMap<ThreadRef, BlockedHistory> = BTreeMap::new();
+    for (index, dump) in ordered.iter().enumerate() {
+        for (thread, id) in dump.threads.iter().zip(thread_ids(dump)) {
+            let counted = options.states.contains(&thread.state)
+                && options.categories.contains(&thread.category)
+                && (include.is_empty() || include.iter().any(|pattern| pattern.is_match(&thread.name)))
+                && !exclude.iter().any(|pattern| pattern.is_match(&thread.name));
+            if !counted {
+                continue;
+            }
+            let key = ThreadRef {
+                name: thread.name.clone(),
+                id,
+            };
+            let history = histories.entry(key).or_default();
+            history.blocked.resize(ordered.len(), false);
+            history.blocked[index] = true;
+            history.latest = Some(thread.clone());
+        }
+    }
+
+    let total_dumps = ordered.len();
+    let mut chronically_blocked: Vec<ChronicallyBlockedThread> = histories
+        .into_iter()
+        .filter_map(|(key, history)| {
+            let blocked_dumps = history.blocked.iter().filter(|&&blocked| blocked).count();
+            let (streak, start) = longest_streak(&history.blocked);
+            let streak_secs = (ordered[start + streak - 1].timestamp - ordered[start].timestamp).num_seconds();
+            let ratio = blocked_dumps as f64 / total_dumps as f64;
+            let qualifies = blocked_dumps >= options.min_blocked_dumps
+                && ratio >= options.min_ratio
+                && streak >= options.min_consecutive
+                && streak_secs >= options.min_duration_secs;
+            if !qualifies {
+                return None;
+            }
+            Some(ChronicallyBlockedThread {
+                name: key.name,
+                id: key.id,
+                thread: history.latest?,
+                blocked_dumps,
+                total_dumps,
+                longest_streak: streak,
+                longest_streak_secs: streak_secs,
+                score: streak as f64 / total_dumps as f64,
+            })
+        })
+        .collect();
+
+    chronically_blocked.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(b.blocked_dumps.cmp(&a.blocked_dumps))
+            .then(a.name.cmp(&b.name))
+            .then(a.id.cmp(&b.id))
+    });
+    Ok(chronically_blocked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn series(pattern: &[&[(&str, &str)]]) -> Vec<ThreadDump> {
+        let start = Utc.with_ymd_and_hms(2025, 9, 21, 3, 0, 0).unwrap();
+        pattern
+            .iter()
+            .enumerate()
+            .map(|(index, threads)| ThreadDump {
+                jvm_version: "1.8.0".to_string(),
+                timestamp: start + Duration::seconds(10 * index as i64),
+                threads: threads
+                    .iter()
+                    .map(|(name, state)| NormalizedThread {
+                        name: name.to_string(),
+                        state: state.to_string(),
+                        category: ThreadCategory::Application,
+                        ..Default::default()
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_find_chronically_blocked_threads() {
+        let dumps = series(&[&[("Thread-1", "BLOCKED")], &[("Thread-1", "BLOCKED")]]);
+
+        let chronically_blocked = find_chronically_blocked_threads(&dumps, &Default::default()).unwrap();
+        assert_eq!(chronically_blocked.len(), 1);
+        assert_eq!(chronically_blocked[0].name, "Thread-1");
+        assert_eq!(chronically_blocked[0].blocked_dumps, 2);
+        assert_eq!(chronically_blocked[0].longest_streak_secs, 10);
+        assert_eq!(chronically_blocked[0].score, 1.0);
+    }
+
+    #[test]
+    fn test_same_named_threads_are_tracked_apart() {
+        // Two threads named `worker` without ids, taking turns to block: merged
+        // by name they would look blocked in every dump.
+        let dumps = series(&[
+            &[("worker", "BLOCKED"), ("worker", "RUNNABLE")],
+            &[("worker", "RUNNABLE"), ("worker", "BLOCKED")],
+            &[("worker", "RUNNABLE"), ("worker", "BLOCKED")],
+        ]);
+        let chronically_blocked = find_chronically_blocked_threads(&dumps, &Default::default()).unwrap();
+        assert_eq!(chronically_blocked.len(), 1);
+        assert_eq!(chronically_blocked[0].thread_ref().to_string(), "worker (#2)");
+        assert_eq!(chronically_blocked[0].blocked_dumps, 2);
+    }
+
+    #[test]
+    fn test_chronic_blocking_thresholds_and_scoring() {
+        let dumps = series(&[
+            &[("steady", "BLOCKED"), ("flapping", "BLOCKED"), ("gc-helper", "BLOCKED")],
+            &[("steady", "BLOCKED"), ("flapping", "RUNNABLE"), ("gc-helper", "BLOCKED")],
+            &[("steady", "BLOCKED"), ("flapping", "BLOCKED"), ("gc-helper", "BLOCKED")],
+            &[("steady", "RUNNABLE"), ("flapping", "RUNNABLE"), ("gc-helper", "BLOCKED")],
+        ]);
+
+        let names = |options: &ChronicBlockingOptions| -> Vec<String> {
+            find_chronically_blocked_threads(&dumps, options)
+                .unwrap()
+                .into_iter()
+                .map(|thread| thread.name)
+                .collect()
+        };
+
+        assert_eq!(names(&Default::default()), vec!["gc-helper", "steady", "flapping"]);
+        let consecutive = ChronicBlockingOptions {
+            min_consecutive: 2,
+            exclude_names: vec!["^gc-".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(names(&consecutive), vec!["steady"]);
+        let long = ChronicBlockingOptions {
+            min_ratio: 0.9,
+            min_duration_secs: 30,
+            ..Default::default()
+        };
+        assert_eq!(names(&long), vec!["gc-helper"]);
+
+        let invalid = ChronicBlockingOptions {
+            include_names: vec!["(".to_string()],
+            ..Default::default()
+        };
+        assert!(find_chronically_blocked_threads(&dumps, &invalid).is_err());
+    }
+}
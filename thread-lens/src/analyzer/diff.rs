@@ -0,0 +1,196 @@
+use crate::analyzer::blockers::{lock_contention, LockContention, ThreadRef};
+use crate::analyzer::groups::{normalize_frame, stack_signature, SignatureOptions};
+use crate::analyzer::pools::pool_stats;
+use crate::models::{NormalizedThread, ThreadDump};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct StateChange {
+    pub thread: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// A thread whose stack changed, with the first frame (from the top) that differs.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct StackChange {
+    pub thread: String,
+    pub frame_before: Option<String>,
+    pub frame_after: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PoolSizeChange {
+    pub pool: String,
+    pub before: usize,
+    pub after: usize,
+}
+
+/// What changed between two dumps, `before` and `after`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DumpDiff {
+    pub before_timestamp: chrono::DateTime<chrono::Utc>,
+    pub after_timestamp: chrono::DateTime<chrono::Utc>,
+    pub appeared: Vec<String>,
+    pub disappeared: Vec<String>,
+    pub state_changes: Vec<StateChange>,
+    pub stack_changes: Vec<StackChange>,
+    pub pool_changes: Vec<PoolSizeChange>,
+    /// Contended locks in `after` with no matching contention in `before`.
+    pub new_contention: Vec<LockContention>,
+}
+
+impl DumpDiff {
+    pub fn is_empty(&self) -> bool {
+        self.appeared.is_empty()
+            && self.disappeared.is_empty()
+            && self.state_changes.is_empty()
+            && self.stack_changes.is_empty()
+            && self.pool_changes.is_empty()
+            && self.new_contention.is_empty()
+    }
+}
+
+/// Keys threads by name plus occurrence, so the second "worker" in one dump is
+/// matched with the second "worker" in the other. Names are used rather than
+/// thread ids because ids do not survive a JVM restart.
+fn keyed_threads(dump: &ThreadDump) -> BTreeMap<(String, usize), &NormalizedThread> {
+    let mut occurrences: HashMap<&str, usize> = HashMap::new();
+    dump.threads
+        .iter()
+        .map(|thread| {
+            let occurrence = occurrences.entry(thread.name.as_str()).or_insert(0);
+            *occurrence += 1;
+            ((thread.name.clone(), *occurrence), thread)
+        })
+        .collect()
+}
+
+fn display_name((name, occurrence): &(String, usize)) -> String {
+    if *occurrence > 1 {
+        format!("{} (#{})", name, occurrence)
+    } else {
+        name.clone()
+    }
+}
+
+/// Compares two dumps. Stacks are compared with line numbers ignored, so a
+/// redeploy that only shifts line numbers does not show up as a stack change.
+/// Contention is matched on lock class and holder thread (name and id, see
+/// [`thread_ids`](crate::analyzer::thread_ids)), as lock addresses move
+/// between dumps.
+pub fn diff_dumps(before: &ThreadDump, after: &ThreadDump) -> DumpDiff {
+    let before_threads = keyed_threads(before);
+    let after_threads = keyed_threads(after);
+    let signature = SignatureOptions {
+        ignore_line_numbers: true,
+        ..Default::default()
+    };
+
+    let mut diff = DumpDiff {
+        before_timestamp: before.timestamp,
+        after_timestamp: after.timestamp,
+        appeared: Vec::new(),
+        disappeared: Vec::new(),
+        state_changes: Vec::new(),
+        stack_changes: Vec::new(),
+        pool_changes: Vec::new(),
+        new_contention: Vec::new(),
+    };
+
+    for (key, old) in &before_threads {
+        let Some(new) = after_threads.get(key) else {
+            diff.disappeared.push(display_name(key));
+            continue;
+        };
+        if old.state != new.state {
+            diff.state_changes.push(StateChange {
+                thread: display_name(key),
+                from: old.state.clone(),
+                to: new.state.clone(),
+            });
+        }
+        if stack_signature(&old.frames, &signature) != stack_signature(&new.frames, &signature) {
+            let depth = old
+                .frames
+                .iter()
+                .zip(&new.frames)
+                .take_while(|(a, b)| normalize_frame(&a.line, &signature) == normalize_frame(&b.line, &signature))
+                .count();
+            diff.stack_changes.push(StackChange {
+                thread: display_name(key),
+                frame_before: old.frames.get(depth).map(|frame| frame.line.clone()),
+                frame_after: new.frames.get(depth).map(|frame| frame.line.clone()),
+            });
+        }
+    }
+    diff.appeared = after_threads
+        .keys()
+        .filter(|key| !before_threads.contains_key(*key))
+        .map(display_name)
+        .collect();
+
+    let before_pools: BTreeMap<String, usize> = pool_stats(before).into_iter().map(|p| (p.name, p.size)).collect();
+    let after_pools: BTreeMap<String, usize> = pool_stats(after).into_iter().map(|p| (p.name, p.size)).collect();
+    let pool_names: BTreeSet<&String> = before_pools.keys().chain(after_pools.keys()).collect();
+    for pool in pool_names {
+        let (old, new) = (
+            before_pools.get(pool).copied().unwrap_or(0),
+            after_pools.get(pool).copied().unwrap_or(0),
+        );
+        if old != new {
+            diff.pool_changes.push(PoolSizeChange {
+                pool: pool.clone(),
+                before: old,
+                after: new,
+            });
+        }
+    }
+
+    let known: BTreeSet<(String, ThreadRef)> = lock_contention(before)
+        .into_iter()
+        .map(|lock| (lock.class_name, lock.holder))
+        .collect();
+    diff.new_contention = lock_contention(after)
+        .into_iter()
+        .filter(|lock| !known.contains(&(lock.class_name.clone(), lock.holder.clone())))
+        .collect();
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_jstack_output;
+    use std::fs;
+
+    fn read_dump(path: &str) -> ThreadDump {
+        let content = fs::read_to_string(path).expect("Unable to read dump");
+        parse_jstack_output(&content).unwrap()
+    }
+
+    #[test]
+    fn test_diff_dumps_before_and_after_deadlock() {
+        let before = read_dump(
+            "src/test_data/java-app-openjdk11_20250921085315_deadlock/java-app-openjdk11_20250921155416927.jstack",
+        );
+        let after = read_dump(
+            "src/test_data/java-app-openjdk11_20250921085315_deadlock/java-app-openjdk11_20250921155503576.jstack",
+        );
+
+        let diff = diff_dumps(&before, &after);
+        assert!(diff.appeared.contains(&"DeadlockThread-1".to_string()));
+        assert!(diff.appeared.contains(&"DeadlockThread-1 (#2)".to_string()));
+        assert!(diff.disappeared.is_empty());
+        assert!(diff.new_contention.iter().any(|lock| lock.holder.name.starts_with("DeadlockThread-")));
+        // Same-named threads are told apart by tid
+        assert!(diff
+            .new_contention
+            .iter()
+            .all(|lock| lock.holder.id.starts_with("0x") && lock.waiters.iter().all(|waiter| waiter.id.starts_with("0x"))));
+
+        assert!(diff_dumps(&after, &after).is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+use crate::analyzer::pools::pool_name;
+use crate::models::{LockAction, LockInfo, NormalizedThread, ThreadCategory, ThreadDump};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Selects threads of a dump. Every condition that is set must hold; within a
+/// condition that takes several values, one match is enough, except for
+/// `frames`, where every pattern must match a frame of the stack.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct ThreadFilter {
+    /// Thread states, e.g. `BLOCKED`; compared case-insensitively.
+    pub states: Vec<String>,
+    pub categories: Vec<ThreadCategory>,
+    /// Regexes matched against the thread name.
+    pub names: Vec<String>,
+    /// Pool names as reported by the pool analysis, e.g. `http-nio-8080-exec`.
+    pub pools: Vec<String>,
+    /// Regexes that must each match some frame, e.g. `HikariPool`.
+    pub frames: Vec<String>,
+    /// Regexes matched against the address and class of the locks the thread holds.
+    pub holding: Vec<String>,
+    /// Regexes matched against the address and class of the lock the thread waits for.
+    pub waiting_on: Vec<String>,
+    /// Frames kept per thread, innermost first; unlimited when unset.
+    pub max_depth: Option<usize>,
+}
+
+struct Matcher<'a> {
+    filter: &'a ThreadFilter,
+    names: Vec<Regex>,
+    frames: Vec<Regex>,
+    holding: Vec<Regex>,
+    waiting_on: Vec<Regex>,
+}
+
+fn compile(patterns: &[String]) -> Result<Vec<Regex>, String> {
+    patterns
+        .iter()
+        .map(|pattern| Regex::new(pattern).map_err(|e| format!("invalid regex '{}': {}", pattern, e)))
+        .collect()
+}
+
+fn any_lock(locks: &[LockInfo], patterns: &[Regex], held: bool) -> bool {
+    locks
+        .iter()
+        .filter(|lock| (lock.action == LockAction::Locked) == held)
+        .any(|lock| patterns.iter().any(|pattern| pattern.is_match(&lock.address) || pattern.is_match(&lock.class_name)))
+}
+
+impl Matcher<'_> {
+    fn matches(&self, thread: &NormalizedThread) -> bool {
+        let filter = self.filter;
+        (filter.states.is_empty() || filter.states.iter().any(|state| state.eq_ignore_ascii_case(&thread.state)))
+            && (filter.categories.is_empty() || filter.categories.contains(&thread.category))
+            && (self.names.is_empty() || self.names.iter().any(|pattern| pattern.is_match(&thread.name)))
+            && (filter.pools.is_empty() || pool_name(&thread.name).is_some_and(|pool| filter.pools.contains(&pool)))
+            && self.frames.iter().all(|pattern| thread.frames.iter().any(|frame| pattern.is_match(&frame.line)))
+            && (self.holding.is_empty() || any_lock(&thread.locks, &self.holding, true))
+            && (self.waiting_on.is_empty() || any_lock(&thread.locks, &self.waiting_on, false))
+    }
+}
+
+impl ThreadFilter {
+    /// Whether the filter keeps every thread unchanged.
+    pub fn is_empty(&self) -> bool {
+        *self == ThreadFilter::default()
+    }
+
+    fn matcher(&self) -> Result<Matcher<'_>, String> {
+        Ok(Matcher {
+            filter: self,
+            names: compile(&self.names)?,
+            frames: compile(&self.frames)?,
+            holding: compile(&self.holding)?,
+            waiting_on: compile(&self.waiting_on)?,
+        })
+    }
+
+    /// The matching threads of a dump, cut to `max_depth` frames.
+    pub fn apply(&self, dump: &ThreadDump) -> Result<ThreadDump, String> {
+        let matcher = self.matcher()?;
+        let threads = dump
+            .threads
+            .iter()
+            .filter(|thread| matcher.matches(thread))
+            .map(|thread| {
+                let mut thread = thread.clone();
+                if let Some(depth) = self.max_depth {
+                    thread.frames.truncate(depth);
+                }
+                thread
+            })
+            .collect();
+        Ok(ThreadDump {
+            jvm_version: dump.jvm_version.clone(),
+            timestamp: dump.timestamp,
+            threads,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_jstack_output;
+    use std::fs;
+
+    #[test]
+    fn test_filters_compose() {
+        let content = fs::read_to_string("src/test_data/java-app-openjdk11_20250921085315_deadlock/java-app-openjdk11_20250921155448034.jstack").unwrap();
+        let dump = parse_jstack_output(&content).unwrap();
+
+        let blocked = ThreadFilter {
+            states: vec!["blocked".to_string()],
+            ..Default::default()
+        };
+        let names = |filter: &ThreadFilter| -> Vec<String> { filter.apply(&dump).unwrap().threads.into_iter().map(|t| t.name).collect() };
+        let all_blocked = names(&blocked);
+        assert!(all_blocked.iter().any(|name| name == "DeadlockThread-1"));
+
+        let narrowed = ThreadFilter {
+            names: vec!["^DeadlockThread-1$".to_string()],
+            holding: vec!["0x00000000e2ed5c90".to_string()],
+            waiting_on: vec![r"^java\.lang\.Object$".to_string()],
+            max_depth: Some(1),
+            ..blocked.clone()
+        };
+        let filtered = narrowed.apply(&dump).unwrap();
+        assert_eq!(filtered.threads.len(), 1);
+        assert_eq!(filtered.threads[0].frames.len(), 1);
+
+        let no_match = ThreadFilter {
+            frames: vec!["HikariPool".to_string()],
+            ..blocked
+        };
+        assert!(names(&no_match).is_empty());
+        assert!(ThreadFilter::default().is_empty());
+        assert!(ThreadFilter { names: vec!["(".to_string()], ..Default::default() }.apply(&dump).is_err());
+    }
+}
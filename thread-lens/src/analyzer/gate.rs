@@ -0,0 +1,172 @@
+use crate::analyzer::blockers::join_threads;
+use crate::analyzer::report::AnalysisReport;
+use crate::antipatterns::Severity;
+use serde::{Deserialize, Serialize};
+
+/// When a series fails a CI check. Deadlocks fail it by default; the other
+/// conditions are only checked when set.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct GateOptions {
+    pub fail_on_deadlock: bool,
+    /// Fail when more threads than this are chronically blocked.
+    pub max_chronically_blocked: Option<usize>,
+    /// Fail on any anti-pattern finding at or above this severity.
+    pub fail_on_severity: Option<Severity>,
+}
+
+impl Default for GateOptions {
+    fn default() -> Self {
+        GateOptions {
+            fail_on_deadlock: true,
+            max_chronically_blocked: None,
+            fail_on_severity: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GateCheck {
+    Deadlocks,
+    ChronicBlocking,
+    Findings,
+}
+
+impl GateCheck {
+    pub fn label(&self) -> &'static str {
+        match self {
+            GateCheck::Deadlocks => "No deadlocks",
+            GateCheck::ChronicBlocking => "Chronically blocked threads within limit",
+            GateCheck::Findings => "No anti-patterns at or above the severity threshold",
+        }
+    }
+}
+
+/// One reason the check failed.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GateFailure {
+    pub check: GateCheck,
+    /// `deadlock`, `chronic-blocking` or the id of the rule that fired.
+    pub rule_id: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GateResult {
+    /// The checks that were enabled, in the order they ran.
+    pub checks: Vec<GateCheck>,
+    pub failures: Vec<GateFailure>,
+}
+
+impl GateResult {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    pub fn failures_of(&self, check: GateCheck) -> impl Iterator<Item = &GateFailure> {
+        self.failures.iter().filter(move |failure| failure.check == check)
+    }
+}
+
+/// Checks an analysis report against CI thresholds.
+pub fn check_report(report: &AnalysisReport, options: &GateOptions) -> GateResult {
+    let mut result = GateResult {
+        checks: Vec::new(),
+        failures: Vec::new(),
+    };
+    let total_dumps = report.dumps.len();
+
+    if options.fail_on_deadlock {
+        result.checks.push(GateCheck::Deadlocks);
+        for deadlock in &report.deadlocks {
+            result.failures.push(GateFailure {
+                check: GateCheck::Deadlocks,
+                rule_id: "deadlock".to_string(),
+                message: format!(
+                    "Deadlock between {} (in {} of {} dumps)",
+                    join_threads(&deadlock.threads),
+                    deadlock.dumps,
+                    total_dumps
+                ),
+            });
+        }
+    }
+
+    if let Some(max) = options.max_chronically_blocked {
+        result.checks.push(GateCheck::ChronicBlocking);
+        let blocked = &report.chronically_blocked;
+        if blocked.len() > max {
+            let names: Vec<&str> = blocked.iter().map(|thread| thread.name.as_str()).collect();
+            result.failures.push(GateFailure {
+                check: GateCheck::ChronicBlocking,
+                rule_id: "chronic-blocking".to_string(),
+                message: format!(
+                    "{} threads are chronically blocked (at most {} allowed): {}",
+                    blocked.len(),
+                    max,
+                    names.join(", ")
+                ),
+            });
+        }
+    }
+
+    if let Some(threshold) = options.fail_on_severity {
+        result.checks.push(GateCheck::Findings);
+        for finding in report.findings.iter().filter(|finding| finding.severity >= threshold) {
+            result.failures.push(GateFailure {
+                check: GateCheck::Findings,
+                rule_id: finding.rule_id.clone(),
+                message: format!(
+                    "{} ({} threads, in {} of {} dumps)",
+                    finding.title,
+                    finding.threads.len(),
+                    finding.dumps,
+                    finding.total_dumps
+                ),
+            });
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::report::analyze;
+    use crate::antipatterns::RuleEngine;
+    use crate::parser::parse_jstack_output;
+    use std::fs;
+
+    #[test]
+    fn test_check_report_thresholds() {
+        let dir = "src/test_data/java-app-openjdk11_20250921085315_deadlock";
+        let dumps: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| parse_jstack_output(&fs::read_to_string(entry.unwrap().path()).unwrap()).unwrap())
+            .collect();
+        let report = analyze(&dumps, &RuleEngine::default(), &Default::default()).unwrap();
+        assert!(!report.chronically_blocked.is_empty());
+
+        let result = check_report(&report, &Default::default());
+        assert!(!result.passed());
+        assert_eq!(result.checks, vec![GateCheck::Deadlocks]);
+        assert_eq!(result.failures[0].rule_id, "deadlock");
+
+        let lenient = GateOptions {
+            fail_on_deadlock: false,
+            max_chronically_blocked: Some(report.chronically_blocked.len()),
+            fail_on_severity: Some(Severity::Critical),
+        };
+        assert!(check_report(&report, &lenient).passed());
+
+        let strict = GateOptions {
+            max_chronically_blocked: Some(0),
+            ..lenient
+        };
+        let result = check_report(&report, &strict);
+        assert_eq!(result.failures_of(GateCheck::ChronicBlocking).count(), 1);
+        assert_eq!(result.failures.len(), 1);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::models::{CategorizedFrame, NormalizedThread, ThreadDump};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+lazy_static! {
+    // `Foo$$Lambda$63/0x0000000840094040.run` (JDK 8-17) and `Foo$$Lambda/0x...` (JDK 21)
+    static ref LAMBDA_ID_REGEX: Regex = Regex::new(r"\$\$Lambda(?:\$\d+)?/0x[0-9a-fA-F]+").unwrap();
+    static ref LINE_NUMBER_REGEX: Regex = Regex::new(r":\d+\)$").unwrap();
+    static ref NUMBERED_NAME_REGEX: Regex = Regex::new(r"^(.*?)(\d+)$").unwrap();
+}
+
+/// Controls how frames are normalized before stacks are compared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureOptions {
+    /// Treat `Foo.java:12` and `Foo.java:15` as the same frame.
+    pub ignore_line_numbers: bool,
+    /// Replace generated lambda class ids (`$$Lambda$63/0x...`) with `$$Lambda`.
+    pub normalize_lambdas: bool,
+}
+
+impl Default for SignatureOptions {
+    fn default() -> Self {
+        SignatureOptions {
+            ignore_line_numbers: false,
+            normalize_lambdas: true,
+        }
+    }
+}
+
+/// Threads sharing the same normalized stack.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StackGroup {
+    pub frames: Vec<CategorizedFrame>,
+    pub count: usize,
+    pub states: BTreeMap<String, usize>,
+    pub names: Vec<String>,
+}
+
+impl StackGroup {
+    /// Compact rendering of the thread names, e.g. `qtp2031288110-[1..312]`.
+    pub fn name_summary(&self) -> String {
+        summarize_thread_names(&self.names)
+    }
+}
+
+pub fn normalize_frame(line: &str, options: &SignatureOptions) -> String {
+    let mut normalized = line.to_string();
+    if options.normalize_lambdas {
+        normalized = LAMBDA_ID_REGEX.replace_all(&normalized, "$$$$Lambda").into_owned();
+    }
+    if options.ignore_line_numbers {
+        normalized = LINE_NUMBER_REGEX.replace(&normalized, ")").into_owned();
+    }
+    normalized
+}
+
+/// A single string identifying a stack once its frames are normalized.
+pub fn stack_signature(frames: &[CategorizedFrame], options: &SignatureOptions) -> String {
+    frames
+        .iter()
+        .map(|frame| normalize_frame(&frame.line, options))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Groups threads by stack signature, largest group first.
+pub fn group_threads<'a, I>(threads: I, options: &SignatureOptions) -> Vec<StackGroup>
+where
+    I: IntoIterator<Item = &'a NormalizedThread>,
+{
+    let mut groups: HashMap<String, StackGroup> = HashMap::new();
+    for thread in threads {
+        let group = groups
+            .entry(stack_signature(&thread.frames, options))
+            .or_insert_with(|| StackGroup {
+                frames: thread
+                    .frames
+                    .iter()
+                    .map(|frame| CategorizedFrame {
+                        line: normalize_frame(&frame.line, options),
+                        ..frame.clone()
+                    })
+                    .collect(),
+                count: 0,
+                states: BTreeMap::new(),
+                names: Vec::new(),
+            });
+        group.count += 1;
+        *group.states.entry(thread.state.clone()).or_insert(0) += 1;
+        group.names.push(thread.name.clone());
+    }
+
+    let mut groups: Vec<StackGroup> = groups.into_values().collect();
+    groups.sort_by(|a, b| {
+        b.count.cmp(&a.count).then_with(|| {
+            let a_top = a.frames.first().map(|frame| frame.line.as_str());
+            let b_top = b.frames.first().map(|frame| frame.line.as_str());
+            a_top.cmp(&b_top)
+        })
+    });
+    groups
+}
+
+pub fn group_by_stack(dump: &ThreadDump, options: &SignatureOptions) -> Vec<StackGroup> {
+    group_threads(&dump.threads, options)
+}
+
+/// Collapses numbered thread names sharing a prefix into ranges:
+/// `["qtp1-1", "qtp1-2", "qtp1-7", "main"]` becomes `main, qtp1-[1..7]`.
+pub fn summarize_thread_names(names: &[String]) -> String {
+    let mut numbered: BTreeMap<&str, Vec<u64>> = BTreeMap::new();
+    let mut plain: BTreeMap<&str, usize> = BTreeMap::new();
+    for name in names {
+        let parsed = NUMBERED_NAME_REGEX
+            .captures(name)
+            .and_then(|c| Some((c.get(1)?.as_str(), c[2].parse::<u64>().ok()?)));
+        match parsed {
+            Some((prefix, number)) => numbered.entry(prefix).or_default().push(number),
+            None => *plain.entry(name.as_str()).or_insert(0) += 1,
+        }
+    }
+
+    let mut parts: Vec<String> = plain
+        .into_iter()
+        .map(|(name, count)| if count > 1 { format!("{} (×{})", name, count) } else { name.to_string() })
+        .collect();
+    for (prefix, numbers) in numbered {
+        let min = numbers.iter().min().copied().unwrap_or_default();
+        let max = numbers.iter().max().copied().unwrap_or_default();
+        if numbers.len() == 1 {
+            parts.push(format!("{}{}", prefix, min));
+        } else {
+            parts.push(format!("{}[{}..{}]", prefix, min, max));
+        }
+    }
+    parts.sort();
+    parts.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_jstack_output;
+    use std::fs;
+
+    #[test]
+    fn test_group_by_stack_collapses_idle_jetty_workers() {
+        let content = fs::read_to_string(
+            "src/test_data/java-app-openjdk11_20250921085315_deadlock/java-app-openjdk11_20250921155416927.jstack",
+        )
+        .expect("Unable to read OpenJDK 11 dump");
+        let dump = parse_jstack_output(&content).unwrap();
+
+        let groups = group_by_stack(&dump, &SignatureOptions::default());
+        let idle = groups
+            .iter()
+            .find(|group| group.frames.iter().any(|frame| frame.line.contains("idleJobPoll")))
+            .expect("idle qtp workers should form a group");
+        assert!(idle.count > 1);
+        assert_eq!(idle.states.get("TIMED_WAITING"), Some(&idle.count));
+        assert!(idle.name_summary().starts_with("qtp1359040269-["));
+        assert_eq!(groups.iter().map(|group| group.count).sum::<usize>(), dump.threads.len());
+    }
+
+    #[test]
+    fn test_normalize_frame() {
+        let options = SignatureOptions {
+            ignore_line_numbers: true,
+            normalize_lambdas: true,
+        };
+        assert_eq!(
+            normalize_frame("at org.eclipse.jetty.io.ManagedSelector$$Lambda$63/0x0000000840094040.run(Unknown Source)", &options),
+            "at org.eclipse.jetty.io.ManagedSelector$$Lambda.run(Unknown Source)"
+        );
+        assert_eq!(
+            normalize_frame("at spark.Service.lambda$init$2(Service.java:644)", &options),
+            "at spark.Service.lambda$init$2(Service.java)"
+        );
+    }
+
+    #[test]
+    fn test_summarize_thread_names() {
+        let names: Vec<String> = ["qtp1-3", "qtp1-1", "qtp1-12", "main", "Finalizer", "main"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        assert_eq!(summarize_thread_names(&names), "Finalizer, main (×2), qtp1-[1..12]");
+    }
+}
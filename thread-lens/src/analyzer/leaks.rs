@@ -0,0 +1,302 @@
+use crate::analyzer::groups::{normalize_frame, SignatureOptions};
+use crate::analyzer::in_time_order;
+use crate::analyzer::pools::pool_name;
+use crate::models::{FrameCategory, NormalizedThread, ThreadDump};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// What a thread count was taken over.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GrowthScope {
+    Total,
+    Pool(String),
+    /// Threads sharing the same entry frames (see [`creation_signature`]).
+    CreationStack(String),
+}
+
+/// Thread counts for one scope over a time-ordered series.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ThreadGrowth {
+    pub scope: GrowthScope,
+    pub counts: Vec<usize>,
+    /// Least-squares slope of the counts, in threads per dump.
+    pub slope_per_dump: f64,
+    /// Net change per minute of capture time, when the dumps span any time.
+    pub threads_per_minute: Option<f64>,
+    /// True when the count never drops and ends higher than it started.
+    pub monotonic: bool,
+    /// Number of dump-to-dump steps in which the count rose.
+    #[serde(default)]
+    pub increases: usize,
+    /// True when the growth is monotonic, spread over at least
+    /// [`LeakOptions::min_increases`] steps and at least
+    /// [`LeakOptions::min_growth`] threads: a leak rather than a one-off
+    /// start-up or scale-up.
+    #[serde(default)]
+    pub steady: bool,
+    pub sample_stack: Option<Vec<String>>,
+}
+
+impl ThreadGrowth {
+    pub fn growth(&self) -> usize {
+        match (self.counts.first(), self.counts.last()) {
+            (Some(first), Some(last)) => last.saturating_sub(*first),
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LeakReport {
+    pub dumps: usize,
+    pub total: ThreadGrowth,
+    /// Pools and creation stacks growing steadily, largest growth first.
+    pub suspects: Vec<ThreadGrowth>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeakOptions {
+    /// Fewer dumps than this are not enough to call a trend.
+    pub min_dumps: usize,
+    /// Minimum net growth, in threads, for a scope to be reported.
+    pub min_growth: usize,
+    /// Minimum number of dump-to-dump steps the count must rise in, so a pool
+    /// that starts a batch of threads once is not mistaken for a leak.
+    pub min_increases: usize,
+}
+
+impl Default for LeakOptions {
+    fn default() -> Self {
+        LeakOptions {
+            min_dumps: 3,
+            min_growth: 3,
+            min_increases: 2,
+        }
+    }
+}
+
+/// jstack does not record where a thread was started, so the entry frames at
+/// the bottom of its stack stand in for the creation stack: the two outermost
+/// non-JVM frames, or the outermost frame for pure JDK threads.
+pub fn creation_signature(thread: &NormalizedThread) -> Option<String> {
+    let options = SignatureOptions {
+        ignore_line_numbers: true,
+        ..Default::default()
+    };
+    let mut entry: Vec<String> = thread
+        .frames
+        .iter()
+        .rev()
+        .filter(|frame| frame.category != FrameCategory::Jvm)
+        .take(2)
+        .map(|frame| normalize_frame(frame.line.trim_start_matches("at "), &options))
+        .collect();
+    if entry.is_empty() {
+        entry.extend(
+            thread
+                .frames
+                .last()
+                .map(|frame| normalize_frame(frame.line.trim_start_matches("at "), &options)),
+        );
+    }
+    if entry.is_empty() {
+        return None;
+    }
+    entry.reverse();
+    Some(entry.join(" <- "))
+}
+
+fn scopes_of(thread: &NormalizedThread) -> Vec<GrowthScope> {
+    let mut scopes = vec![GrowthScope::Total];
+    scopes.extend(pool_name(&thread.name).map(GrowthScope::Pool));
+    scopes.extend(creation_signature(thread).map(GrowthScope::CreationStack));
+    scopes
+}
+
+fn slope(counts: &[usize]) -> f64 {
+    let n = counts.len() as f64;
+    if counts.len() < 2 {
+        return 0.0;
+    }
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = counts.iter().sum::<usize>() as f64 / n;
+    let (mut numerator, mut denominator) = (0.0, 0.0);
+    for (x, &y) in counts.iter().enumerate() {
+        let dx = x as f64 - mean_x;
+        numerator += dx * (y as f64 - mean_y);
+        denominator += dx * dx;
+    }
+    numerator / denominator
+}
+
+fn is_monotonic_growth(counts: &[usize]) -> bool {
+    counts.windows(2).all(|pair| pair[0] <= pair[1]) && counts.first() < counts.last()
+}
+
+fn increases(counts: &[usize]) -> usize {
+    counts.windows(2).filter(|pair| pair[0] < pair[1]).count()
+}
+
+/// Tracks total, per-pool and per-creation-stack thread counts across the
+/// series (in capture order) and reports the scopes that grow steadily.
+pub fn detect_thread_leaks(dumps: &[ThreadDump], options: &LeakOptions) -> LeakReport {
+    let ordered = in_time_order(dumps);
+    let minutes = match (ordered.first(), ordered.last()) {
+        (Some(first), Some(last)) => (last.timestamp - first.timestamp).num_milliseconds() as f64 / 60_000.0,
+        _ => 0.0,
+    };
+
+    let mut counts: BTreeMap<GrowthScope, Vec<usize>> = BTreeMap::new();
+    let mut samples: BTreeMap<GrowthScope, Vec<String>> = BTreeMap::new();
+    for (index, dump) in ordered.iter().enumerate() {
+        for thread in &dump.threads {
+            for scope in scopes_of(thread) {
+                counts.entry(scope.clone()).or_insert_with(|| vec![0; ordered.len()])[index] += 1;
+                // jstack lists threads roughly in creation order, so the sample
+                // ends up being one of the newest threads of the latest dump.
+                if scope != GrowthScope::Total && !thread.frames.is_empty() {
+                    samples.insert(scope, thread.frames.iter().map(|frame| frame.line.clone()).collect());
+                }
+            }
+        }
+    }
+
+    let enough_dumps = ordered.len() >= options.min_dumps;
+    let growth_of = |scope: GrowthScope, counts: Vec<usize>| {
+        let mut growth = ThreadGrowth {
+            slope_per_dump: slope(&counts),
+            threads_per_minute: (minutes > 0.0).then(|| {
+                (*counts.last().unwrap_or(&0) as f64 - *counts.first().unwrap_or(&0) as f64) / minutes
+            }),
+            monotonic: is_monotonic_growth(&counts),
+            increases: increases(&counts),
+            steady: false,
+            sample_stack: samples.get(&scope).cloned(),
+            scope,
+            counts,
+        };
+        growth.steady = enough_dumps
+            && growth.monotonic
+            && growth.increases >= options.min_increases
+            && growth.growth() >= options.min_growth;
+        growth
+    };
+
+    let total_counts = counts.remove(&GrowthScope::Total).unwrap_or_else(|| vec![0; ordered.len()]);
+    let total = growth_of(GrowthScope::Total, total_counts);
+
+    let mut suspects: Vec<ThreadGrowth> = counts
+        .into_iter()
+        .map(|(scope, counts)| growth_of(scope, counts))
+        .filter(|growth| growth.steady)
+        .collect();
+    suspects.sort_by(|a, b| b.growth().cmp(&a.growth()).then(a.scope.cmp(&b.scope)));
+
+    LeakReport {
+        dumps: ordered.len(),
+        total,
+        suspects,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CategorizedFrame;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn worker(name: &str, entry: &str) -> NormalizedThread {
+        NormalizedThread {
+            name: name.to_string(),
+            state: "WAITING".to_string(),
+            frames: vec![
+                CategorizedFrame {
+                    line: format!("at {}(Worker.java:10)", entry),
+                    category: FrameCategory::Application,
+                    library: None,
+                    role: None,
+                },
+                CategorizedFrame {
+                    line: "at java.lang.Thread.run(java.base@17.0.2/Thread.java:833)".to_string(),
+                    category: FrameCategory::Jvm,
+                    library: None,
+                    role: None,
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_detect_thread_leaks_flags_growing_name_pattern() {
+        let start = Utc.with_ymd_and_hms(2025, 9, 21, 3, 0, 0).unwrap();
+        // Listed out of order to check the series is sorted by timestamp
+        let dumps: Vec<ThreadDump> = [2usize, 0, 1, 3]
+            .iter()
+            .map(|&step| {
+                let mut threads = vec![worker("main", "com.example.Main.main")];
+                for n in 0..=step * 2 {
+                    threads.push(worker(&format!("Thread-{}", n), "com.example.Leaky.poll"));
+                }
+                ThreadDump {
+                    jvm_version: "17".to_string(),
+                    timestamp: start + Duration::minutes(step as i64),
+                    threads,
+                }
+            })
+            .collect();
+
+        let report = detect_thread_leaks(&dumps, &LeakOptions::default());
+        assert_eq!(report.total.counts, vec![2, 4, 6, 8]);
+        assert!(report.total.monotonic && report.total.steady);
+        assert_eq!(report.total.slope_per_dump, 2.0);
+        assert_eq!(report.total.threads_per_minute, Some(2.0));
+
+        let pool = report
+            .suspects
+            .iter()
+            .find(|growth| growth.scope == GrowthScope::Pool("Thread".to_string()))
+            .expect("Thread-N should be flagged");
+        assert_eq!(pool.counts, vec![1, 3, 5, 7]);
+        assert!(pool.sample_stack.as_ref().unwrap()[0].contains("Leaky.poll"));
+
+        assert!(report.suspects.iter().any(|growth| matches!(
+            &growth.scope,
+            GrowthScope::CreationStack(signature) if signature.contains("Leaky.poll")
+        )));
+        assert!(!report.suspects.iter().any(|growth| matches!(
+            &growth.scope,
+            GrowthScope::CreationStack(signature) if signature.contains("Main.main")
+        )));
+    }
+
+    #[test]
+    fn test_detect_thread_leaks_ignores_single_step_change() {
+        let start = Utc.with_ymd_and_hms(2025, 9, 21, 3, 0, 0).unwrap();
+        // A batch of 8 workers started once, and a scheduler going from 1 to 2
+        // threads: both grow in a single step, which is not a leak.
+        let dumps: Vec<ThreadDump> = (0..6)
+            .map(|step| {
+                let mut threads = vec![worker("scheduler-1", "com.example.Scheduler.tick")];
+                if step >= 3 {
+                    threads.push(worker("scheduler-2", "com.example.Scheduler.tick"));
+                    for n in 0..8 {
+                        threads.push(worker(&format!("Batch-{}", n), "com.example.Batch.run"));
+                    }
+                }
+                ThreadDump {
+                    jvm_version: "17".to_string(),
+                    timestamp: start + Duration::minutes(step),
+                    threads,
+                }
+            })
+            .collect();
+
+        let report = detect_thread_leaks(&dumps, &LeakOptions::default());
+        assert_eq!(report.total.counts, vec![1, 1, 1, 10, 10, 10]);
+        assert!(report.total.monotonic);
+        assert_eq!(report.total.increases, 1);
+        assert!(!report.total.steady);
+        assert!(report.suspects.is_empty());
+    }
+}
@@ -0,0 +1,340 @@
+use crate::analyzer::pools::{is_known_pool_worker, pool_name};
+use crate::catalog::library_context;
+use crate::classifier::{in_package, qualified_frame};
+use crate::models::{
+    CategorizedFrame, CategoryDecision, FrameCategory, LibraryRole, NormalizedThread, ThreadCategory, ThreadDump,
+};
+use std::collections::HashMap;
+
+pub mod baseline;
+pub mod blockers;
+pub mod chronic;
+pub mod diff;
+pub mod filter;
+pub mod gate;
+pub mod groups;
+pub mod leaks;
+pub mod packages;
+pub mod pools;
+pub mod report;
+pub mod summary;
+pub mod timeline;
+
+pub use chronic::find_chronically_blocked_threads;
+
+/// Whether a frame belongs to the application package, e.g. `com.mycorp`.
+/// Without a package nothing is known to be application code.
+pub fn is_application_frame(frame: &str, app_package_prefix: Option<&str>) -> bool {
+    app_package_prefix.is_some_and(|package| in_package(frame, package))
+}
+
+/// Threads the JVM starts itself (HotSpot and OpenJ9), matched by name prefix.
+const JVM_THREAD_NAMES: &[&str] = &[
+    "Reference Handler",
+    "Finalizer",
+    "Signal Dispatcher",
+    "Service Thread",
+    "Monitor Deflation Thread",
+    "C1 CompilerThread",
+    "C2 CompilerThread",
+    "Sweeper thread",
+    "Common-Cleaner",
+    "Attach Listener",
+    "Notification Thread",
+    "VM Thread",
+    "VM Periodic Task Thread",
+    "GC Thread",
+    "G1 ",
+    "DestroyJavaVM",
+    "process reaper",
+    "JIT Compilation Thread",
+    "JIT Diagnostic Compilation Thread",
+    "JIT-SamplerThread",
+    "Signal Reporter",
+    "Concurrent Mark Helper",
+    "GC Worker",
+    "GC Slave",
+];
+
+/// Frames of a framework's I/O loop. Threads running one stay framework
+/// threads even while the loop calls back into application code.
+const EVENT_LOOP_FRAMES: &[&str] = &[
+    "org.eclipse.jetty.io.ManagedSelector",
+    "AbstractConnector$Acceptor.run",
+    "io.netty.channel.nio.NioEventLoop.run",
+    "io.netty.channel.epoll.EpollEventLoop.run",
+    "NioEndpoint$Poller.run",
+];
+
+/// Weight of each kind of evidence in [`determine_thread_category`].
+const JVM_NAME_WEIGHT: f64 = 10.0;
+const APP_FRAME_WEIGHT: f64 = 6.0;
+const APP_FRAME_ON_TOP_WEIGHT: f64 = 1.0;
+const MAIN_THREAD_WEIGHT: f64 = 4.0;
+const EVENT_LOOP_WEIGHT: f64 = 4.0;
+const ENTRY_FRAME_WEIGHT: f64 = 3.0;
+const POOL_WEIGHT: f64 = 2.0;
+const EXECUTOR_POOL_WEIGHT: f64 = 1.0;
+/// A call out to a database or remote service is as good a sign of application
+/// work as an application frame, which proxies and lambdas often hide.
+const LIBRARY_CALL_WEIGHT: f64 = 6.0;
+const LIBRARY_OWNER_WEIGHT: f64 = 2.0;
+const NO_FRAMES_WEIGHT: f64 = 1.0;
+
+/// `at org.eclipse.jetty.io.ManagedSelector.select(ManagedSelector.java:156)` -> `org.eclipse.jetty.io.ManagedSelector.select`
+fn frame_method(frame: &CategorizedFrame) -> &str {
+    let frame = qualified_frame(&frame.line);
+    frame.split('(').next().unwrap_or(frame)
+}
+
+/// What the role of the library a thread is currently in says about the
+/// thread: calls out to a database, a remote service, a logger or a
+/// serializer are made on behalf of application work, while a thread inside a
+/// web server or messaging client without application code above it belongs
+/// to that library. The other roles say nothing either way.
+fn role_evidence(role: &LibraryRole) -> Option<(ThreadCategory, f64)> {
+    match role {
+        LibraryRole::Database | LibraryRole::Rpc | LibraryRole::Logging | LibraryRole::Serialization => {
+            Some((ThreadCategory::Application, LIBRARY_CALL_WEIGHT))
+        }
+        LibraryRole::WebServer | LibraryRole::Messaging => Some((ThreadCategory::Framework, LIBRARY_OWNER_WEIGHT)),
+        LibraryRole::WebFramework
+        | LibraryRole::AppFramework
+        | LibraryRole::Reflection
+        | LibraryRole::Testing => None,
+    }
+}
+
+fn frame_thread_category(category: &FrameCategory) -> ThreadCategory {
+    match category {
+        FrameCategory::Jvm => ThreadCategory::Jvm,
+        FrameCategory::Framework => ThreadCategory::Framework,
+        FrameCategory::Application => ThreadCategory::Application,
+    }
+}
+
+/// Decides what a thread is for by weighing its name, its entry frame (the
+/// bottom of the stack), the pool it belongs to, whether it runs a framework
+/// event loop, the role of the catalogued library it is currently in and
+/// where its first application frame is. The category with the most weight
+/// wins; ties go to Application, then Framework.
+pub fn determine_thread_category(name: &str, frames: &[CategorizedFrame]) -> CategoryDecision {
+    let mut evidence: Vec<(ThreadCategory, f64, String)> = Vec::new();
+
+    if JVM_THREAD_NAMES.iter().any(|prefix| name.starts_with(prefix)) {
+        evidence.push((ThreadCategory::Jvm, JVM_NAME_WEIGHT, "JVM thread name".to_string()));
+    }
+    if name == "main" {
+        evidence.push((ThreadCategory::Application, MAIN_THREAD_WEIGHT, "runs the main method".to_string()));
+    }
+
+    match frames.iter().rev().find(|frame| frame.category != FrameCategory::Jvm) {
+        Some(entry) => evidence.push((
+            frame_thread_category(&entry.category),
+            ENTRY_FRAME_WEIGHT,
+            format!("entered through {}", frame_method(entry)),
+        )),
+        None if frames.is_empty() => {
+            evidence.push((ThreadCategory::Jvm, NO_FRAMES_WEIGHT, "no Java frames".to_string()))
+        }
+        None => evidence.push((ThreadCategory::Jvm, ENTRY_FRAME_WEIGHT, "only JDK frames".to_string())),
+    }
+
+    if is_known_pool_worker(name) {
+        if let Some(pool) = pool_name(name) {
+            if pool.starts_with("pool-") {
+                evidence.push((ThreadCategory::Application, EXECUTOR_POOL_WEIGHT, format!("worker of executor {}", pool)));
+            } else {
+                evidence.push((ThreadCategory::Framework, POOL_WEIGHT, format!("worker of pool {}", pool)));
+            }
+        }
+    }
+
+    if let Some(frame) = frames
+        .iter()
+        .find(|frame| EVENT_LOOP_FRAMES.iter().any(|marker| frame.line.contains(marker)))
+    {
+        evidence.push((ThreadCategory::Framework, EVENT_LOOP_WEIGHT, format!("runs event loop {}", frame_method(frame))));
+    }
+
+    // The innermost catalogued library above any application code, with
+    // proxies and reflection skipped: what the thread is busy with or blocked in.
+    let application_frame = frames.iter().position(|frame| frame.category == FrameCategory::Application);
+    let current_role = frames[..application_frame.unwrap_or(frames.len())]
+        .iter()
+        .filter_map(|frame| frame.role.as_ref())
+        .find(|role| **role != LibraryRole::Reflection);
+    if let Some((category, weight)) = current_role.and_then(role_evidence) {
+        if let Some(context) = library_context(frames) {
+            evidence.push((category, weight, format!("in {}", context)));
+        }
+    }
+
+    if let Some(index) = application_frame {
+        evidence.push((
+            ThreadCategory::Application,
+            APP_FRAME_WEIGHT,
+            format!("application frame {} of {}", index + 1, frames.len()),
+        ));
+        // Only JDK frames above it: the thread is running application code
+        // rather than waiting in a library the application called.
+        if frames[..index].iter().all(|frame| frame.category == FrameCategory::Jvm) {
+            evidence.push((
+                ThreadCategory::Application,
+                APP_FRAME_ON_TOP_WEIGHT,
+                "application code on top of the stack".to_string(),
+            ));
+        }
+    }
+
+    let weight = |category: &ThreadCategory| -> f64 {
+        evidence
+            .iter()
+            .filter(|(supports, _, _)| supports == category)
+            .map(|(_, weight, _)| weight)
+            .sum()
+    };
+    let total: f64 = evidence.iter().map(|(_, weight, _)| weight).sum();
+    let category = [ThreadCategory::Application, ThreadCategory::Framework, ThreadCategory::Jvm]
+        .into_iter()
+        .fold(None, |best: Option<(ThreadCategory, f64)>, category| {
+            let score = weight(&category);
+            match best {
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((category, score)),
+            }
+        })
+        .filter(|(_, score)| *score > 0.0)
+        .map(|(category, _)| category)
+        .unwrap_or_default();
+
+    let reason = evidence
+        .iter()
+        .filter(|(supports, _, _)| *supports == category)
+        .map(|(_, _, description)| description.as_str())
+        .collect::<Vec<_>>()
+        .join("; ");
+    CategoryDecision {
+        confidence: if total > 0.0 { weight(&category) / total } else { 0.0 },
+        category,
+        reason,
+    }
+}
+
+/// Sets a thread's category, confidence and reason from its name and frames.
+pub fn categorize_thread(thread: &mut NormalizedThread) {
+    let decision = determine_thread_category(&thread.name, &thread.frames);
+    thread.category = decision.category;
+    thread.category_confidence = decision.confidence;
+    thread.category_reason = decision.reason;
+}
+
+/// The id of every thread of a dump, in order: its `tid` or `Id`, or `#n` for
+/// the n-th thread of that name when the dump has no ids.
+pub fn thread_ids(dump: &ThreadDump) -> Vec<String> {
+    let mut occurrences: HashMap<&str, usize> = HashMap::new();
+    dump.threads
+        .iter()
+        .map(|thread| {
+            let occurrence = occurrences.entry(thread.name.as_str()).or_insert(0);
+            *occurrence += 1;
+            thread.id.clone().unwrap_or_else(|| format!("#{}", occurrence))
+        })
+        .collect()
+}
+
+/// The dumps of a series sorted by capture time; dumps with equal timestamps keep their order.
+pub fn in_time_order(dumps: &[ThreadDump]) -> Vec<&ThreadDump> {
+    let mut ordered: Vec<&ThreadDump> = dumps.iter().collect();
+    ordered.sort_by_key(|dump| dump.timestamp);
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classifier::Classifier;
+
+    fn decide(name: &str, frames: &[&str]) -> CategoryDecision {
+        let classifier = Classifier::default();
+        let frames: Vec<CategorizedFrame> = frames.iter().map(|line| classifier.classify_frame(line)).collect();
+        determine_thread_category(name, &frames)
+    }
+
+    #[test]
+    fn test_determine_thread_category() {
+        let main = decide("main", &[]);
+        assert_eq!(main.category, ThreadCategory::Application);
+        assert_eq!(main.reason, "runs the main method");
+
+        let compiler = decide("C2 CompilerThread0", &[]);
+        assert_eq!(compiler.category, ThreadCategory::Jvm);
+        assert_eq!(compiler.confidence, 1.0);
+
+        let callback = [
+            "at com.mycorp.shop.ConnectionListener.onOpened(ConnectionListener.java:20)",
+            "at org.eclipse.jetty.io.ManagedSelector$Accept.run(ManagedSelector.java:860)",
+            "at org.eclipse.jetty.io.ManagedSelector$SelectorProducer.produce(ManagedSelector.java:509)",
+            "at org.eclipse.jetty.util.thread.strategy.EatWhatYouKill.produce(EatWhatYouKill.java:135)",
+            "at org.eclipse.jetty.util.thread.QueuedThreadPool$Runner.run(QueuedThreadPool.java:938)",
+            "at java.lang.Thread.run(java.base@11.0.16/Thread.java:829)",
+        ];
+        let selector = decide("qtp1359040269-12", &callback);
+        assert_eq!(selector.category, ThreadCategory::Framework);
+        assert!(selector.confidence > 0.5 && selector.confidence < 1.0);
+        assert!(selector.reason.contains("runs event loop org.eclipse.jetty.io.ManagedSelector$Accept.run"));
+
+        let handler = decide(
+            "qtp1359040269-15",
+            &[
+                "at com.mycorp.shop.OrderHandler.handle(OrderHandler.java:44)",
+                "at org.eclipse.jetty.server.handler.HandlerWrapper.handle(HandlerWrapper.java:127)",
+                "at org.eclipse.jetty.server.HttpChannel.handle(HttpChannel.java:479)",
+                "at org.eclipse.jetty.util.thread.QueuedThreadPool$Runner.run(QueuedThreadPool.java:938)",
+                "at java.lang.Thread.run(java.base@11.0.16/Thread.java:829)",
+            ],
+        );
+        assert_eq!(handler.category, ThreadCategory::Application);
+
+        let cleaner = decide(
+            "Thread-3",
+            &[
+                "at java.lang.Object.wait(java.base@11.0.16/Native Method)",
+                "at java.lang.ref.ReferenceQueue.remove(java.base@11.0.16/ReferenceQueue.java:155)",
+            ],
+        );
+        assert_eq!((cleaner.category, cleaner.reason.as_str()), (ThreadCategory::Jvm, "only JDK frames"));
+    }
+
+    #[test]
+    fn test_thread_category_uses_library_role() {
+        let jdbc = decide(
+            "qtp1359040269-21",
+            &[
+                "at java.base@17.0.2/jdk.internal.misc.Unsafe.park(Native Method)",
+                "at com.zaxxer.hikari.util.ConcurrentBag.borrow(ConcurrentBag.java:162)",
+                "at com.zaxxer.hikari.pool.HikariPool.getConnection(HikariPool.java:181)",
+                "at org.springframework.web.servlet.DispatcherServlet.doDispatch(DispatcherServlet.java:1067)",
+                "at org.eclipse.jetty.server.HttpChannel.handle(HttpChannel.java:479)",
+                "at org.eclipse.jetty.util.thread.QueuedThreadPool$Runner.run(QueuedThreadPool.java:938)",
+                "at java.lang.Thread.run(java.base@17.0.2/Thread.java:833)",
+            ],
+        );
+        let jetty = decide(
+            "qtp1359040269-22",
+            &[
+                "at java.base@17.0.2/jdk.internal.misc.Unsafe.park(Native Method)",
+                "at org.eclipse.jetty.util.BlockingArrayQueue.poll(BlockingArrayQueue.java:382)",
+                "at org.eclipse.jetty.util.thread.QueuedThreadPool.idleJobPoll(QueuedThreadPool.java:974)",
+                "at org.eclipse.jetty.util.thread.QueuedThreadPool$Runner.run(QueuedThreadPool.java:1018)",
+                "at java.lang.Thread.run(java.base@17.0.2/Thread.java:833)",
+            ],
+        );
+
+        // Waiting for a connection is request work; an idle worker belongs to Jetty.
+        assert_eq!(jdbc.category, ThreadCategory::Application);
+        assert!(jdbc.reason.contains("in database (HikariCP) under Spring MVC"));
+        assert_eq!(jetty.category, ThreadCategory::Framework);
+        assert!(jetty.reason.contains("in web server (Jetty)"));
+    }
+}
@@ -0,0 +1,173 @@
+use crate::classifier::qualified_frame;
+use crate::models::{CategorizedFrame, FrameCategory, ThreadDump};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Inferred packages are cut to this many segments, so `com.mycorp.orders.api`
+/// and `com.mycorp.orders.db` are both proposed as `com.mycorp.orders`.
+const MAX_PACKAGE_DEPTH: usize = 3;
+
+/// Weight of each kind of evidence in [`PackageCandidate::score`].
+const MAIN_ENTRY_WEIGHT: usize = 10;
+const DISPATCH_WEIGHT: usize = 3;
+
+/// A package that looks like application code, with the evidence for it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PackageCandidate {
+    pub package: String,
+    /// Threads whose `main` method is in the package.
+    pub main_entries: usize,
+    /// Threads in which a library (e.g. `spark.RouteImpl` or a Spring
+    /// `invoke`) calls straight into the package.
+    pub dispatches: usize,
+    /// Threads with any frame in the package.
+    pub threads: usize,
+    pub score: usize,
+}
+
+/// `com.example.threadanalyzer.App.lambda$main$1(App.java:83)` -> `com.example.threadanalyzer`
+fn frame_package(frame: &CategorizedFrame) -> Option<String> {
+    let method = qualified_frame(&frame.line).split('(').next()?;
+    let segments: Vec<&str> = method
+        .split('.')
+        .take_while(|segment| segment.starts_with(|c: char| c.is_ascii_lowercase()) && !segment.contains('$'))
+        .collect();
+    // At least one package segment, and something (class, method) after it
+    if segments.is_empty() || segments.len() + 2 > method.split('.').count() {
+        return None;
+    }
+    Some(segments[..segments.len().min(MAX_PACKAGE_DEPTH)].join("."))
+}
+
+/// Frames that could be application code: not the JDK, not a catalogued library
+/// and not a Java EE API.
+fn candidate_package(frame: &CategorizedFrame) -> Option<String> {
+    if frame.category == FrameCategory::Jvm || frame.library.is_some() {
+        return None;
+    }
+    frame_package(frame).filter(|package| !package.starts_with("javax") && !package.starts_with("jakarta"))
+}
+
+fn candidate<'a>(candidates: &'a mut BTreeMap<String, PackageCandidate>, package: &str) -> &'a mut PackageCandidate {
+    candidates.entry(package.to_string()).or_insert_with(|| PackageCandidate {
+        package: package.to_string(),
+        main_entries: 0,
+        dispatches: 0,
+        threads: 0,
+        score: 0,
+    })
+}
+
+/// Scores every package that could hold application code. The entry frame of
+/// the `main` thread weighs most, then code called directly from a library's
+/// dispatch point, then how many threads run code in the package at all.
+pub fn score_app_packages(dumps: &[ThreadDump]) -> Vec<PackageCandidate> {
+    let mut candidates: BTreeMap<String, PackageCandidate> = BTreeMap::new();
+
+    for thread in dumps.iter().flat_map(|dump| &dump.threads) {
+        let packages: Vec<Option<String>> = thread.frames.iter().map(candidate_package).collect();
+
+        let seen: BTreeSet<&String> = packages.iter().flatten().collect();
+        for package in &seen {
+            candidate(&mut candidates, package).threads += 1;
+        }
+
+        if thread.name == "main" {
+            if let Some(package) = packages.iter().rev().flatten().next() {
+                candidate(&mut candidates, package).main_entries += 1;
+            }
+        }
+
+        // Frames are listed innermost first, so a frame's caller is the next one.
+        let dispatched: BTreeSet<&String> = packages
+            .windows(2)
+            .zip(thread.frames.iter().skip(1))
+            .filter(|(_, caller)| caller.library.is_some())
+            .filter_map(|(pair, _)| pair[0].as_ref())
+            .collect();
+        for package in dispatched {
+            candidate(&mut candidates, package).dispatches += 1;
+        }
+    }
+
+    let mut candidates: Vec<PackageCandidate> = candidates
+        .into_values()
+        .map(|mut candidate| {
+            candidate.score =
+                candidate.main_entries * MAIN_ENTRY_WEIGHT + candidate.dispatches * DISPATCH_WEIGHT + candidate.threads;
+            candidate
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.score.cmp(&a.score).then(a.package.cmp(&b.package)));
+    candidates
+}
+
+/// The packages worth treating as application code: those holding the `main`
+/// entry point, plus any scoring at least a quarter of the best candidate.
+pub fn infer_app_packages(dumps: &[ThreadDump]) -> Vec<String> {
+    let candidates = score_app_packages(dumps);
+    let Some(best) = candidates.first().map(|candidate| candidate.score) else {
+        return Vec::new();
+    };
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.main_entries > 0 || candidate.score * 4 >= best)
+        .map(|candidate| candidate.package)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classifier::Classifier;
+    use crate::models::NormalizedThread;
+    use chrono::Utc;
+
+    fn thread(name: &str, frames: &[&str]) -> NormalizedThread {
+        let classifier = Classifier::default();
+        NormalizedThread {
+            name: name.to_string(),
+            state: "RUNNABLE".to_string(),
+            frames: frames.iter().map(|line| classifier.classify_frame(line)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_infer_app_packages() {
+        let dump = ThreadDump {
+            jvm_version: "17".to_string(),
+            timestamp: Utc::now(),
+            threads: vec![
+                thread(
+                    "main",
+                    &[
+                        "at java.lang.Thread.sleep(java.base@17.0.2/Native Method)",
+                        "at com.mycorp.shop.Main.main(Main.java:12)",
+                    ],
+                ),
+                thread(
+                    "qtp1-5",
+                    &[
+                        "at org.apache.commons.lang3.StringUtils.join(StringUtils.java:4001)",
+                        "at com.mycorp.shop.api.OrderRoutes.lambda$routes$0(OrderRoutes.java:31)",
+                        "at spark.RouteImpl$1.handle(RouteImpl.java:72)",
+                        "at org.eclipse.jetty.util.thread.QueuedThreadPool$Runner.run(QueuedThreadPool.java:1034)",
+                    ],
+                ),
+                thread(
+                    "evictor",
+                    &[
+                        "at org.apache.commons.pool2.impl.EvictionTimer$Reaper.run(EvictionTimer.java:96)",
+                        "at java.lang.Thread.run(java.base@17.0.2/Thread.java:833)",
+                    ],
+                ),
+            ],
+        };
+
+        let candidates = score_app_packages(std::slice::from_ref(&dump));
+        assert_eq!(candidates[0].package, "com.mycorp.shop");
+        assert_eq!((candidates[0].main_entries, candidates[0].dispatches, candidates[0].threads), (1, 1, 2));
+        assert_eq!(infer_app_packages(&[dump]), vec!["com.mycorp.shop"]);
+    }
+}
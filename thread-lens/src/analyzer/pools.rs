@@ -0,0 +1,225 @@
+use crate::analyzer::groups::{group_threads, SignatureOptions, StackGroup};
+use crate::models::{NormalizedThread, ThreadDump};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+lazy_static! {
+    /// Well-known worker naming schemes; capture group 1 is the pool name.
+    static ref KNOWN_POOL_PATTERNS: Vec<Regex> = vec![
+        // Jetty: qtp2031288110-28, qtp2031288110-13-acceptor-0@...
+        Regex::new(r"^(qtp\d+)-\d+").unwrap(),
+        // Executors.defaultThreadFactory: pool-3-thread-7
+        Regex::new(r"^(pool-\d+)-thread-\d+$").unwrap(),
+        // ForkJoinPool.commonPool-worker-5, ForkJoinPool-1-worker-3
+        Regex::new(r"^(.+)-worker-\d+$").unwrap(),
+        // Tomcat: http-nio-8080-exec-12
+        Regex::new(r"^(.+-exec)-\d+$").unwrap(),
+        // gRPC: grpc-default-executor-3
+        Regex::new(r"^(.+-executor)-\d+$").unwrap(),
+    ];
+    /// Any other name ending in a sequence number, e.g. Thread-1234.
+    static ref NUMBERED_THREAD_PATTERN: Regex = Regex::new(r"^(.*[^\d\s#_-])[-_#]\d+$").unwrap();
+}
+
+/// Frames that only appear on a pool worker's stack while it waits for work.
+const IDLE_WORKER_FRAMES: &[&str] = &[
+    "QueuedThreadPool$Runner.idleJobPoll",
+    "ThreadPoolExecutor.getTask",
+    "ForkJoinPool.awaitWork",
+    "NioEventLoop.select",
+    "EpollEventLoop.epollWait",
+];
+
+/// Per-dump statistics for one thread pool. `busy` counts every worker that is
+/// not waiting for work, so `blocked` workers are included in it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PoolStats {
+    pub name: String,
+    pub size: usize,
+    pub idle: usize,
+    pub busy: usize,
+    pub blocked: usize,
+    pub top_busy_stacks: Vec<StackGroup>,
+    /// True when the pool was only recognised by a generic `Name-N` pattern
+    /// rather than a known executor naming scheme.
+    pub generic: bool,
+}
+
+impl PoolStats {
+    /// Generic name patterns are not assumed to be worker pools, so they are
+    /// only saturated against a configured maximum.
+    pub fn is_saturated(&self, max_size: Option<usize>) -> bool {
+        let no_idle_workers = !self.generic && self.size > 0 && self.idle == 0;
+        no_idle_workers || max_size.is_some_and(|max| self.size >= max)
+    }
+}
+
+/// A pool that ran out of idle workers (or hit its configured maximum) in at
+/// least one dump of a series.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PoolSaturation {
+    pub name: String,
+    pub saturated_dumps: usize,
+    pub dumps_seen: usize,
+    pub max_size: usize,
+    pub max_busy: usize,
+}
+
+/// Normalizes a thread name to the pool it belongs to, if it looks like a pool worker.
+pub fn pool_name(thread_name: &str) -> Option<String> {
+    KNOWN_POOL_PATTERNS
+        .iter()
+        .chain(std::iter::once(&*NUMBERED_THREAD_PATTERN))
+        .find_map(|pattern| pattern.captures(thread_name))
+        .map(|captures| captures[1].to_string())
+}
+
+/// The pool name without the instance number the JVM hands out at start-up,
+/// so the same pool matches across restarts: `pool-3` and `ForkJoinPool-1`
+/// become `pool` and `ForkJoinPool`, and Jetty's `qtp2031288110` (named after
+/// an identity hash) becomes `qtp`.
+pub(crate) fn pool_family(pool: &str) -> String {
+    if pool.strip_prefix("qtp").is_some_and(|hash| !hash.is_empty() && hash.bytes().all(|b| b.is_ascii_digit())) {
+        return "qtp".to_string();
+    }
+    NUMBERED_THREAD_PATTERN
+        .captures(pool)
+        .map_or_else(|| pool.to_string(), |captures| captures[1].to_string())
+}
+
+pub(crate) fn is_known_pool_worker(thread_name: &str) -> bool {
+    KNOWN_POOL_PATTERNS.iter().any(|pattern| pattern.is_match(thread_name))
+}
+
+pub fn is_idle_worker(thread: &NormalizedThread) -> bool {
+    thread
+        .frames
+        .iter()
+        .any(|frame| IDLE_WORKER_FRAMES.iter().any(|marker| frame.line.contains(marker)))
+}
+
+/// Groups the threads of a dump into pools, largest first. VM-internal threads
+/// without Java frames are ignored, and a generic numbered name (`Thread-12`)
+/// only counts as a pool once at least two threads share it.
+pub fn pool_stats(dump: &ThreadDump) -> Vec<PoolStats> {
+    let mut members: BTreeMap<String, Vec<&NormalizedThread>> = BTreeMap::new();
+    let mut known: HashMap<String, bool> = HashMap::new();
+    for thread in dump.threads.iter().filter(|thread| !thread.frames.is_empty()) {
+        if let Some(name) = pool_name(&thread.name) {
+            *known.entry(name.clone()).or_insert(false) |= is_known_pool_worker(&thread.name);
+            members.entry(name).or_default().push(thread);
+        }
+    }
+
+    let signature = SignatureOptions {
+        ignore_line_numbers: true,
+        ..Default::default()
+    };
+    let mut stats: Vec<PoolStats> = members
+        .into_iter()
+        .filter(|(name, threads)| known[name] || threads.len() > 1)
+        .map(|(name, threads)| {
+            let generic = !known[&name];
+            let busy: Vec<&NormalizedThread> = threads.iter().copied().filter(|t| !is_idle_worker(t)).collect();
+            let mut top_busy_stacks = group_threads(busy.iter().copied(), &signature);
+            top_busy_stacks.truncate(3);
+            PoolStats {
+                name,
+                size: threads.len(),
+                idle: threads.len() - busy.len(),
+                busy: busy.len(),
+                blocked: busy.iter().filter(|t| t.state == "BLOCKED").count(),
+                top_busy_stacks,
+                generic,
+            }
+        })
+        .collect();
+    stats.sort_by(|a, b| b.size.cmp(&a.size).then(a.name.cmp(&b.name)));
+    stats
+}
+
+/// Finds pools that were saturated in any dump of the series. `max_sizes` holds
+/// known configured maximums by pool name; without one, a pool is saturated
+/// when none of its workers is idle.
+pub fn detect_pool_saturation(dumps: &[ThreadDump], max_sizes: &HashMap<String, usize>) -> Vec<PoolSaturation> {
+    let mut saturation: BTreeMap<String, PoolSaturation> = BTreeMap::new();
+    for dump in dumps {
+        for stats in pool_stats(dump) {
+            let entry = saturation.entry(stats.name.clone()).or_insert_with(|| PoolSaturation {
+                name: stats.name.clone(),
+                saturated_dumps: 0,
+                dumps_seen: 0,
+                max_size: 0,
+                max_busy: 0,
+            });
+            entry.dumps_seen += 1;
+            entry.max_size = entry.max_size.max(stats.size);
+            entry.max_busy = entry.max_busy.max(stats.busy);
+            if stats.is_saturated(max_sizes.get(&stats.name).copied()) {
+                entry.saturated_dumps += 1;
+            }
+        }
+    }
+
+    let mut saturated: Vec<PoolSaturation> = saturation
+        .into_values()
+        .filter(|pool| pool.saturated_dumps > 0)
+        .collect();
+    saturated.sort_by(|a, b| {
+        b.saturated_dumps
+            .cmp(&a.saturated_dumps)
+            .then(b.max_size.cmp(&a.max_size))
+            .then(a.name.cmp(&b.name))
+    });
+    saturated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_jstack_output;
+    use std::fs;
+
+    #[test]
+    fn test_pool_name() {
+        assert_eq!(pool_name("qtp2031288110-28").as_deref(), Some("qtp2031288110"));
+        assert_eq!(
+            pool_name("qtp1359040269-13-acceptor-0@5c224f32-ServerConnector@17d0e667{HTTP/1.1, (http/1.1)}{0.0.0.0:8080}").as_deref(),
+            Some("qtp1359040269")
+        );
+        assert_eq!(pool_name("pool-3-thread-7").as_deref(), Some("pool-3"));
+        assert_eq!(pool_name("ForkJoinPool.commonPool-worker-5").as_deref(), Some("ForkJoinPool.commonPool"));
+        assert_eq!(pool_name("http-nio-8080-exec-12").as_deref(), Some("http-nio-8080-exec"));
+        assert_eq!(pool_name("grpc-default-executor-3").as_deref(), Some("grpc-default-executor"));
+        assert_eq!(pool_name("Thread-1234").as_deref(), Some("Thread"));
+        assert_eq!(pool_name("main"), None);
+        assert_eq!(pool_name("C2 CompilerThread0"), None);
+
+        assert_eq!(pool_family("qtp2031288110"), "qtp");
+        assert_eq!(pool_family("pool-3"), "pool");
+        assert_eq!(pool_family("ForkJoinPool-1"), "ForkJoinPool");
+        assert_eq!(pool_family("ForkJoinPool.commonPool"), "ForkJoinPool.commonPool");
+        assert_eq!(pool_family("http-nio-8080-exec"), "http-nio-8080-exec");
+    }
+
+    #[test]
+    fn test_pool_stats_for_jetty() {
+        let content = fs::read_to_string(
+            "src/test_data/java-app-openjdk11_20250921085315_deadlock/java-app-openjdk11_20250921155416927.jstack",
+        )
+        .expect("Unable to read OpenJDK 11 dump");
+        let dump = parse_jstack_output(&content).unwrap();
+
+        let stats = pool_stats(&dump);
+        let jetty = stats.iter().find(|pool| pool.name == "qtp1359040269").unwrap();
+        assert_eq!(jetty.size, 8);
+        assert_eq!(jetty.idle, 6);
+        assert_eq!(jetty.busy, 2);
+        assert!(!jetty.is_saturated(None));
+        assert!(jetty.is_saturated(Some(8)));
+        assert!(!jetty.generic);
+        assert!(detect_pool_saturation(&[dump], &HashMap::new()).is_empty());
+    }
+}
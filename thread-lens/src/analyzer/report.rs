@@ -0,0 +1,148 @@
+use crate::analyzer::blockers::{find_deadlocks, summarize_root_blockers_across, Deadlock, RootBlocker};
+use crate::analyzer::chronic::{find_chronically_blocked_threads, ChronicBlockingOptions, ChronicallyBlockedThread};
+use crate::analyzer::in_time_order;
+use crate::analyzer::leaks::{detect_thread_leaks, LeakReport};
+use crate::analyzer::pools::{detect_pool_saturation, PoolSaturation};
+use crate::antipatterns::{Finding, RuleEngine};
+use crate::models::ThreadDump;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Version of the [`AnalysisReport`] document. Fields may be added within a
+/// version; renaming or removing one bumps it.
+pub const ANALYSIS_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct AnalysisOptions {
+    pub chronic: ChronicBlockingOptions,
+    /// Known configured maximum size by pool name.
+    pub pool_max_sizes: BTreeMap<String, usize>,
+}
+
+/// One dump of the analyzed series.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnalyzedDump {
+    pub timestamp: DateTime<Utc>,
+    pub jvm_version: String,
+    pub threads: usize,
+}
+
+/// Everything `analyze` reports on a series, in a stable order: dumps in
+/// capture order, and every list most important first.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnalysisReport {
+    pub version: u32,
+    pub dumps: Vec<AnalyzedDump>,
+    pub root_blockers: Vec<RootBlocker>,
+    pub deadlocks: Vec<Deadlock>,
+    pub saturated_pools: Vec<PoolSaturation>,
+    pub leaks: LeakReport,
+    pub findings: Vec<Finding>,
+    pub chronically_blocked: Vec<ChronicallyBlockedThread>,
+}
+
+/// Runs the analyses of the `analyze` command over a series.
+pub fn analyze(dumps: &[ThreadDump], engine: &RuleEngine, options: &AnalysisOptions) -> Result<AnalysisReport, String> {
+    let root_blockers = summarize_root_blockers_across(dumps);
+    let max_sizes = options.pool_max_sizes.clone().into_iter().collect();
+    Ok(AnalysisReport {
+        version: ANALYSIS_VERSION,
+        dumps: in_time_order(dumps)
+            .into_iter()
+            .map(|dump| AnalyzedDump {
+                timestamp: dump.timestamp,
+                jvm_version: dump.jvm_version.clone(),
+                threads: dump.threads.len(),
+            })
+            .collect(),
+        deadlocks: find_deadlocks(dumps),
+        root_blockers,
+        saturated_pools: detect_pool_saturation(dumps, &max_sizes),
+        leaks: detect_thread_leaks(dumps, &Default::default()),
+        findings: engine.evaluate(dumps),
+        chronically_blocked: find_chronically_blocked_threads(dumps, &options.chronic).map_err(|e| e.to_string())?,
+    })
+}
+
+fn record<T: Serialize>(kind: &str, item: &T) -> Result<Value, serde_json::Error> {
+    let mut value = serde_json::to_value(item)?;
+    if let Value::Object(fields) = &mut value {
+        fields.insert("record".to_string(), Value::from(kind));
+    }
+    Ok(value)
+}
+
+impl AnalysisReport {
+    /// The report as newline-delimited JSON: a `header` record with the
+    /// version, then one record per dump, root blocker, deadlock, saturated
+    /// pool, finding and chronically blocked thread, and one `leaks` record.
+    /// Each record names its type in a `record` field.
+    pub fn to_ndjson(&self) -> Result<String, serde_json::Error> {
+        let mut records = vec![serde_json::json!({
+            "record": "header",
+            "version": self.version,
+            "dumps": self.dumps.len(),
+        })];
+        for dump in &self.dumps {
+            records.push(record("dump", dump)?);
+        }
+        for blocker in &self.root_blockers {
+            records.push(record("root_blocker", blocker)?);
+        }
+        for deadlock in &self.deadlocks {
+            records.push(record("deadlock", deadlock)?);
+        }
+        for pool in &self.saturated_pools {
+            records.push(record("saturated_pool", pool)?);
+        }
+        records.push(record("leaks", &self.leaks)?);
+        for finding in &self.findings {
+            records.push(record("finding", finding)?);
+        }
+        for blocked in &self.chronically_blocked {
+            records.push(record("chronically_blocked", blocked)?);
+        }
+        let lines: Vec<String> = records.iter().map(Value::to_string).collect();
+        Ok(lines.join("\n") + "\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_jstack_output;
+    use std::fs;
+
+    #[test]
+    fn test_analysis_report_is_stable_and_versioned() {
+        let dir = "src/test_data/java-app-openjdk11_20250921085315_deadlock";
+        let mut paths: Vec<_> = fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        paths.sort();
+        let mut dumps: Vec<ThreadDump> = paths
+            .iter()
+            .map(|path| parse_jstack_output(&fs::read_to_string(path).unwrap()).unwrap())
+            .collect();
+
+        let report = analyze(&dumps, &RuleEngine::default(), &Default::default()).unwrap();
+        assert_eq!(report.version, ANALYSIS_VERSION);
+        assert_eq!(report.dumps.len(), dumps.len());
+        assert!(report.dumps.windows(2).all(|pair| pair[0].timestamp <= pair[1].timestamp));
+        assert_eq!(report.deadlocks.len(), 1);
+        assert_eq!(report.deadlocks[0].threads.len(), 2);
+
+        // The same series in another order gives the same document.
+        dumps.reverse();
+        let reversed = analyze(&dumps, &RuleEngine::default(), &Default::default()).unwrap();
+        assert_eq!(serde_json::to_string(&report).unwrap(), serde_json::to_string(&reversed).unwrap());
+
+        let ndjson = report.to_ndjson().unwrap();
+        let records: Vec<Value> = ndjson.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(records[0]["record"], "header");
+        assert_eq!(records[0]["version"], ANALYSIS_VERSION);
+        assert_eq!(records.iter().filter(|record| record["record"] == "dump").count(), report.dumps.len());
+        assert_eq!(records.iter().filter(|record| record["record"] == "deadlock").count(), report.deadlocks.len());
+    }
+}
@@ -0,0 +1,264 @@
+use crate::analyzer::blockers::{find_deadlocks, join_threads, summarize_root_blockers_across};
+use crate::analyzer::chronic::find_chronically_blocked_threads;
+use crate::analyzer::in_time_order;
+use crate::analyzer::leaks::{detect_thread_leaks, GrowthScope, ThreadGrowth};
+use crate::analyzer::pools::detect_pool_saturation;
+use crate::antipatterns::{RuleEngine, Severity};
+use crate::models::ThreadDump;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Health points taken off for each kind of finding.
+const DEADLOCK_PENALTY: u32 = 50;
+const SATURATED_POOL_PENALTY: u32 = 15;
+const LEAK_PENALTY: u32 = 10;
+const CHRONIC_PENALTY_PER_THREAD: u32 = 5;
+const MAX_BLOCKER_PENALTY: u32 = 20;
+/// Taken off in full when every thread of the latest dump is BLOCKED.
+const BLOCKED_SHARE_PENALTY: f64 = 20.0;
+
+fn rule_penalty(severity: Severity) -> u32 {
+    match severity {
+        Severity::Critical => 20,
+        Severity::Warning => 10,
+        Severity::Info => 2,
+    }
+}
+
+/// The analysis a finding comes from, i.e. where to look for the details.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Section {
+    RootBlockers,
+    Pools,
+    Leaks,
+    ChronicBlocking,
+    AntiPatterns,
+    States,
+}
+
+impl Section {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Section::RootBlockers => "Root blockers",
+            Section::Pools => "Saturated thread pools",
+            Section::Leaks => "Possible thread leaks",
+            Section::ChronicBlocking => "Chronically blocked threads",
+            Section::AntiPatterns => "Known anti-patterns",
+            Section::States => "Thread states",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SummaryFinding {
+    pub severity: Severity,
+    pub title: String,
+    pub section: Section,
+    /// Health points this finding costs.
+    pub penalty: u32,
+}
+
+/// A one-screen verdict over a dump or series.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Summary {
+    pub dumps: usize,
+    /// 100 for a healthy series, down to 0.
+    pub health_score: u32,
+    pub deadlocked: bool,
+    /// Thread count and states of the latest dump.
+    pub threads: usize,
+    pub blocked_threads: usize,
+    pub states: BTreeMap<String, usize>,
+    /// Thread count per dump in capture order, and its trend in threads per dump.
+    pub thread_counts: Vec<usize>,
+    pub thread_trend_per_dump: f64,
+    pub saturated_pools: Vec<String>,
+    /// Most severe (and costly) first.
+    pub findings: Vec<SummaryFinding>,
+}
+
+/// Runs every analysis over the dumps and condenses the results into a health
+/// score and a prioritized list of findings.
+pub fn summarize(dumps: &[ThreadDump], engine: &RuleEngine) -> Summary {
+    let mut findings = Vec::new();
+
+    let root_blockers = summarize_root_blockers_across(dumps);
+    let deadlocks = find_deadlocks(dumps);
+    for deadlock in &deadlocks {
+        findings.push(SummaryFinding {
+            severity: Severity::Critical,
+            title: format!(
+                "Deadlock between {} (in {} of {} dumps)",
+                join_threads(&deadlock.threads),
+                deadlock.dumps,
+                dumps.len()
+            ),
+            section: Section::RootBlockers,
+            penalty: DEADLOCK_PENALTY,
+        });
+    }
+    for blocker in root_blockers.iter().filter(|blocker| !blocker.deadlocked) {
+        let at = blocker
+            .frame
+            .as_deref()
+            .map(|frame| format!(" at {}", frame.trim().trim_start_matches("at ")))
+            .unwrap_or_default();
+        let unresolved = blocker.unresolved_note().map(|note| format!(", {}", note)).unwrap_or_default();
+        findings.push(SummaryFinding {
+            severity: Severity::Warning,
+            title: format!(
+                "{} threads blocked by '{}'{}{} (in {} of {} dumps)",
+                blocker.blocked_threads.len(),
+                blocker.thread,
+                at,
+                unresolved,
+                blocker.dumps,
+                dumps.len()
+            ),
+            section: Section::RootBlockers,
+            penalty: (blocker.blocked_threads.len() as u32 * 2 + 3).min(MAX_BLOCKER_PENALTY),
+        });
+    }
+
+    let saturated = detect_pool_saturation(dumps, &HashMap::new());
+    for pool in &saturated {
+        findings.push(SummaryFinding {
+            severity: Severity::Warning,
+            title: format!(
+                "Pool '{}' had no idle workers in {} of {} dumps (max size {})",
+                pool.name, pool.saturated_dumps, pool.dumps_seen, pool.max_size
+            ),
+            section: Section::Pools,
+            penalty: SATURATED_POOL_PENALTY,
+        });
+    }
+
+    let leaks = detect_thread_leaks(dumps, &Default::default());
+    if leaks.total.steady {
+        findings.push(SummaryFinding {
+            severity: Severity::Warning,
+            title: format!("Thread count grew by {} over the series", leaks.total.growth()),
+            section: Section::Leaks,
+            penalty: LEAK_PENALTY,
+        });
+    }
+    let suspects: Vec<&ThreadGrowth> = leaks
+        .suspects
+        .iter()
+        .filter(|growth| growth.scope != GrowthScope::Total)
+        .collect();
+    if let Some(largest) = suspects.first() {
+        let scope = match &largest.scope {
+            GrowthScope::Pool(name) => format!("pool '{}'", name),
+            GrowthScope::CreationStack(entry) => format!("threads started from {}", entry),
+            GrowthScope::Total => unreachable!("filtered out above"),
+        };
+        findings.push(SummaryFinding {
+            severity: Severity::Warning,
+            title: format!(
+                "{} pools or creation stacks grow steadily, most of all {} (+{})",
+                suspects.len(),
+                scope,
+                largest.growth()
+            ),
+            section: Section::Leaks,
+            penalty: LEAK_PENALTY,
+        });
+    }
+
+    let chronic = find_chronically_blocked_threads(dumps, &Default::default()).unwrap_or_default();
+    if !chronic.is_empty() {
+        let names: Vec<String> = chronic.iter().map(|blocked| blocked.thread_ref().to_string()).collect();
+        findings.push(SummaryFinding {
+            severity: Severity::Warning,
+            title: format!("{} application threads stay blocked: {}", chronic.len(), names.join(", ")),
+            section: Section::ChronicBlocking,
+            penalty: (chronic.len() as u32 * CHRONIC_PENALTY_PER_THREAD).min(MAX_BLOCKER_PENALTY),
+        });
+    }
+
+    for finding in engine.evaluate(dumps) {
+        findings.push(SummaryFinding {
+            severity: finding.severity,
+            title: format!("{} ({} threads, in {} of {} dumps)", finding.title, finding.threads.len(), finding.dumps, finding.total_dumps),
+            section: Section::AntiPatterns,
+            penalty: rule_penalty(finding.severity),
+        });
+    }
+
+    let ordered = in_time_order(dumps);
+    let mut states: BTreeMap<String, usize> = BTreeMap::new();
+    if let Some(latest) = ordered.last() {
+        for thread in &latest.threads {
+            *states.entry(thread.state.clone()).or_insert(0) += 1;
+        }
+    }
+    let threads = ordered.last().map_or(0, |latest| latest.threads.len());
+    let blocked_threads = states.get("BLOCKED").copied().unwrap_or(0);
+    if blocked_threads > 0 {
+        let share = blocked_threads as f64 / threads as f64;
+        findings.push(SummaryFinding {
+            severity: Severity::Info,
+            title: format!("{} of {} threads BLOCKED in the latest dump", blocked_threads, threads),
+            section: Section::States,
+            penalty: (share * BLOCKED_SHARE_PENALTY).round() as u32,
+        });
+    }
+
+    findings.sort_by(|a, b| b.severity.cmp(&a.severity).then(b.penalty.cmp(&a.penalty)));
+    let penalty: u32 = findings.iter().map(|finding| finding.penalty).sum();
+
+    Summary {
+        dumps: dumps.len(),
+        health_score: 100u32.saturating_sub(penalty),
+        deadlocked: !deadlocks.is_empty(),
+        threads,
+        blocked_threads,
+        states,
+        thread_counts: leaks.total.counts.clone(),
+        thread_trend_per_dump: leaks.total.slope_per_dump,
+        saturated_pools: saturated.into_iter().map(|pool| pool.name).collect(),
+        findings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_jstack_output;
+    use std::fs;
+
+    fn read_series(dir: &str) -> Vec<ThreadDump> {
+        let mut paths: Vec<_> = fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        paths.sort();
+        paths
+            .iter()
+            .map(|path| parse_jstack_output(&fs::read_to_string(path).unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_summarize_deadlocked_series() {
+        let dumps = read_series("src/test_data/java-app-openjdk11_20250921085315_deadlock");
+        let summary = summarize(&dumps, &RuleEngine::default());
+
+        assert!(summary.deadlocked);
+        assert_eq!(summary.dumps, dumps.len());
+        assert_eq!(summary.findings[0].severity, Severity::Critical);
+        assert_eq!(summary.findings[0].section, Section::RootBlockers);
+        assert!(summary.findings[0].title.starts_with(
+            "Deadlock between DeadlockThread-1 (0x0000e7a05c004000), DeadlockThread-2 (0x0000e7a05c006000) "
+        ));
+        assert_eq!(summary.findings.iter().filter(|finding| finding.severity == Severity::Critical).count(), 1);
+        assert!(summary.health_score <= 100 - DEADLOCK_PENALTY);
+        assert_eq!(summary.thread_counts.len(), dumps.len());
+    }
+
+    #[test]
+    fn test_summarize_empty_series_is_healthy() {
+        let summary = summarize(&[], &RuleEngine::default());
+        assert_eq!(summary.health_score, 100);
+        assert!(summary.findings.is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::analyzer::blockers::ThreadRef;
+use crate::analyzer::{in_time_order, thread_ids};
+use crate::models::{FrameCategory, NormalizedThread, ThreadDump};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A thread's state in one dump; `state` is `None` when the thread was not in that dump.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TimelineStep {
+    pub state: Option<String>,
+    pub top_app_frame: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ThreadTimeline {
+    pub thread: String,
+    pub id: Option<String>,
+    /// One step per dump, aligned with `Timeline::timestamps`.
+    pub steps: Vec<TimelineStep>,
+}
+
+impl ThreadTimeline {
+    /// Compact state strip, one character per dump: R(unnable), B(locked),
+    /// W(aiting), T(imed waiting), `?` for unknown states and `.` when absent.
+    pub fn state_strip(&self) -> String {
+        self.steps
+            .iter()
+            .map(|step| match step.state.as_deref() {
+                None => '.',
+                Some("RUNNABLE") => 'R',
+                Some("BLOCKED") => 'B',
+                Some("WAITING") => 'W',
+                Some("TIMED_WAITING") => 'T',
+                Some(_) => '?',
+            })
+            .collect()
+    }
+
+    pub fn changes_state(&self) -> bool {
+        let mut states = self.steps.iter().filter_map(|step| step.state.as_deref());
+        match states.next() {
+            Some(first) => states.any(|state| state != first),
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Timeline {
+    pub timestamps: Vec<DateTime<Utc>>,
+    pub threads: Vec<ThreadTimeline>,
+}
+
+fn top_app_frame(thread: &NormalizedThread) -> Option<String> {
+    thread
+        .frames
+        .iter()
+        .find(|frame| frame.category == FrameCategory::Application)
+        .map(|frame| frame.line.clone())
+}
+
+/// Builds per-thread state sequences over the series in capture order. Threads
+/// are followed by name and id (see [`thread_ids`]).
+pub fn build_timeline(dumps: &[ThreadDump]) -> Timeline {
+    let ordered = in_time_order(dumps);
+    let mut timelines: BTreeMap<ThreadRef, ThreadTimeline> = BTreeMap::new();
+
+    for (index, dump) in ordered.iter().enumerate() {
+        for (thread, id) in dump.threads.iter().zip(thread_ids(dump)) {
+            let timeline = timelines
+                .entry(ThreadRef {
+                    name: thread.name.clone(),
+                    id,
+                })
+                .or_insert_with(|| ThreadTimeline {
+                    thread: thread.name.clone(),
+                    id: thread.id.clone(),
+                    steps: vec![
+                        TimelineStep {
+                            state: None,
+                            top_app_frame: None,
+                        };
+                        ordered.len()
+                    ],
+                });
+            timeline.steps[index] = TimelineStep {
+                state: Some(thread.state.clone()),
+                top_app_frame: top_app_frame(thread),
+            };
+        }
+    }
+
+    Timeline {
+        timestamps: ordered.iter().map(|dump| dump.timestamp).collect(),
+        threads: timelines.into_values().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn dump_at(minute: i64, states: &[(&str, &str)]) -> ThreadDump {
+        ThreadDump {
+            jvm_version: "17".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2025, 9, 21, 3, 0, 0).unwrap() + Duration::minutes(minute),
+            threads: states
+                .iter()
+                .map(|(name, state)| NormalizedThread {
+                    name: name.to_string(),
+                    state: state.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_build_timeline_state_strips() {
+        let dumps = vec![
+            dump_at(2, &[("worker", "BLOCKED"), ("late", "WAITING")]),
+            dump_at(0, &[("worker", "RUNNABLE"), ("steady", "TIMED_WAITING")]),
+            dump_at(1, &[("worker", "BLOCKED"), ("steady", "TIMED_WAITING")]),
+            dump_at(3, &[("worker", "RUNNABLE"), ("late", "NEW")]),
+        ];
+
+        let timeline = build_timeline(&dumps);
+        assert_eq!(timeline.timestamps.len(), 4);
+        assert!(timeline.timestamps.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let strips: Vec<(String, String, bool)> = timeline
+            .threads
+            .iter()
+            .map(|t| (t.thread.clone(), t.state_strip(), t.changes_state()))
+            .collect();
+        assert_eq!(
+            strips,
+            vec![
+                ("late".to_string(), "..W?".to_string(), true),
+                ("steady".to_string(), "TT..".to_string(), false),
+                ("worker".to_string(), "RBBR".to_string(), true),
+            ]
+        );
+    }
+}
@@ -0,0 +1,325 @@
+use crate::classifier::qualified_frame;
+use crate::models::{LockAction, NormalizedThread, ThreadDump};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// The rules shipped with thread-lens, in the same format as a rules file.
+pub const STARTER_RULES: &str = include_str!("starter.toml");
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+/// One anti-pattern. A thread matches when every condition that is set holds;
+/// the rule fires in a dump when at least `min_threads` threads match.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PatternRule {
+    pub id: String,
+    pub title: String,
+    pub severity: Severity,
+    pub explanation: String,
+    /// Link to documentation on how to fix it.
+    pub remediation: String,
+    /// Regexes that must match frames in this order, innermost first. The
+    /// frames need not be adjacent, but each pattern needs a frame of its own.
+    #[serde(default)]
+    pub frames: Vec<String>,
+    /// Only the top `within_top` frames are searched; 0 searches the whole stack.
+    #[serde(default)]
+    pub within_top: usize,
+    /// Thread states the rule applies to; empty means any state.
+    #[serde(default)]
+    pub states: Vec<String>,
+    /// Regexes of which at least one must match the class of a lock the
+    /// thread is waiting for, e.g. `^org\.apache\.log4j\.Logger$`.
+    #[serde(default)]
+    pub locks: Vec<String>,
+    #[serde(default = "default_min_threads")]
+    pub min_threads: usize,
+    /// Minimum number of dumps of a series the rule must fire in.
+    #[serde(default = "default_min_dumps")]
+    pub min_dumps: usize,
+}
+
+fn default_min_threads() -> usize {
+    1
+}
+
+fn default_min_dumps() -> usize {
+    1
+}
+
+/// The contents of a rules file: a list of `[[rules]]`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct RuleSet {
+    pub rules: Vec<PatternRule>,
+}
+
+/// A rule that fired, with the threads it matched.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Finding {
+    pub rule_id: String,
+    pub title: String,
+    pub severity: Severity,
+    pub explanation: String,
+    pub remediation: String,
+    /// Names of the matching threads, across all dumps the rule fired in.
+    pub threads: Vec<String>,
+    pub dumps: usize,
+    pub total_dumps: usize,
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    rule: PatternRule,
+    frames: Vec<Regex>,
+    locks: Vec<Regex>,
+}
+
+impl CompiledRule {
+    fn new(rule: PatternRule) -> Result<CompiledRule, String> {
+        let compile = |patterns: &[String]| -> Result<Vec<Regex>, String> {
+            patterns
+                .iter()
+                .map(|pattern| Regex::new(pattern).map_err(|e| format!("rule '{}': invalid regex: {}", rule.id, e)))
+                .collect()
+        };
+        Ok(CompiledRule {
+            frames: compile(&rule.frames)?,
+            locks: compile(&rule.locks)?,
+            rule,
+        })
+    }
+
+    fn matches(&self, thread: &NormalizedThread) -> bool {
+        if !self.rule.states.is_empty() && !self.rule.states.contains(&thread.state) {
+            return false;
+        }
+        if !self.locks.is_empty() {
+            let waited_for = thread.locks.iter().filter(|lock| lock.action != LockAction::Locked);
+            let mut waited_for = waited_for.map(|lock| lock.class_name.as_str());
+            if !waited_for.any(|class_name| self.locks.iter().any(|pattern| pattern.is_match(class_name))) {
+                return false;
+            }
+        }
+        let depth = match self.rule.within_top {
+            0 => thread.frames.len(),
+            top => top.min(thread.frames.len()),
+        };
+        let mut frames = thread.frames[..depth].iter().map(|frame| qualified_frame(&frame.line));
+        self.frames.iter().all(|pattern| frames.any(|frame| pattern.is_match(frame)))
+    }
+}
+
+/// Matches anti-pattern rules against the threads of a dump or a series.
+#[derive(Debug, Clone)]
+pub struct RuleEngine {
+    rules: Vec<CompiledRule>,
+}
+
+impl Default for RuleEngine {
+    fn default() -> Self {
+        RuleEngine::from_toml(STARTER_RULES).expect("starter rules are valid")
+    }
+}
+
+impl RuleEngine {
+    pub fn new(rules: Vec<PatternRule>) -> Result<RuleEngine, String> {
+        Ok(RuleEngine {
+            rules: rules.into_iter().map(CompiledRule::new).collect::<Result<_, _>>()?,
+        })
+    }
+
+    pub fn from_toml(content: &str) -> Result<RuleEngine, String> {
+        let rules: RuleSet = toml::from_str(content).map_err(|e| e.to_string())?;
+        RuleEngine::new(rules.rules)
+    }
+
+    pub fn from_yaml(content: &str) -> Result<RuleEngine, String> {
+        let rules: RuleSet = serde_yaml::from_str(content).map_err(|e| e.to_string())?;
+        RuleEngine::new(rules.rules)
+    }
+
+    /// Adds rules after the current ones; a rule with the id of an existing
+    /// rule replaces it.
+    pub fn with_rules(mut self, other: RuleEngine) -> RuleEngine {
+        for rule in other.rules {
+            match self.rules.iter_mut().find(|existing| existing.rule.id == rule.rule.id) {
+                Some(existing) => *existing = rule,
+                None => self.rules.push(rule),
+            }
+        }
+        self
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item = &PatternRule> {
+        self.rules.iter().map(|compiled| &compiled.rule)
+    }
+
+    /// Runs every rule over a series of dumps, most severe findings first.
+    pub fn evaluate(&self, dumps: &[ThreadDump]) -> Vec<Finding> {
+        let mut findings: Vec<Finding> = self
+            .rules
+            .iter()
+            .filter_map(|compiled| {
+                let mut threads: BTreeSet<&str> = BTreeSet::new();
+                let mut fired_dumps = 0;
+                for dump in dumps {
+                    let matching: Vec<&str> = dump
+                        .threads
+                        .iter()
+                        .filter(|thread| compiled.matches(thread))
+                        .map(|thread| thread.name.as_str())
+                        .collect();
+                    if !matching.is_empty() && matching.len() >= compiled.rule.min_threads {
+                        fired_dumps += 1;
+                        threads.extend(matching);
+                    }
+                }
+                if fired_dumps == 0 || fired_dumps < compiled.rule.min_dumps {
+                    return None;
+                }
+                let rule = &compiled.rule;
+                Some(Finding {
+                    rule_id: rule.id.clone(),
+                    title: rule.title.clone(),
+                    severity: rule.severity,
+                    explanation: rule.explanation.clone(),
+                    remediation: rule.remediation.clone(),
+                    threads: threads.into_iter().map(str::to_string).collect(),
+                    dumps: fired_dumps,
+                    total_dumps: dumps.len(),
+                })
+            })
+            .collect();
+        findings.sort_by(|a, b| {
+            b.severity
+                .cmp(&a.severity)
+                .then(b.dumps.cmp(&a.dumps))
+                .then(a.rule_id.cmp(&b.rule_id))
+        });
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classifier::Classifier;
+    use crate::models::LockInfo;
+    use chrono::Utc;
+
+    fn thread(name: &str, state: &str, frames: &[&str]) -> NormalizedThread {
+        let classifier = Classifier::default();
+        NormalizedThread {
+            name: name.to_string(),
+            state: state.to_string(),
+            frames: frames.iter().map(|line| classifier.classify_frame(line)).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn dump(threads: Vec<NormalizedThread>) -> ThreadDump {
+        ThreadDump {
+            jvm_version: "17".to_string(),
+            timestamp: Utc::now(),
+            threads,
+        }
+    }
+
+    #[test]
+    fn test_starter_rules() {
+        let appender = [
+            "at ch.qos.logback.core.AppenderBase.doAppend(AppenderBase.java:63)",
+            "at ch.qos.logback.core.spi.AppenderAttachableImpl.appendLoopOnAppenders(AppenderAttachableImpl.java:51)",
+            "at com.mycorp.orders.OrderService.place(OrderService.java:88)",
+        ];
+        let dumps = vec![dump(vec![
+            thread(
+                "http-nio-8080-exec-1",
+                "TIMED_WAITING",
+                &[
+                    "at java.base@17.0.2/jdk.internal.misc.Unsafe.park(Native Method)",
+                    "at java.base@17.0.2/java.util.concurrent.locks.LockSupport.parkNanos(LockSupport.java:252)",
+                    "at com.zaxxer.hikari.util.ConcurrentBag.borrow(ConcurrentBag.java:162)",
+                    "at com.zaxxer.hikari.pool.HikariPool.getConnection(HikariPool.java:181)",
+                    "at com.mycorp.orders.OrderRepository.find(OrderRepository.java:40)",
+                ],
+            ),
+            thread(
+                "main",
+                "RUNNABLE",
+                &[
+                    "at java.base@17.0.2/java.io.FileInputStream.readBytes(Native Method)",
+                    "at java.base@17.0.2/java.io.FileInputStream.read(FileInputStream.java:276)",
+                    "at java.base@17.0.2/sun.security.provider.NativePRNG$RandomIO.readFully(NativePRNG.java:424)",
+                    "at java.base@17.0.2/java.security.SecureRandom.nextBytes(SecureRandom.java:758)",
+                ],
+            ),
+            thread("http-nio-8080-exec-2", "BLOCKED", &appender),
+            thread("http-nio-8080-exec-3", "BLOCKED", &appender),
+            thread(
+                "cache-loader",
+                "RUNNABLE",
+                &[
+                    "at java.base@17.0.2/java.util.concurrent.ConcurrentHashMap.computeIfAbsent(ConcurrentHashMap.java:1708)",
+                    "at com.mycorp.Cache.lambda$get$0(Cache.java:12)",
+                    "at java.base@17.0.2/java.util.concurrent.ConcurrentHashMap.computeIfAbsent(ConcurrentHashMap.java:1708)",
+                ],
+            ),
+        ])];
+
+        let findings = RuleEngine::default().evaluate(&dumps);
+        let fired: Vec<(&str, Severity)> = findings.iter().map(|f| (f.rule_id.as_str(), f.severity)).collect();
+        assert_eq!(
+            fired,
+            vec![
+                ("chm-compute-if-absent-recursion", Severity::Critical),
+                ("hikari-connection-wait", Severity::Critical),
+                ("secure-random-blocking", Severity::Warning),
+                ("synchronized-log-appender", Severity::Warning),
+            ]
+        );
+        assert_eq!(findings[3].threads, vec!["http-nio-8080-exec-2", "http-nio-8080-exec-3"]);
+        assert!(findings[1].remediation.starts_with("https://"));
+    }
+
+    #[test]
+    fn test_custom_rules_with_locks_and_series_threshold() {
+        let yaml = r#"
+rules:
+  - id: log4j1-call-appenders
+    title: Custom override
+    severity: info
+    explanation: Logger monitor contention.
+    remediation: https://example.invalid/log4j
+    locks: ['^org\.apache\.log4j\.Logger$']
+    min_dumps: 2
+"#;
+        let engine = RuleEngine::default().with_rules(RuleEngine::from_yaml(yaml).unwrap());
+        assert_eq!(engine.rules().filter(|rule| rule.id == "log4j1-call-appenders").count(), 1);
+
+        let mut blocked = thread("worker", "BLOCKED", &["at org.apache.log4j.Category.callAppenders(Category.java:204)"]);
+        blocked.locks.push(LockInfo {
+            action: LockAction::WaitingToLock,
+            address: "0x00000000e2e2f648".to_string(),
+            class_name: "org.apache.log4j.Logger".to_string(),
+        });
+        let one = vec![dump(vec![blocked.clone()])];
+        assert!(engine.evaluate(&one).is_empty());
+
+        let two = vec![dump(vec![blocked.clone()]), dump(vec![blocked])];
+        let findings = engine.evaluate(&two);
+        assert_eq!(findings.len(), 1);
+        assert_eq!((findings[0].severity, findings[0].dumps, findings[0].total_dumps), (Severity::Info, 2, 2));
+
+        assert!(RuleEngine::from_toml("[[rules]]\nid = \"x\"").is_err());
+    }
+}
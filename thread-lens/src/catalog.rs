@@ -0,0 +1,175 @@
+use crate::models::{CategorizedFrame, LibraryRole};
+
+/// A well-known library, recognised by the packages its classes live in.
+#[derive(Debug)]
+pub struct LibraryEntry {
+    pub name: &'static str,
+    pub role: LibraryRole,
+    pub packages: &'static [&'static str],
+}
+
+const fn library(name: &'static str, role: LibraryRole, packages: &'static [&'static str]) -> LibraryEntry {
+    LibraryEntry { name, role, packages }
+}
+
+/// The built-in catalog. When several entries match a frame the longest
+/// package prefix wins, so `Spring MVC` takes precedence over `Spring`.
+pub const LIBRARIES: &[LibraryEntry] = &[
+    library("Jetty", LibraryRole::WebServer, &["org.eclipse.jetty."]),
+    library("Tomcat", LibraryRole::WebServer, &["org.apache.catalina.", "org.apache.coyote.", "org.apache.tomcat."]),
+    library("Undertow", LibraryRole::WebServer, &["io.undertow."]),
+    library("Netty", LibraryRole::WebServer, &["io.netty."]),
+    library("Servlet API", LibraryRole::WebFramework, &["javax.servlet.", "jakarta.servlet."]),
+    library("Spring MVC", LibraryRole::WebFramework, &["org.springframework.web."]),
+    library("Spring WebFlux", LibraryRole::WebFramework, &["org.springframework.web.reactive."]),
+    library("Spark", LibraryRole::WebFramework, &["spark."]),
+    library("Jersey", LibraryRole::WebFramework, &["org.glassfish.jersey.", "com.sun.jersey."]),
+    library("RESTEasy", LibraryRole::WebFramework, &["org.jboss.resteasy."]),
+    library("Spring", LibraryRole::AppFramework, &["org.springframework."]),
+    library("Guice", LibraryRole::AppFramework, &["com.google.inject."]),
+    library("Micronaut", LibraryRole::AppFramework, &["io.micronaut."]),
+    library("Quarkus", LibraryRole::AppFramework, &["io.quarkus."]),
+    library("JDBC", LibraryRole::Database, &["java.sql.", "javax.sql."]),
+    library("HikariCP", LibraryRole::Database, &["com.zaxxer.hikari."]),
+    library("c3p0", LibraryRole::Database, &["com.mchange.v2.c3p0."]),
+    library("Commons DBCP", LibraryRole::Database, &["org.apache.commons.dbcp.", "org.apache.commons.dbcp2."]),
+    library("Hibernate", LibraryRole::Database, &["org.hibernate."]),
+    library("Spring JDBC", LibraryRole::Database, &["org.springframework.jdbc."]),
+    library("Spring Data", LibraryRole::Database, &["org.springframework.data."]),
+    library("MyBatis", LibraryRole::Database, &["org.apache.ibatis.", "org.mybatis."]),
+    library("jOOQ", LibraryRole::Database, &["org.jooq."]),
+    library("PostgreSQL JDBC", LibraryRole::Database, &["org.postgresql."]),
+    library("MySQL Connector/J", LibraryRole::Database, &["com.mysql."]),
+    library("MariaDB Connector/J", LibraryRole::Database, &["org.mariadb.jdbc."]),
+    library("Oracle JDBC", LibraryRole::Database, &["oracle.jdbc."]),
+    library("MongoDB", LibraryRole::Database, &["com.mongodb."]),
+    library("Jedis", LibraryRole::Database, &["redis.clients.jedis."]),
+    library("Lettuce", LibraryRole::Database, &["io.lettuce."]),
+    library("Kafka", LibraryRole::Messaging, &["org.apache.kafka."]),
+    library("Spring Kafka", LibraryRole::Messaging, &["org.springframework.kafka."]),
+    library("RabbitMQ", LibraryRole::Messaging, &["com.rabbitmq."]),
+    library("Spring AMQP", LibraryRole::Messaging, &["org.springframework.amqp."]),
+    library("ActiveMQ", LibraryRole::Messaging, &["org.apache.activemq."]),
+    library("JMS", LibraryRole::Messaging, &["javax.jms.", "jakarta.jms."]),
+    library("Jakarta Mail", LibraryRole::Messaging, &["javax.mail.", "jakarta.mail.", "com.sun.mail."]),
+    library("gRPC", LibraryRole::Rpc, &["io.grpc."]),
+    library("Apache HttpClient", LibraryRole::Rpc, &["org.apache.http.", "org.apache.hc."]),
+    library("OkHttp", LibraryRole::Rpc, &["okhttp3."]),
+    library("JDK HttpClient", LibraryRole::Rpc, &["java.net.http.", "jdk.internal.net.http."]),
+    library("Feign", LibraryRole::Rpc, &["feign."]),
+    library("Thrift", LibraryRole::Rpc, &["org.apache.thrift."]),
+    library("Logback", LibraryRole::Logging, &["ch.qos.logback."]),
+    library("Log4j 2", LibraryRole::Logging, &["org.apache.logging.log4j."]),
+    library("Log4j", LibraryRole::Logging, &["org.apache.log4j."]),
+    library("SLF4J", LibraryRole::Logging, &["org.slf4j."]),
+    library("java.util.logging", LibraryRole::Logging, &["java.util.logging."]),
+    library("Jackson", LibraryRole::Serialization, &["com.fasterxml.jackson."]),
+    library("Gson", LibraryRole::Serialization, &["com.google.gson."]),
+    library("Protobuf", LibraryRole::Serialization, &["com.google.protobuf."]),
+    library("Kryo", LibraryRole::Serialization, &["com.esotericsoftware.kryo."]),
+    library("JAXB", LibraryRole::Serialization, &["javax.xml.bind.", "jakarta.xml.bind.", "com.sun.xml.bind."]),
+    library("JDK reflection", LibraryRole::Reflection, &["java.lang.reflect.", "jdk.internal.reflect.", "sun.reflect."]),
+    library("Byte Buddy", LibraryRole::Reflection, &["net.bytebuddy."]),
+    library("CGLIB", LibraryRole::Reflection, &["net.sf.cglib.", "org.springframework.cglib."]),
+    library("Javassist", LibraryRole::Reflection, &["javassist."]),
+    library("Spring AOP", LibraryRole::Reflection, &["org.springframework.aop."]),
+    library("JUnit", LibraryRole::Testing, &["org.junit."]),
+    library("TestNG", LibraryRole::Testing, &["org.testng."]),
+    library("Mockito", LibraryRole::Testing, &["org.mockito."]),
+    library("Maven Surefire", LibraryRole::Testing, &["org.apache.maven.surefire."]),
+];
+
+/// Looks up the library a qualified frame (`org.foo.Bar.baz(Bar.java:1)`) belongs to.
+pub fn identify_library(qualified_frame: &str) -> Option<&'static LibraryEntry> {
+    LIBRARIES
+        .iter()
+        .filter_map(|entry| {
+            entry
+                .packages
+                .iter()
+                .filter(|package| qualified_frame.starts_with(**package))
+                .map(|package| package.len())
+                .max()
+                .map(|length| (length, entry))
+        })
+        .max_by_key(|(length, _)| *length)
+        .map(|(_, entry)| entry)
+}
+
+/// Roles that describe what a thread was started to do, in order of preference
+/// when choosing what a library call happened "under".
+const ENTRY_ROLES: &[LibraryRole] = &[
+    LibraryRole::WebFramework,
+    LibraryRole::Messaging,
+    LibraryRole::Testing,
+    LibraryRole::Rpc,
+    LibraryRole::WebServer,
+    LibraryRole::AppFramework,
+];
+
+/// Describes where in the library stack a thread is, e.g. `database (HikariCP)
+/// under Spring MVC`: the innermost library frame (proxies and reflection are
+/// skipped), and the framework further down the stack that it was called from.
+pub fn library_context(frames: &[CategorizedFrame]) -> Option<String> {
+    let tagged: Vec<(usize, &str, &LibraryRole)> = frames
+        .iter()
+        .enumerate()
+        .filter_map(|(index, frame)| Some((index, frame.library.as_deref()?, frame.role.as_ref()?)))
+        .collect();
+    let &(inner_index, inner_library, inner_role) = tagged
+        .iter()
+        .find(|(_, _, role)| **role != LibraryRole::Reflection)
+        .or_else(|| tagged.first())?;
+
+    let outer = ENTRY_ROLES.iter().find_map(|entry_role| {
+        tagged
+            .iter()
+            .find(|(index, library, role)| *index > inner_index && *library != inner_library && *role == entry_role)
+            .map(|(_, library, _)| *library)
+    });
+
+    let inner = format!("{} ({})", inner_role.label(), inner_library);
+    Some(match outer {
+        Some(outer) => format!("{} under {}", inner, outer),
+        None => inner,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classifier::Classifier;
+
+    #[test]
+    fn test_identify_library_prefers_longest_prefix() {
+        assert_eq!(identify_library("org.springframework.web.servlet.DispatcherServlet.doDispatch(DispatcherServlet.java:1067)").unwrap().name, "Spring MVC");
+        assert_eq!(identify_library("org.springframework.beans.factory.support.AbstractBeanFactory.getBean(AbstractBeanFactory.java:208)").unwrap().name, "Spring");
+        assert_eq!(identify_library("com.zaxxer.hikari.pool.HikariPool.getConnection(HikariPool.java:181)").unwrap().role, LibraryRole::Database);
+        assert!(identify_library("com.example.App.main(App.java:3)").is_none());
+    }
+
+    #[test]
+    fn test_library_context() {
+        let classifier = Classifier::default();
+        let frames: Vec<CategorizedFrame> = [
+            "at java.base@17.0.2/jdk.internal.misc.Unsafe.park(Native Method)",
+            "at com.zaxxer.hikari.util.ConcurrentBag.borrow(ConcurrentBag.java:162)",
+            "at com.zaxxer.hikari.pool.HikariPool.getConnection(HikariPool.java:181)",
+            "at jdk.proxy2/jdk.proxy2.$Proxy84.getConnection(Unknown Source)",
+            "at com.mycorp.orders.OrderRepository.find(OrderRepository.java:40)",
+            "at jdk.internal.reflect.GeneratedMethodAccessor12.invoke(Unknown Source)",
+            "at org.springframework.web.servlet.DispatcherServlet.doDispatch(DispatcherServlet.java:1067)",
+            "at org.apache.catalina.core.ApplicationFilterChain.doFilter(ApplicationFilterChain.java:166)",
+            "at org.apache.tomcat.util.threads.TaskThread$WrappingRunnable.run(TaskThread.java:61)",
+        ]
+        .iter()
+        .map(|line| classifier.classify_frame(line))
+        .collect();
+
+        assert_eq!(frames[2].library.as_deref(), Some("HikariCP"));
+        assert_eq!(frames[4].library, None);
+        assert_eq!(library_context(&frames).as_deref(), Some("database (HikariCP) under Spring MVC"));
+        assert_eq!(library_context(&frames[7..]).as_deref(), Some("web server (Tomcat)"));
+        assert_eq!(library_context(&frames[..1]), None);
+    }
+}
@@ -0,0 +1,325 @@
+use crate::analyzer::categorize_thread;
+use crate::catalog::identify_library;
+use crate::models::{CategorizedFrame, FrameCategory, ThreadDump};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Rules applied when no configuration replaces them, tried in order. Frames
+/// of libraries in the [catalog](crate::catalog) are framework frames too.
+const BUILTIN_RULES: &[(&str, &str)] = &[
+    ("jvm", "java."),
+    ("jvm", "sun."),
+    ("jvm", "jdk."),
+    // Only the JDK's own `com.sun` packages: Jersey 1 (`com.sun.jersey`),
+    // JAXB (`com.sun.xml.bind`) and JavaMail (`com.sun.mail`) are libraries.
+    ("jvm", "com.sun.proxy."),
+    ("jvm", "com.sun.management."),
+    ("jvm", "com.sun.jmx."),
+    ("jvm", "com.sun.net.httpserver."),
+    ("jvm", "com.sun.net.ssl."),
+    ("jvm", "com.sun.nio."),
+    ("jvm", "com.sun.jndi."),
+    ("jvm", "com.sun.naming."),
+    ("jvm", "com.sun.crypto.provider."),
+    ("jvm", "com.sun.security."),
+    ("jvm", "com.sun.org.apache."),
+    ("jvm", "com.sun.beans."),
+    ("jvm", "com.sun.tools."),
+    ("jvm", "com.sun.jdi."),
+    ("jvm", "openj9."),
+    ("jvm", "com.ibm.oti."),
+    ("jvm", "com.ibm.lang."),
+    ("framework", "javax."),
+    ("framework", "jakarta."),
+];
+
+lazy_static! {
+    // Class loader and module prefixes: `app//`, `java.base@17.0.16/`, `app/java.sql@17/`
+    static ref LOADER_MODULE_PREFIX: Regex = Regex::new(r"^(?:[\w.$-]+/)?(?:[\w.$-]*@[^/(]*)?/").unwrap();
+}
+
+/// One entry of a rules file. Exactly one of `prefix` and `regex` must be set.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct FrameRuleConfig {
+    /// `jvm`, `framework` or `application`.
+    pub category: String,
+    /// Matched against the start of the qualified method, e.g. `org.springframework.`.
+    pub prefix: Option<String>,
+    /// Matched against the frame without its `at ` and class loader prefix,
+    /// e.g. `com.example.Foo.bar(Foo.java:12)`.
+    pub regex: Option<String>,
+}
+
+/// The contents of a `.thread-lens.toml` (or YAML) file.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct ClassifierConfig {
+    /// Whether the built-in rules are tried after `frame_rules`.
+    pub builtin_rules: bool,
+    /// Packages holding application code, e.g. `com.mycorp`. When set, frames
+    /// outside them that no rule matches are treated as library (framework) code.
+    pub app_packages: Vec<String>,
+    pub frame_rules: Vec<FrameRuleConfig>,
+}
+
+impl Default for ClassifierConfig {
+    fn default() -> Self {
+        ClassifierConfig {
+            builtin_rules: true,
+            app_packages: Vec::new(),
+            frame_rules: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum FrameMatcher {
+    Prefix(String),
+    Regex(Regex),
+}
+
+#[derive(Debug, Clone)]
+struct FrameRule {
+    matcher: FrameMatcher,
+    category: FrameCategory,
+}
+
+/// Assigns a [`FrameCategory`] to stack frames from an ordered list of rules.
+/// Frames in a configured application package are always application frames;
+/// otherwise the first matching rule wins. Frames no rule matches are
+/// framework frames when they belong to a catalogued library, and application
+/// frames otherwise, unless application packages are configured.
+#[derive(Debug, Clone)]
+pub struct Classifier {
+    rules: Vec<FrameRule>,
+    app_packages: Vec<String>,
+}
+
+impl Default for Classifier {
+    fn default() -> Self {
+        Classifier::from_config(&ClassifierConfig::default()).expect("built-in rules are valid")
+    }
+}
+
+fn parse_category(name: &str) -> Result<FrameCategory, String> {
+    match name.to_ascii_lowercase().as_str() {
+        "jvm" => Ok(FrameCategory::Jvm),
+        "framework" => Ok(FrameCategory::Framework),
+        "application" => Ok(FrameCategory::Application),
+        _ => Err(format!(
+            "unknown frame category '{}' (expected jvm, framework or application)",
+            name
+        )),
+    }
+}
+
+/// `at app//org.eclipse.jetty.Foo.run(Foo.java:1)` -> `org.eclipse.jetty.Foo.run(Foo.java:1)`
+pub fn qualified_frame(frame_line: &str) -> &str {
+    let frame = frame_line.trim_start();
+    let frame = frame.strip_prefix("at ").unwrap_or(frame);
+    match LOADER_MODULE_PREFIX.find(frame) {
+        Some(prefix) => &frame[prefix.end()..],
+        None => frame,
+    }
+}
+
+/// `com.mycorp`, `com.mycorp.` and `com.mycorp.*` all name the same package.
+fn normalize_package(package: &str) -> String {
+    package.trim().trim_end_matches('*').trim_end_matches('.').to_string()
+}
+
+/// Whether a frame's method is in `package` or one of its subpackages.
+pub fn in_package(frame_line: &str, package: &str) -> bool {
+    let package = normalize_package(package);
+    let frame = qualified_frame(frame_line);
+    !package.is_empty() && frame.starts_with(&package) && frame[package.len()..].starts_with('.')
+}
+
+impl Classifier {
+    pub fn from_config(config: &ClassifierConfig) -> Result<Classifier, String> {
+        let mut rules = Vec::new();
+        for (index, rule) in config.frame_rules.iter().enumerate() {
+            let category = parse_category(&rule.category).map_err(|e| format!("rule {}: {}", index + 1, e))?;
+            let matcher = match (&rule.prefix, &rule.regex) {
+                (Some(prefix), None) => FrameMatcher::Prefix(prefix.clone()),
+                (None, Some(pattern)) => FrameMatcher::Regex(
+                    Regex::new(pattern).map_err(|e| format!("rule {}: invalid regex: {}", index + 1, e))?,
+                ),
+                _ => return Err(format!("rule {}: set exactly one of 'prefix' and 'regex'", index + 1)),
+            };
+            rules.push(FrameRule { matcher, category });
+        }
+        if config.builtin_rules {
+            rules.extend(BUILTIN_RULES.iter().map(|(category, prefix)| FrameRule {
+                matcher: FrameMatcher::Prefix(prefix.to_string()),
+                category: parse_category(category).expect("built-in category"),
+            }));
+        }
+        Ok(Classifier {
+            rules,
+            app_packages: Vec::new(),
+        }
+        .with_app_packages(config.app_packages.iter().cloned()))
+    }
+
+    /// Adds application packages, e.g. from the command line.
+    pub fn with_app_packages<I: IntoIterator<Item = String>>(mut self, packages: I) -> Classifier {
+        for package in packages {
+            let package = normalize_package(&package);
+            if !package.is_empty() && !self.app_packages.contains(&package) {
+                self.app_packages.push(package);
+            }
+        }
+        self
+    }
+
+    pub fn app_packages(&self) -> &[String] {
+        &self.app_packages
+    }
+
+    pub fn from_toml(content: &str) -> Result<Classifier, String> {
+        let config: ClassifierConfig = toml::from_str(content).map_err(|e| e.to_string())?;
+        Classifier::from_config(&config)
+    }
+
+    pub fn from_yaml(content: &str) -> Result<Classifier, String> {
+        let config: ClassifierConfig = serde_yaml::from_str(content).map_err(|e| e.to_string())?;
+        Classifier::from_config(&config)
+    }
+
+    pub fn categorize_frame(&self, frame_line: &str) -> FrameCategory {
+        if self.app_packages.iter().any(|package| in_package(frame_line, package)) {
+            return FrameCategory::Application;
+        }
+        let frame = qualified_frame(frame_line);
+        let rule = self.rules.iter().find(|rule| match &rule.matcher {
+            FrameMatcher::Prefix(prefix) => frame.starts_with(prefix.as_str()),
+            FrameMatcher::Regex(regex) => regex.is_match(frame),
+        });
+        match rule {
+            Some(rule) => rule.category.clone(),
+            None if !self.app_packages.is_empty() || identify_library(frame).is_some() => FrameCategory::Framework,
+            None => FrameCategory::Application,
+        }
+    }
+
+    /// Categorizes a frame and tags it with its catalogued library, if any.
+    pub fn classify_frame(&self, frame_line: &str) -> CategorizedFrame {
+        let category = self.categorize_frame(frame_line);
+        let library = match category {
+            FrameCategory::Application => None,
+            _ => identify_library(qualified_frame(frame_line)),
+        };
+        CategorizedFrame {
+            line: frame_line.to_string(),
+            category,
+            library: library.map(|entry| entry.name.to_string()),
+            role: library.map(|entry| entry.role),
+        }
+    }
+
+    /// Classifies the frames of an already parsed dump again, e.g. after
+    /// application packages were added.
+    pub fn reclassify(&self, dump: &mut ThreadDump) {
+        for thread in &mut dump.threads {
+            for frame in &mut thread.frames {
+                *frame = self.classify_frame(&frame.line);
+            }
+            categorize_thread(thread);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_rules() {
+        let classifier = Classifier::default();
+        assert_eq!(
+            classifier.categorize_frame("at java.lang.Thread.run(java.base@17.0.2/Thread.java:833)"),
+            FrameCategory::Jvm
+        );
+        assert_eq!(
+            classifier.categorize_frame("at app//org.eclipse.jetty.util.thread.QueuedThreadPool$Runner.run(QueuedThreadPool.java:1034)"),
+            FrameCategory::Framework
+        );
+        assert_eq!(
+            classifier.categorize_frame("at com.zaxxer.hikari.pool.HikariPool.getConnection(HikariPool.java:181)"),
+            FrameCategory::Framework
+        );
+        assert_eq!(
+            classifier.categorize_frame("at com.example.BlockingApp.lambda$main$0(BlockingApp.java:22)"),
+            FrameCategory::Application
+        );
+        assert_eq!(
+            classifier.categorize_frame("at com.example.App$$Lambda$82/0x00000008400c9c40.run(Unknown Source)"),
+            FrameCategory::Application
+        );
+        assert_eq!(
+            classifier.categorize_frame("at java.base@17.0.16/java.lang.Object.wait(Object.java:251)"),
+            FrameCategory::Jvm
+        );
+        assert_eq!(
+            classifier.categorize_frame("at app/java.sql@17/java.sql.DriverManager.getConnection(DriverManager.java:1)"),
+            FrameCategory::Jvm
+        );
+        assert_eq!(
+            classifier.categorize_frame("at com.sun.proxy.$Proxy42.handle(Unknown Source)"),
+            FrameCategory::Jvm
+        );
+        assert_eq!(
+            classifier.categorize_frame("at java.management@17/com.sun.jmx.mbeanserver.JmxMBeanServer.invoke(JmxMBeanServer.java:809)"),
+            FrameCategory::Jvm
+        );
+        for library in [
+            "at com.sun.jersey.spi.container.servlet.ServletContainer.service(ServletContainer.java:538)",
+            "at com.sun.xml.bind.v2.runtime.MarshallerImpl.marshal(MarshallerImpl.java:232)",
+            "at com.sun.mail.smtp.SMTPTransport.sendMessage(SMTPTransport.java:1316)",
+        ] {
+            assert_eq!(classifier.categorize_frame(library), FrameCategory::Framework, "{}", library);
+        }
+    }
+
+    #[test]
+    fn test_configured_rules_take_precedence() {
+        let toml = r#"
+            [[frame_rules]]
+            category = "application"
+            prefix = "org.springframework.samples."
+
+            [[frame_rules]]
+            category = "Framework"
+            regex = '^com\.mycorp\.platform\.'
+        "#;
+        let classifier = Classifier::from_toml(toml).unwrap();
+        assert_eq!(
+            classifier.categorize_frame("at org.springframework.samples.petclinic.OwnerController.find(OwnerController.java:40)"),
+            FrameCategory::Application
+        );
+        assert_eq!(
+            classifier.categorize_frame("at org.springframework.web.servlet.DispatcherServlet.doDispatch(DispatcherServlet.java:1067)"),
+            FrameCategory::Framework
+        );
+        assert_eq!(
+            classifier.categorize_frame("at com.mycorp.platform.Bus.send(Bus.java:7)"),
+            FrameCategory::Framework
+        );
+
+        let yaml = "builtin_rules: false\nframe_rules:\n  - category: jvm\n    prefix: java.\n";
+        let classifier = Classifier::from_yaml(yaml).unwrap();
+        assert_eq!(classifier.categorize_frame("at sun.misc.Unsafe.park(Native Method)"), FrameCategory::Application);
+
+        let classifier = Classifier::from_toml("app_packages = [\"com.mycorp.*\"]").unwrap();
+        assert_eq!(classifier.app_packages(), ["com.mycorp"]);
+        assert_eq!(classifier.categorize_frame("at com.mycorp.Cache.get(Cache.java:3)"), FrameCategory::Application);
+        assert_eq!(classifier.categorize_frame("at com.mycorporate.Util.run(Util.java:3)"), FrameCategory::Framework);
+        assert_eq!(classifier.categorize_frame("at java.lang.Thread.run(Thread.java:833)"), FrameCategory::Jvm);
+
+        assert!(Classifier::from_toml("[[frame_rules]]\ncategory = \"library\"\nprefix = \"x.\"").is_err());
+        assert!(Classifier::from_toml("[[frame_rules]]\ncategory = \"jvm\"").is_err());
+    }
+}
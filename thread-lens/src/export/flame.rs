@@ -0,0 +1,370 @@
+use crate::analyzer::groups::{normalize_frame, SignatureOptions};
+use crate::analyzer::blockers::ThreadRef;
+use crate::analyzer::{in_time_order, thread_ids};
+use crate::classifier::qualified_frame;
+use crate::export::xml_escape;
+use crate::models::{FrameCategory, NormalizedThread, ThreadCategory, ThreadDump};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+/// What a stack is weighted by.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FlameWeight {
+    /// One per thread per dump the stack was seen in.
+    #[default]
+    Occurrences,
+    /// CPU milliseconds the thread used since the previous dump, charged to
+    /// its stack in the later dump. Needs `cpu=` in the dumps (OpenJDK 11+).
+    CpuDelta,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct FlameOptions {
+    pub weight: FlameWeight,
+    /// Put each thread's state below its stack, so every state gets a tower of its own.
+    pub split_by_state: bool,
+    /// Only threads of these categories; empty means all.
+    pub categories: Vec<ThreadCategory>,
+}
+
+/// One frame of a flame graph stack: a method without its source location.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FlameFrame {
+    pub name: String,
+    /// `None` for the state frame added by `split_by_state`.
+    pub category: Option<FrameCategory>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FlameStack {
+    /// Outermost frame first.
+    pub frames: Vec<FlameFrame>,
+    pub weight: u64,
+}
+
+/// Stacks aggregated across a series, in the order of their frames.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FlameGraph {
+    pub weight: FlameWeight,
+    pub stacks: Vec<FlameStack>,
+}
+
+/// `at java.base@17/java.lang.Thread.run(Thread.java:833)` -> `java.lang.Thread.run`,
+/// with generated lambda class ids removed so lambdas aggregate.
+fn frame_name(line: &str) -> String {
+    let normalized = normalize_frame(line, &SignatureOptions::default());
+    let method = qualified_frame(&normalized);
+    method.split('(').next().unwrap_or(method).to_string()
+}
+
+fn flame_frames(thread: &NormalizedThread, options: &FlameOptions) -> Vec<FlameFrame> {
+    let mut frames = Vec::with_capacity(thread.frames.len() + 1);
+    if options.split_by_state {
+        frames.push(FlameFrame {
+            name: thread.state.clone(),
+            category: None,
+        });
+    }
+    frames.extend(thread.frames.iter().rev().map(|frame| FlameFrame {
+        name: frame_name(&frame.line),
+        category: Some(frame.category.clone()),
+    }));
+    frames
+}
+
+/// Aggregates the stacks of every thread in every dump. Threads without
+/// frames, such as GC and compiler threads, are left out.
+pub fn build_flame_graph(dumps: &[ThreadDump], options: &FlameOptions) -> FlameGraph {
+    let mut weights: BTreeMap<Vec<FlameFrame>, u64> = BTreeMap::new();
+    // CPU time of each thread in the previous dump, by name and id (see
+    // `thread_ids`).
+    let mut previous_cpu: HashMap<ThreadRef, f64> = HashMap::new();
+
+    for dump in in_time_order(dumps) {
+        let mut current_cpu = HashMap::new();
+        for (thread, id) in dump.threads.iter().zip(thread_ids(dump)) {
+            let key = ThreadRef {
+                name: thread.name.clone(),
+                id,
+            };
+
+            let weight = match options.weight {
+                FlameWeight::Occurrences => 1,
+                FlameWeight::CpuDelta => match (thread.cpu_ms, previous_cpu.get(&key)) {
+                    (Some(now), Some(before)) if now > *before => (now - before).round() as u64,
+                    _ => 0,
+                },
+            };
+            if let Some(cpu) = thread.cpu_ms {
+                current_cpu.insert(key, cpu);
+            }
+
+            let selected = options.categories.is_empty() || options.categories.contains(&thread.category);
+            if weight == 0 || !selected || thread.frames.is_empty() {
+                continue;
+            }
+            *weights.entry(flame_frames(thread, options)).or_insert(0) += weight;
+        }
+        previous_cpu = current_cpu;
+    }
+
+    FlameGraph {
+        weight: options.weight,
+        stacks: weights.into_iter().map(|(frames, weight)| FlameStack { frames, weight }).collect(),
+    }
+}
+
+/// A node of the merged call tree used to lay out the SVG.
+struct Node<'a> {
+    frame: &'a FlameFrame,
+    weight: u64,
+    children: Vec<Node<'a>>,
+}
+
+fn insert<'a>(children: &mut Vec<Node<'a>>, frames: &'a [FlameFrame], weight: u64) {
+    let Some((first, rest)) = frames.split_first() else {
+        return;
+    };
+    let index = match children.iter().position(|node| node.frame == first) {
+        Some(index) => index,
+        None => {
+            children.push(Node {
+                frame: first,
+                weight: 0,
+                children: Vec::new(),
+            });
+            children.len() - 1
+        }
+    };
+    children[index].weight += weight;
+    insert(&mut children[index].children, rest, weight);
+}
+
+fn depth(nodes: &[Node]) -> usize {
+    nodes.iter().map(|node| 1 + depth(&node.children)).max().unwrap_or(0)
+}
+
+/// Warm colours for application code, yellow for frameworks, blue for the
+/// JVM and grey for state frames; the shade varies with the name so
+/// neighbouring frames can be told apart.
+fn colour(frame: &FlameFrame) -> String {
+    let shade = frame.name.bytes().fold(0u32, |hash, byte| hash.wrapping_mul(31).wrapping_add(byte as u32)) % 20;
+    match frame.category {
+        Some(FrameCategory::Application) => format!("hsl({}, 85%, 60%)", 5 + shade),
+        Some(FrameCategory::Framework) => format!("hsl({}, 80%, 58%)", 40 + shade),
+        Some(FrameCategory::Jvm) => format!("hsl({}, 55%, 68%)", 195 + shade),
+        None => format!("hsl(0, 0%, {}%)", 70 + shade / 2),
+    }
+}
+
+/// Scale and position shared by every frame of an SVG flame graph.
+struct Layout {
+    scale: f64,
+    bottom: f64,
+    total: u64,
+    unit: &'static str,
+}
+
+fn write_nodes(svg: &mut String, nodes: &[Node], mut x: f64, level: usize, layout: &Layout) {
+    for node in nodes {
+        let width = node.weight as f64 * layout.scale;
+        let y = layout.bottom - (level + 1) as f64 * FRAME_HEIGHT;
+        if width >= 0.5 {
+            let _ = write!(
+                svg,
+                "<g><title>{} ({} {}, {:.2}%)</title><rect x=\"{:.2}\" y=\"{:.1}\" width=\"{:.2}\" height=\"{}\" fill=\"{}\" rx=\"2\"/>",
+                xml_escape(&node.frame.name),
+                node.weight,
+                layout.unit,
+                node.weight as f64 * 100.0 / layout.total as f64,
+                x,
+                y,
+                width,
+                FRAME_HEIGHT - 1.0,
+                colour(node.frame)
+            );
+            let fits = ((width - 6.0) / CHAR_WIDTH).floor() as usize;
+            if fits >= 3 {
+                let label: String = if node.frame.name.chars().count() > fits {
+                    node.frame.name.chars().take(fits - 2).chain("..".chars()).collect()
+                } else {
+                    node.frame.name.clone()
+                };
+                let _ = write!(svg, "<text x=\"{:.2}\" y=\"{:.1}\">{}</text>", x + 3.0, y + 11.5, xml_escape(&label));
+            }
+            svg.push_str("</g>\n");
+            write_nodes(svg, &node.children, x, level + 1, layout);
+        }
+        x += width;
+    }
+}
+
+const SVG_WIDTH: f64 = 1200.0;
+const FRAME_HEIGHT: f64 = 16.0;
+const TITLE_HEIGHT: f64 = 40.0;
+/// Approximate width of a character at the label font size.
+const CHAR_WIDTH: f64 = 6.6;
+
+impl FlameGraph {
+    pub fn total_weight(&self) -> u64 {
+        self.stacks.iter().map(|stack| stack.weight).sum()
+    }
+
+    fn unit(&self) -> &'static str {
+        match self.weight {
+            FlameWeight::Occurrences => "samples",
+            FlameWeight::CpuDelta => "ms",
+        }
+    }
+
+    /// Brendan Gregg's collapsed-stack format: one `outer;...;inner weight` line per stack.
+    pub fn to_collapsed(&self) -> String {
+        let mut out = String::new();
+        for stack in &self.stacks {
+            let names: Vec<&str> = stack.frames.iter().map(|frame| frame.name.as_str()).collect();
+            let _ = writeln!(out, "{} {}", names.join(";"), stack.weight);
+        }
+        out
+    }
+
+    /// A speedscope file with one sampled profile, one sample per stack.
+    pub fn to_speedscope(&self, name: &str) -> Value {
+        let mut frames: Vec<&FlameFrame> = Vec::new();
+        let mut index: HashMap<&FlameFrame, usize> = HashMap::new();
+        let samples: Vec<Vec<usize>> = self
+            .stacks
+            .iter()
+            .map(|stack| {
+                stack
+                    .frames
+                    .iter()
+                    .map(|frame| {
+                        *index.entry(frame).or_insert_with(|| {
+                            frames.push(frame);
+                            frames.len() - 1
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+        let shared: Vec<Value> = frames.iter().map(|frame| json!({ "name": frame.name })).collect();
+        json!({
+            "$schema": "https://www.speedscope.app/file-format-schema.json",
+            "name": name,
+            "exporter": format!("thread-lens {}", env!("CARGO_PKG_VERSION")),
+            "activeProfileIndex": 0,
+            "shared": { "frames": shared },
+            "profiles": [{
+                "type": "sampled",
+                "name": name,
+                "unit": match self.weight {
+                    FlameWeight::Occurrences => "none",
+                    FlameWeight::CpuDelta => "milliseconds",
+                },
+                "startValue": 0,
+                "endValue": self.total_weight(),
+                "samples": samples,
+                "weights": self.stacks.iter().map(|stack| stack.weight).collect::<Vec<_>>(),
+            }],
+        })
+    }
+
+    /// A static SVG flame graph, outermost frames at the bottom, with a
+    /// tooltip on every frame.
+    pub fn to_svg(&self, title: &str) -> String {
+        let mut roots: Vec<Node> = Vec::new();
+        for stack in &self.stacks {
+            insert(&mut roots, &stack.frames, stack.weight);
+        }
+        let total = self.total_weight();
+        let rows = depth(&roots).max(1);
+        let height = TITLE_HEIGHT + rows as f64 * FRAME_HEIGHT + 10.0;
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"Verdana, sans-serif\" font-size=\"11\">",
+            w = SVG_WIDTH,
+            h = height
+        );
+        let _ = writeln!(svg, "<rect width=\"100%\" height=\"100%\" fill=\"#fafafa\"/>");
+        let _ = writeln!(
+            svg,
+            "<text x=\"{}\" y=\"20\" text-anchor=\"middle\" font-size=\"15\">{}</text>\n<text x=\"{}\" y=\"34\" text-anchor=\"middle\" fill=\"#666\">{} {} in total</text>",
+            SVG_WIDTH / 2.0,
+            xml_escape(title),
+            SVG_WIDTH / 2.0,
+            total,
+            self.unit()
+        );
+        if total > 0 {
+            let layout = Layout {
+                scale: (SVG_WIDTH - 20.0) / total as f64,
+                bottom: height - 10.0,
+                total,
+                unit: self.unit(),
+            };
+            write_nodes(&mut svg, &roots, 10.0, 0, &layout);
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_jstack_output;
+    use std::fs;
+
+    #[test]
+    fn test_flame_graph_weights_and_formats() {
+        let dir = "src/test_data/java-app-openjdk11_20250921085315_deadlock";
+        let dumps: Vec<ThreadDump> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| parse_jstack_output(&fs::read_to_string(entry.unwrap().path()).unwrap()).unwrap())
+            .collect();
+        let threads_with_frames: usize = dumps
+            .iter()
+            .map(|dump| dump.threads.iter().filter(|thread| !thread.frames.is_empty()).count())
+            .sum();
+
+        let graph = build_flame_graph(&dumps, &FlameOptions::default());
+        assert_eq!(graph.total_weight() as usize, threads_with_frames);
+        let collapsed = graph.to_collapsed();
+        assert!(collapsed
+            .lines()
+            .any(|line| line.starts_with("java.lang.Thread.run;com.example.threadanalyzer.ThreadAnalyzerApplication$$Lambda.run;")));
+
+        let by_state = build_flame_graph(
+            &dumps,
+            &FlameOptions {
+                split_by_state: true,
+                categories: vec![ThreadCategory::Application],
+                ..Default::default()
+            },
+        );
+        assert!(by_state.stacks.iter().all(|stack| stack.frames[0].category.is_none()));
+        assert!(by_state.stacks.iter().any(|stack| stack.frames[0].name == "BLOCKED"));
+
+        let cpu = build_flame_graph(
+            &dumps,
+            &FlameOptions {
+                weight: FlameWeight::CpuDelta,
+                ..Default::default()
+            },
+        );
+        assert!(dumps[0].threads.iter().any(|thread| thread.cpu_ms.is_some()));
+        assert!(cpu.total_weight() > 0);
+
+        let speedscope = graph.to_speedscope("series");
+        let profile = &speedscope["profiles"][0];
+        assert_eq!(profile["samples"].as_array().unwrap().len(), graph.stacks.len());
+        assert_eq!(profile["endValue"], graph.total_weight());
+        assert!(graph.to_svg("series").contains("<title>java.lang.Thread.run"));
+    }
+}
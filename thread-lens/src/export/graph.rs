@@ -0,0 +1,290 @@
+use crate::analyzer::blockers::ThreadRef;
+use crate::analyzer::{in_time_order, thread_ids};
+use crate::models::{LockAction, ThreadCategory, ThreadDump};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+
+/// How a thread is related to a lock.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum LockEdgeKind {
+    /// The thread owns the lock.
+    Holds,
+    /// Waiting to enter or re-enter a monitor, or in `Object.wait()` on it.
+    Waits,
+    /// Parked on a `java.util.concurrent` lock or condition.
+    Parks,
+}
+
+impl LockEdgeKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LockEdgeKind::Holds => "holds",
+            LockEdgeKind::Waits => "waits",
+            LockEdgeKind::Parks => "parks",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GraphThread {
+    pub name: String,
+    pub category: ThreadCategory,
+    /// The state in the latest dump the thread was seen in.
+    pub state: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GraphLock {
+    pub address: String,
+    pub class_name: String,
+}
+
+/// An edge between `threads[thread]` and `locks[lock]`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LockEdge {
+    pub thread: usize,
+    pub lock: usize,
+    pub kind: LockEdgeKind,
+    /// Number of dumps the edge was seen in.
+    pub dumps: usize,
+}
+
+/// Threads and the contended locks they hold, wait for or park on, merged
+/// over a series. Locks nobody waits or parks on are left out.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LockGraph {
+    pub dumps: usize,
+    pub threads: Vec<GraphThread>,
+    pub locks: Vec<GraphLock>,
+    pub edges: Vec<LockEdge>,
+}
+
+fn edge_kind(action: &LockAction) -> LockEdgeKind {
+    match action {
+        LockAction::Locked => LockEdgeKind::Holds,
+        LockAction::ParkingToWaitFor => LockEdgeKind::Parks,
+        LockAction::WaitingToLock | LockAction::WaitingOn | LockAction::WaitingToReLock => LockEdgeKind::Waits,
+    }
+}
+
+/// Builds the thread-lock graph of one dump, or the merged graph of a series.
+/// Threads are told apart by name and id (see [`thread_ids`]); locks by address.
+pub fn build_lock_graph(dumps: &[ThreadDump]) -> LockGraph {
+    let mut threads: BTreeMap<ThreadRef, GraphThread> = BTreeMap::new();
+    let mut locks: BTreeMap<String, GraphLock> = BTreeMap::new();
+    let mut edges: BTreeMap<(ThreadRef, String, LockEdgeKind), usize> = BTreeMap::new();
+    let ordered = in_time_order(dumps);
+
+    for dump in &ordered {
+        let contended: HashSet<&str> = dump
+            .threads
+            .iter()
+            .flat_map(|thread| &thread.locks)
+            .filter(|lock| lock.action != LockAction::Locked)
+            .map(|lock| lock.address.as_str())
+            .collect();
+        for (thread, id) in dump.threads.iter().zip(thread_ids(dump)) {
+            // A thread inside Object.wait() still reports the monitor as
+            // "locked" on the synchronized frame, but it has released it.
+            let released: HashSet<&str> = thread
+                .locks
+                .iter()
+                .filter(|lock| lock.action == LockAction::WaitingOn)
+                .map(|lock| lock.address.as_str())
+                .collect();
+            let related: Vec<_> = thread
+                .locks
+                .iter()
+                .filter(|lock| contended.contains(lock.address.as_str()))
+                .filter(|lock| lock.action != LockAction::Locked || !released.contains(lock.address.as_str()))
+                .collect();
+            if related.is_empty() {
+                continue;
+            }
+
+            let key = ThreadRef {
+                name: thread.name.clone(),
+                id,
+            };
+            threads.insert(
+                key.clone(),
+                GraphThread {
+                    name: thread.name.clone(),
+                    category: thread.category.clone(),
+                    state: thread.state.clone(),
+                },
+            );
+            let mut seen = HashSet::new();
+            for lock in related {
+                locks.entry(lock.address.clone()).or_insert_with(|| GraphLock {
+                    address: lock.address.clone(),
+                    class_name: lock.class_name.clone(),
+                });
+                let edge = (key.clone(), lock.address.clone(), edge_kind(&lock.action));
+                // A lock taken twice up the stack is still one edge per dump.
+                if seen.insert(edge.clone()) {
+                    *edges.entry(edge).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    let thread_index: HashMap<&ThreadRef, usize> = threads.keys().enumerate().map(|(index, key)| (key, index)).collect();
+    let lock_index: HashMap<&String, usize> = locks.keys().enumerate().map(|(index, key)| (key, index)).collect();
+    LockGraph {
+        dumps: ordered.len(),
+        edges: edges
+            .iter()
+            .map(|((thread, lock, kind), dumps)| LockEdge {
+                thread: thread_index[thread],
+                lock: lock_index[lock],
+                kind: *kind,
+                dumps: *dumps,
+            })
+            .collect(),
+        threads: threads.into_values().collect(),
+        locks: locks.into_values().collect(),
+    }
+}
+
+fn category_colour(category: &ThreadCategory) -> &'static str {
+    match category {
+        ThreadCategory::Application => "#f4a261",
+        ThreadCategory::Framework => "#e9c46a",
+        ThreadCategory::Jvm => "#8ecae6",
+        ThreadCategory::Unknown => "#d3d3d3",
+    }
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// `&`, `"`, `<` and `>` as Mermaid entity codes, which are safe inside quoted labels.
+fn mermaid_escape(text: &str) -> String {
+    text.replace('&', "#amp;").replace('"', "#quot;").replace('<', "#lt;").replace('>', "#gt;")
+}
+
+impl LockGraph {
+    /// The edge label, with the number of dumps when the graph covers a series.
+    fn edge_label(&self, edge: &LockEdge) -> String {
+        if self.dumps > 1 {
+            format!("{} ({}/{})", edge.kind.label(), edge.dumps, self.dumps)
+        } else {
+            edge.kind.label().to_string()
+        }
+    }
+
+    /// Graphviz DOT. Threads are boxes filled by category and locks are
+    /// ellipses; waits point from the thread to the lock, holds from the lock
+    /// to its owner, so a deadlock shows up as a directed cycle.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph wait_for {\n    rankdir=LR;\n    node [fontname=\"Helvetica\", fontsize=10];\n    edge [fontname=\"Helvetica\", fontsize=9];\n");
+        for (index, thread) in self.threads.iter().enumerate() {
+            let _ = writeln!(
+                dot,
+                "    t{} [label=\"{}\\n{} ({:?})\", shape=box, style=\"filled,rounded\", fillcolor=\"{}\"];",
+                index,
+                dot_escape(&thread.name),
+                dot_escape(&thread.state),
+                thread.category,
+                category_colour(&thread.category)
+            );
+        }
+        for (index, lock) in self.locks.iter().enumerate() {
+            let _ = writeln!(
+                dot,
+                "    l{} [label=\"{}\\n<{}>\", shape=ellipse];",
+                index,
+                dot_escape(&lock.class_name),
+                dot_escape(&lock.address)
+            );
+        }
+        for edge in &self.edges {
+            let label = self.edge_label(edge);
+            let _ = match edge.kind {
+                LockEdgeKind::Holds => writeln!(dot, "    l{} -> t{} [label=\"{}\", penwidth=2];", edge.lock, edge.thread, label),
+                LockEdgeKind::Waits => writeln!(dot, "    t{} -> l{} [label=\"{}\", color=\"#c0392b\"];", edge.thread, edge.lock, label),
+                LockEdgeKind::Parks => {
+                    writeln!(dot, "    t{} -> l{} [label=\"{}\", color=\"#2471a3\", style=dashed];", edge.thread, edge.lock, label)
+                }
+            };
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// A Mermaid flowchart with the same layout as [`LockGraph::to_dot`]:
+    /// waits are solid arrows, parks dotted and holds thick.
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = String::from("flowchart LR\n");
+        for (index, thread) in self.threads.iter().enumerate() {
+            let _ = writeln!(
+                mermaid,
+                "    t{}[\"{}<br/>{} ({:?})\"]",
+                index,
+                mermaid_escape(&thread.name),
+                mermaid_escape(&thread.state),
+                thread.category
+            );
+        }
+        for (index, lock) in self.locks.iter().enumerate() {
+            let _ = writeln!(
+                mermaid,
+                "    l{}((\"{}<br/>{}\"))",
+                index,
+                mermaid_escape(&lock.class_name),
+                mermaid_escape(&lock.address)
+            );
+        }
+        for edge in &self.edges {
+            let label = mermaid_escape(&self.edge_label(edge));
+            let _ = match edge.kind {
+                LockEdgeKind::Holds => writeln!(mermaid, "    l{} ==>|\"{}\"| t{}", edge.lock, label, edge.thread),
+                LockEdgeKind::Waits => writeln!(mermaid, "    t{} -->|\"{}\"| l{}", edge.thread, label, edge.lock),
+                LockEdgeKind::Parks => writeln!(mermaid, "    t{} -.->|\"{}\"| l{}", edge.thread, label, edge.lock),
+            };
+        }
+        let mut by_category: BTreeMap<String, (&str, Vec<String>)> = BTreeMap::new();
+        for (index, thread) in self.threads.iter().enumerate() {
+            let class = format!("{:?}", thread.category).to_lowercase();
+            let (_, nodes) = by_category.entry(class).or_insert((category_colour(&thread.category), Vec::new()));
+            nodes.push(format!("t{}", index));
+        }
+        for (class, (colour, nodes)) in &by_category {
+            let _ = writeln!(mermaid, "    classDef {} fill:{},stroke:#333", class, colour);
+            let _ = writeln!(mermaid, "    class {} {}", nodes.join(","), class);
+        }
+        mermaid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_jstack_output;
+    use std::fs;
+
+    #[test]
+    fn test_lock_graph_of_deadlock() {
+        let content = fs::read_to_string("src/test_data/java-app-openjdk11_20250921085315_deadlock/java-app-openjdk11_20250921155448034.jstack").unwrap();
+        let dump = parse_jstack_output(&content).unwrap();
+        let graph = build_lock_graph(std::slice::from_ref(&dump));
+        assert_eq!(graph.dumps, 1);
+
+        let lock = graph.locks.iter().position(|lock| lock.address == "0x00000000e2ed5c90").unwrap();
+        let edges: Vec<&LockEdge> = graph.edges.iter().filter(|edge| edge.lock == lock).collect();
+        assert!(edges.iter().any(|edge| edge.kind == LockEdgeKind::Holds));
+        assert!(edges.iter().any(|edge| edge.kind == LockEdgeKind::Waits));
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph wait_for {"));
+        assert!(dot.contains("<0x00000000e2ed5c90>"));
+        let mermaid = graph.to_mermaid();
+        assert!(mermaid.starts_with("flowchart LR"));
+        assert!(mermaid.contains("classDef application"));
+        assert!(!mermaid.replace("<br/>", "").contains('<'));
+    }
+}
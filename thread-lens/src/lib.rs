@@ -1,9 +1,13 @@
 use wasm_bindgen::prelude::*;
-use console_error_panic_hook;
 
 pub mod analyzer;
+pub mod antipatterns;
+pub mod catalog;
+pub mod classifier;
+pub mod export;
 pub mod models;
 pub mod parser;
+pub mod query;
 
 #[wasm_bindgen(start)]
 pub fn main_wasm() -> Result<(), JsValue> {
@@ -12,31 +16,300 @@ pub fn main_wasm() -> Result<(), JsValue> {
     Ok(())
 }
 
+/// `rules_toml` optionally holds frame classification rules in the
+/// `.thread-lens.toml` format; `app_packages` lists application packages.
 #[wasm_bindgen]
-pub fn parse_jstack_output_wasm(input: &str) -> Result<JsValue, JsValue> {
-    let dump = parser::parse_jstack_output(input).map_err(|e| JsValue::from_str(&e))?;
+pub fn parse_jstack_output_wasm(
+    input: &str,
+    rules_toml: Option<String>,
+    app_packages: Option<Vec<String>>,
+) -> Result<JsValue, JsValue> {
+    let classifier = match rules_toml {
+        Some(rules) => classifier::Classifier::from_toml(&rules).map_err(|e| JsValue::from_str(&e))?,
+        None => classifier::Classifier::default(),
+    }
+    .with_app_packages(app_packages.unwrap_or_default());
+    let dump = parser::parse_jstack_output_with(input, &classifier).map_err(|e| JsValue::from_str(&e))?;
     serde_json::to_string(&dump)
         .map_err(|e| JsValue::from_str(&e.to_string()))
         .map(|s| JsValue::from_str(&s))
 }
 
+/// `options_json` is an optional `ChronicBlockingOptions` object; missing
+/// fields take their defaults.
 #[wasm_bindgen]
-pub fn find_chronically_blocked_threads_wasm(dumps_json: &str) -> Result<JsValue, JsValue> {
+pub fn find_chronically_blocked_threads_wasm(dumps_json: &str, options_json: Option<String>) -> Result<JsValue, JsValue> {
     let dumps: Vec<models::ThreadDump> = serde_json::from_str(dumps_json)
         .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let options: analyzer::chronic::ChronicBlockingOptions = match options_json {
+        Some(json) => serde_json::from_str(&json).map_err(|e| JsValue::from_str(&e.to_string()))?,
+        None => Default::default(),
+    };
 
-    let result = analyzer::find_chronically_blocked_threads(&dumps);
+    let result = analyzer::find_chronically_blocked_threads(&dumps, &options)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+        .map(|s| JsValue::from_str(&s))
+}
+
+#[wasm_bindgen]
+pub fn summarize_root_blockers_wasm(dumps_json: &str) -> Result<JsValue, JsValue> {
+    let dumps: Vec<models::ThreadDump> = serde_json::from_str(dumps_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    let result = analyzer::blockers::summarize_root_blockers_across(&dumps);
+
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+        .map(|s| JsValue::from_str(&s))
+}
+
+#[wasm_bindgen]
+pub fn group_by_stack_wasm(dump_json: &str, ignore_line_numbers: bool) -> Result<JsValue, JsValue> {
+    let dump: models::ThreadDump = serde_json::from_str(dump_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    let options = analyzer::groups::SignatureOptions {
+        ignore_line_numbers,
+        ..Default::default()
+    };
+    let result = analyzer::groups::group_by_stack(&dump, &options);
+
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+        .map(|s| JsValue::from_str(&s))
+}
+
+#[wasm_bindgen]
+pub fn pool_stats_wasm(dump_json: &str) -> Result<JsValue, JsValue> {
+    let dump: models::ThreadDump = serde_json::from_str(dump_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    let result = analyzer::pools::pool_stats(&dump);
+
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+        .map(|s| JsValue::from_str(&s))
+}
+
+#[wasm_bindgen]
+pub fn detect_pool_saturation_wasm(dumps_json: &str) -> Result<JsValue, JsValue> {
+    let dumps: Vec<models::ThreadDump> = serde_json::from_str(dumps_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    let result = analyzer::pools::detect_pool_saturation(&dumps, &Default::default());
+
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+        .map(|s| JsValue::from_str(&s))
+}
+
+#[wasm_bindgen]
+pub fn detect_thread_leaks_wasm(dumps_json: &str) -> Result<JsValue, JsValue> {
+    let dumps: Vec<models::ThreadDump> = serde_json::from_str(dumps_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
 
-    // Convert HashMap<String, (NormalizedThread, usize)> to a serializable format
-    let serializable_result: Vec<_> = result.into_iter().map(|(name, (thread, count))| {
-        serde_json::json!({
-            "name": name,
-            "thread": thread,
-            "count": count
-        })
-    }).collect();
+    let result = analyzer::leaks::detect_thread_leaks(&dumps, &Default::default());
 
-    serde_json::to_string(&serializable_result)
+    serde_json::to_string(&result)
         .map_err(|e| JsValue::from_str(&e.to_string()))
         .map(|s| JsValue::from_str(&s))
 }
+
+#[wasm_bindgen]
+pub fn diff_dumps_wasm(before_json: &str, after_json: &str) -> Result<JsValue, JsValue> {
+    let before: models::ThreadDump = serde_json::from_str(before_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let after: models::ThreadDump = serde_json::from_str(after_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    let result = analyzer::diff::diff_dumps(&before, &after);
+
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+        .map(|s| JsValue::from_str(&s))
+}
+
+#[wasm_bindgen]
+pub fn build_timeline_wasm(dumps_json: &str) -> Result<JsValue, JsValue> {
+    let dumps: Vec<models::ThreadDump> = serde_json::from_str(dumps_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    let result = analyzer::timeline::build_timeline(&dumps);
+
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+        .map(|s| JsValue::from_str(&s))
+}
+
+#[wasm_bindgen]
+pub fn score_app_packages_wasm(dumps_json: &str) -> Result<JsValue, JsValue> {
+    let dumps: Vec<models::ThreadDump> = serde_json::from_str(dumps_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    let result = analyzer::packages::score_app_packages(&dumps);
+
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+        .map(|s| JsValue::from_str(&s))
+}
+
+/// `rules_toml` optionally holds extra anti-pattern rules, added to the
+/// starter rules.
+#[wasm_bindgen]
+pub fn find_antipatterns_wasm(dumps_json: &str, rules_toml: Option<String>) -> Result<JsValue, JsValue> {
+    let dumps: Vec<models::ThreadDump> = serde_json::from_str(dumps_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let mut engine = antipatterns::RuleEngine::default();
+    if let Some(rules) = rules_toml {
+        engine = engine.with_rules(antipatterns::RuleEngine::from_toml(&rules).map_err(|e| JsValue::from_str(&e))?);
+    }
+
+    let result = engine.evaluate(&dumps);
+
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+        .map(|s| JsValue::from_str(&s))
+}
+
+/// `rules_toml` optionally holds extra anti-pattern rules, added to the
+/// starter rules.
+#[wasm_bindgen]
+pub fn summarize_wasm(dumps_json: &str, rules_toml: Option<String>) -> Result<JsValue, JsValue> {
+    let dumps: Vec<models::ThreadDump> = serde_json::from_str(dumps_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let mut engine = antipatterns::RuleEngine::default();
+    if let Some(rules) = rules_toml {
+        engine = engine.with_rules(antipatterns::RuleEngine::from_toml(&rules).map_err(|e| JsValue::from_str(&e))?);
+    }
+
+    let result = analyzer::summary::summarize(&dumps, &engine);
+
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+        .map(|s| JsValue::from_str(&s))
+}
+
+#[wasm_bindgen]
+pub fn build_baseline_wasm(dumps_json: &str) -> Result<JsValue, JsValue> {
+    let dumps: Vec<models::ThreadDump> = serde_json::from_str(dumps_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    let result = analyzer::baseline::build_baseline(&dumps);
+
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+        .map(|s| JsValue::from_str(&s))
+}
+
+/// `options_json` is an optional `BaselineOptions` object; missing fields take
+/// their defaults.
+#[wasm_bindgen]
+pub fn compare_to_baseline_wasm(baseline_json: &str, dumps_json: &str, options_json: Option<String>) -> Result<JsValue, JsValue> {
+    let baseline = analyzer::baseline::Baseline::from_json(baseline_json).map_err(|e| JsValue::from_str(&e))?;
+    let dumps: Vec<models::ThreadDump> = serde_json::from_str(dumps_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let options: analyzer::baseline::BaselineOptions = match options_json {
+        Some(json) => serde_json::from_str(&json).map_err(|e| JsValue::from_str(&e.to_string()))?,
+        None => Default::default(),
+    };
+
+    let result = analyzer::baseline::compare_to_baseline(&baseline, &dumps, &options);
+
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+        .map(|s| JsValue::from_str(&s))
+}
+
+/// `filter_json` is a `ThreadFilter` object; missing fields match everything.
+#[wasm_bindgen]
+pub fn filter_threads_wasm(dump_json: &str, filter_json: &str) -> Result<JsValue, JsValue> {
+    let dump: models::ThreadDump = serde_json::from_str(dump_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let filter: analyzer::filter::ThreadFilter = serde_json::from_str(filter_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    let result = filter.apply(&dump).map_err(|e| JsValue::from_str(&e))?;
+
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+        .map(|s| JsValue::from_str(&s))
+}
+
+/// `query` uses the thread query language, e.g. `state = BLOCKED count by pool`.
+#[wasm_bindgen]
+pub fn query_threads_wasm(dumps_json: &str, query: &str) -> Result<JsValue, JsValue> {
+    let dumps: Vec<models::ThreadDump> = serde_json::from_str(dumps_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let query = query::Query::parse(query).map_err(|e| JsValue::from_str(&e))?;
+
+    let result = query.run(&dumps);
+
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+        .map(|s| JsValue::from_str(&s))
+}
+
+/// `options_json` is an optional `AnalysisOptions` object; `rules_toml`
+/// optionally holds extra anti-pattern rules, added to the starter rules.
+#[wasm_bindgen]
+pub fn analyze_wasm(dumps_json: &str, options_json: Option<String>, rules_toml: Option<String>) -> Result<JsValue, JsValue> {
+    let dumps: Vec<models::ThreadDump> = serde_json::from_str(dumps_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let options: analyzer::report::AnalysisOptions = match options_json {
+        Some(json) => serde_json::from_str(&json).map_err(|e| JsValue::from_str(&e.to_string()))?,
+        None => Default::default(),
+    };
+    let mut engine = antipatterns::RuleEngine::default();
+    if let Some(rules) = rules_toml {
+        engine = engine.with_rules(antipatterns::RuleEngine::from_toml(&rules).map_err(|e| JsValue::from_str(&e))?);
+    }
+
+    let result = analyzer::report::analyze(&dumps, &engine, &options).map_err(|e| JsValue::from_str(&e))?;
+
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+        .map(|s| JsValue::from_str(&s))
+}
+
+/// `options_json` is an optional `FlameOptions` object; `format` is
+/// `collapsed`, `speedscope` or `svg`. Returns the exported file as a string.
+#[wasm_bindgen]
+pub fn flame_graph_wasm(dumps_json: &str, options_json: Option<String>, format: &str) -> Result<JsValue, JsValue> {
+    let dumps: Vec<models::ThreadDump> = serde_json::from_str(dumps_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let options: export::flame::FlameOptions = match options_json {
+        Some(json) => serde_json::from_str(&json).map_err(|e| JsValue::from_str(&e.to_string()))?,
+        None => Default::default(),
+    };
+
+    let graph = export::flame::build_flame_graph(&dumps, &options);
+
+    match format {
+        "collapsed" => Ok(JsValue::from_str(&graph.to_collapsed())),
+        "speedscope" => Ok(JsValue::from_str(&graph.to_speedscope("thread dumps").to_string())),
+        "svg" => Ok(JsValue::from_str(&graph.to_svg("Thread stacks"))),
+        other => Err(JsValue::from_str(&format!("unknown flame graph format '{}'", other))),
+    }
+}
+
+/// The thread-lock graph of the dumps, merged over the series, as `dot`,
+/// `mermaid` or `json`.
+#[wasm_bindgen]
+pub fn lock_graph_wasm(dumps_json: &str, format: &str) -> Result<JsValue, JsValue> {
+    let dumps: Vec<models::ThreadDump> = serde_json::from_str(dumps_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    let graph = export::graph::build_lock_graph(&dumps);
+
+    match format {
+        "dot" => Ok(JsValue::from_str(&graph.to_dot())),
+        "mermaid" => Ok(JsValue::from_str(&graph.to_mermaid())),
+        "json" => serde_json::to_string(&graph)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+            .map(|s| JsValue::from_str(&s)),
+        other => Err(JsValue::from_str(&format!("unknown graph format '{}'", other))),
+    }
+}
@@ -1,33 +1,112 @@
 use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Utc};
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum FrameCategory {
     Jvm,
     Framework,
     Application,
 }
 
+/// What a well-known library does, as recorded in the built-in catalog.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LibraryRole {
+    WebServer,
+    WebFramework,
+    AppFramework,
+    Database,
+    Messaging,
+    Rpc,
+    Logging,
+    Serialization,
+    Reflection,
+    Testing,
+}
+
+impl LibraryRole {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LibraryRole::WebServer => "web server",
+            LibraryRole::WebFramework => "web framework",
+            LibraryRole::AppFramework => "application framework",
+            LibraryRole::Database => "database",
+            LibraryRole::Messaging => "messaging",
+            LibraryRole::Rpc => "RPC/HTTP client",
+            LibraryRole::Logging => "logging",
+            LibraryRole::Serialization => "serialization",
+            LibraryRole::Reflection => "reflection/proxy",
+            LibraryRole::Testing => "test framework",
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CategorizedFrame {
     pub line: String,
     pub category: FrameCategory,
+    /// Library from the built-in catalog, e.g. `HikariCP`; never set on application frames.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub library: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<LibraryRole>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
 pub enum ThreadCategory {
     Jvm,
     Framework,
     Application,
+    #[default]
     Unknown,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// A thread category with the confidence in it and the evidence it rests on.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CategoryDecision {
+    pub category: ThreadCategory,
+    pub confidence: f64,
+    pub reason: String,
+}
+
+/// How a thread relates to a monitor or `java.util.concurrent` lock, as reported
+/// by the `- locked <...>` style annotations under a stack frame.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum LockAction {
+    Locked,
+    WaitingToLock,
+    WaitingOn,
+    ParkingToWaitFor,
+    WaitingToReLock,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LockInfo {
+    pub action: LockAction,
+    pub address: String,
+    pub class_name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct NormalizedThread {
     pub name: String,
+    /// JVM thread identifier (`tid` on HotSpot, `Id` on OpenJ9), used to tell
+    /// apart threads that share a name.
+    #[serde(default)]
+    pub id: Option<String>,
     pub state: String, // Consider making this an enum later
+    /// CPU time the thread has used so far, in milliseconds (`cpu=` on OpenJDK 11+).
+    #[serde(default)]
+    pub cpu_ms: Option<f64>,
     pub category: ThreadCategory,
+    /// Share (0.0-1.0) of the weighted evidence that supports `category`.
+    #[serde(default)]
+    pub category_confidence: f64,
+    /// The evidence behind `category`, e.g. `entry point is framework code`.
+    #[serde(default)]
+    pub category_reason: String,
     pub frames: Vec<CategorizedFrame>,
+    #[serde(default)]
+    pub locks: Vec<LockInfo>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -35,4 +114,4 @@ pub struct ThreadDump {
     pub jvm_version: String,
     pub timestamp: DateTime<Utc>,
     pub threads: Vec<NormalizedThread>,
-}
\ No newline at end of file
+}
@@ -1,14 +1,21 @@
-use crate::models::{ThreadDump, NormalizedThread, CategorizedFrame, ThreadCategory};
-use crate::analyzer::{categorize_frame, determine_thread_category};
+use crate::models::{ThreadDump, NormalizedThread, ThreadCategory, LockAction, LockInfo};
+use crate::analyzer::categorize_thread;
+use crate::classifier::Classifier;
+use super::openjdk::parse_lock_line;
+use super::parse_dump_timestamp;
 use chrono::Utc;
 use lazy_static::lazy_static;
 use regex::Regex;
 
 lazy_static! {
     static ref IBM_JVM_VERSION_REGEX: Regex = Regex::new(r"Full thread dump IBM Semeru Runtime Open Edition (\d+\.\d+\.\d+\.\d+)").unwrap();
+    static ref IBM_THREAD_ID_REGEX: Regex = Regex::new(r"\btid=(0x[0-9a-fA-F]+)|\bId=(\d+)").unwrap();
+    // e.g. `"BlockedThread" prio=5 Id=41 BLOCKED on java.lang.Object@3b4b3daf owned by "BlockerThread" Id=40`
+    static ref IBM_HEADER_LOCK_REGEX: Regex = Regex::new(r"\b(BLOCKED|WAITING|TIMED_WAITING) on (\S+)@([0-9a-fA-F]+)").unwrap();
+    static ref IBM_LOCK_REGEX: Regex = Regex::new(r"^- (locked|waiting to lock|waiting on|parking to wait for) (\S+)@([0-9a-fA-F]+)$").unwrap();
 }
 
-pub fn parse_jstack_output_ibm(output: &str) -> Result<ThreadDump, String> {
+pub fn parse_jstack_output_ibm(output: &str, classifier: &Classifier) -> Result<ThreadDump, String> {
     let mut threads = Vec::new();
     let mut current_thread: Option<NormalizedThread> = None;
     let mut jvm_version = "IBM J9 (Unknown Version)".to_string(); // Default placeholder
@@ -21,10 +28,10 @@ pub fn parse_jstack_output_ibm(output: &str) -> Result<ThreadDump, String> {
             }
         }
 
-        if line.contains("prio=") && line.contains("tid=") {
+        if line.contains("prio=") && (line.contains("tid=") || line.contains(" Id=")) {
             // Finalize the previous thread before starting a new one
             if let Some(mut thread) = current_thread.take() {
-                thread.category = determine_thread_category(&thread.frames);
+                categorize_thread(&mut thread);
                 threads.push(thread);
             }
 
@@ -41,34 +48,72 @@ pub fn parse_jstack_output_ibm(output: &str) -> Result<ThreadDump, String> {
                 "UNKNOWN".to_string()
             };
 
+            let id = IBM_THREAD_ID_REGEX
+                .captures(line)
+                .and_then(|c| c.get(1).or_else(|| c.get(2)))
+                .map(|m| m.as_str().to_string());
+            let mut locks = Vec::new();
+            if let Some(captures) = IBM_HEADER_LOCK_REGEX.captures(line) {
+                let action = if &captures[1] == "BLOCKED" {
+                    LockAction::WaitingToLock
+                } else {
+                    LockAction::WaitingOn
+                };
+                locks.push(LockInfo {
+                    action,
+                    address: captures[3].to_string(),
+                    class_name: captures[2].to_string(),
+                });
+            }
+
             current_thread = Some(NormalizedThread {
                 name,
+                id,
                 state,
+                cpu_ms: None,
                 category: ThreadCategory::Unknown, // Will be determined after parsing frames
+                category_confidence: 0.0,
+                category_reason: String::new(),
                 frames: Vec::new(),
+                locks,
             });
 
         } else if let Some(ref mut thread) = current_thread {
             if line.trim().starts_with("at ") {
-                let frame_line = line.trim().to_string();
-                let category = categorize_frame(&frame_line);
-                thread.frames.push(CategorizedFrame {
-                    line: frame_line,
-                    category,
-                });
+                thread.frames.push(classifier.classify_frame(line.trim()));
+            } else if let Some(lock) = parse_ibm_lock_line(line.trim()) {
+                thread.locks.push(lock);
             }
         }
     }
 
     // Finalize the last thread in the file
     if let Some(mut thread) = current_thread.take() {
-        thread.category = determine_thread_category(&thread.frames);
+        categorize_thread(&mut thread);
         threads.push(thread);
     }
 
     Ok(ThreadDump {
         jvm_version, // Use the extracted JVM version
-        timestamp: Utc::now(), // Placeholder
+        timestamp: parse_dump_timestamp(output).unwrap_or_else(Utc::now),
         threads,
     })
 }
+
+fn parse_ibm_lock_line(line: &str) -> Option<LockInfo> {
+    let Some(captures) = IBM_LOCK_REGEX.captures(line) else {
+        // Some OpenJ9 builds emit HotSpot-style `<0x...> (a ...)` annotations
+        return parse_lock_line(line);
+    };
+    let action = match &captures[1] {
+        "locked" => LockAction::Locked,
+        "waiting to lock" => LockAction::WaitingToLock,
+        "waiting on" => LockAction::WaitingOn,
+        _ => LockAction::ParkingToWaitFor,
+    };
+    Some(LockInfo {
+        action,
+        address: captures[3].to_string(),
+        class_name: captures[2].to_string(),
+    })
+}
@@ -1,14 +1,22 @@
+use crate::classifier::Classifier;
 use crate::models::ThreadDump;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use lazy_static::lazy_static;
 
 pub mod jvm_vendor;
 pub mod openjdk;
 pub mod ibm;
 pub mod tests;
+pub mod test_ibm_frames;
 
 pub use jvm_vendor::JvmVendor;
 use openjdk::parse_jstack_output_openjdk;
 use ibm::parse_jstack_output_ibm;
 
+lazy_static! {
+    static ref DEFAULT_CLASSIFIER: Classifier = Classifier::default();
+}
+
 pub fn detect_jvm_vendor(output: &str) -> JvmVendor {
     if output.contains("OpenJDK") || output.contains("HotSpot") {
         JvmVendor::OpenJDK
@@ -19,19 +27,85 @@ pub fn detect_jvm_vendor(output: &str) -> JvmVendor {
     }
 }
 
+fn parse_timestamp_line(line: &str) -> Option<DateTime<Utc>> {
+    let line = line.trim();
+    NaiveDateTime::parse_from_str(line, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(line, "%Y-%m-%dT%H:%M:%S%.f"))
+        .ok()
+        .map(|timestamp| timestamp.and_utc())
+}
+
+/// Reads the capture time jstack prints above the dump: `2025-09-21 15:54:17` on
+/// HotSpot, `2025-09-21T03:43:15.285796153` on OpenJ9. The JVM prints local time
+/// without a zone, so it is taken as UTC.
+pub fn parse_dump_timestamp(output: &str) -> Option<DateTime<Utc>> {
+    output.lines().take(5).find_map(parse_timestamp_line)
+}
+
+/// Splits text holding several dumps one after the other (e.g. repeated
+/// `jstack` runs piped together) into one slice per dump. A dump starts at its
+/// `Full thread dump` line (HotSpot) or `Virtual machine:` line (OpenJ9),
+/// together with the capture time above it. Text without any such header is
+/// returned whole.
+pub fn split_dumps(output: &str) -> Vec<&str> {
+    let mut lines: Vec<(usize, &str)> = Vec::new();
+    let mut offset = 0;
+    for line in output.split_inclusive('\n') {
+        lines.push((offset, line));
+        offset += line.len();
+    }
+
+    let mut starts: Vec<usize> = Vec::new();
+    for (index, (offset, line)) in lines.iter().enumerate() {
+        if !(line.starts_with("Full thread dump") || line.starts_with("Virtual machine:")) {
+            continue;
+        }
+        let start = match index.checked_sub(1).map(|previous| lines[previous]) {
+            Some((previous_offset, previous)) if parse_timestamp_line(previous).is_some() => previous_offset,
+            _ => *offset,
+        };
+        starts.push(start);
+    }
+    if starts.len() < 2 {
+        return vec![output];
+    }
+
+    // Anything before the first header (e.g. a `PID:` line) stays with the first dump.
+    starts[0] = 0;
+    starts
+        .iter()
+        .zip(starts.iter().skip(1).chain(std::iter::once(&output.len())))
+        .map(|(&start, &end)| &output[start..end])
+        .collect()
+}
+
+/// Whether the text contains at least one thread header either parser
+/// recognises, so that files can be told apart by content rather than name.
+pub fn looks_like_thread_dump(output: &str) -> bool {
+    output.lines().any(|line| {
+        (line.starts_with('"') && line.contains("nid="))
+            || (line.contains("prio=") && (line.contains("tid=") || line.contains(" Id=")))
+    })
+}
+
+/// Parses a dump, categorizing frames with the built-in rules.
 pub fn parse_jstack_output(output: &str) -> Result<ThreadDump, String> {
+    parse_jstack_output_with(output, &DEFAULT_CLASSIFIER)
+}
+
+pub fn parse_jstack_output_with(output: &str, classifier: &Classifier) -> Result<ThreadDump, String> {
     match detect_jvm_vendor(output) {
         JvmVendor::OpenJDK => {
             log::info!("Detected OpenJDK/HotSpot JVM.");
-            parse_jstack_output_openjdk(output)
+            parse_jstack_output_openjdk(output, classifier)
         }
         JvmVendor::IBM => {
             log::info!("Detected IBM J9/Eclipse OpenJ9 JVM.");
-            parse_jstack_output_ibm(output)
+            parse_jstack_output_ibm(output, classifier)
         }
         JvmVendor::Unknown => {
             log::warn!("Unknown JVM vendor detected. Attempting OpenJDK parsing.");
-            parse_jstack_output_openjdk(output)
+            parse_jstack_output_openjdk(output, classifier)
         }
     }
 }
\ No newline at end of file
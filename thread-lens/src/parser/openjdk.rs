@@ -1,15 +1,21 @@
-use crate::models::{ThreadDump, NormalizedThread, CategorizedFrame, ThreadCategory};
-use crate::analyzer::{categorize_frame, determine_thread_category};
+use crate::models::{ThreadDump, NormalizedThread, ThreadCategory, LockAction, LockInfo};
+use crate::analyzer::categorize_thread;
+use crate::classifier::Classifier;
 use chrono::Utc;
+use super::parse_dump_timestamp;
 use log;
 use lazy_static::lazy_static;
 use regex::Regex;
 
 lazy_static! {
     static ref OPENJDK_JVM_VERSION_REGEX: Regex = Regex::new(r"Full thread dump (.*) \((\d+\.\d+\.\d+\+\d+).*\):$").unwrap();
+    static ref OPENJDK_TID_REGEX: Regex = Regex::new(r"tid=(0x[0-9a-fA-F]+)").unwrap();
+    static ref OPENJDK_CPU_REGEX: Regex = Regex::new(r"\bcpu=([0-9.]+)ms").unwrap();
+    static ref OPENJDK_LOCK_REGEX: Regex = Regex::new(r"^- (locked|waiting to lock|waiting on|parking to wait for|waiting to re-lock in wait\(\))\s+<(0x[0-9a-fA-F]+)> \(a (.+)\)$").unwrap();
+    static ref OPENJDK_OWNABLE_SYNC_REGEX: Regex = Regex::new(r"^- <(0x[0-9a-fA-F]+)> \(a (.+)\)$").unwrap();
 }
 
-pub fn parse_jstack_output_openjdk(output: &str) -> Result<ThreadDump, String> {
+pub fn parse_jstack_output_openjdk(output: &str, classifier: &Classifier) -> Result<ThreadDump, String> {
     let mut threads = Vec::new();
     let mut current_thread: Option<NormalizedThread> = None;
     let mut current_state_line: Option<String> = None;
@@ -17,6 +23,12 @@ pub fn parse_jstack_output_openjdk(output: &str) -> Result<ThreadDump, String> {
 
     for line in output.lines() {
         log::info!("Processing line: {}", line);
+        // The JVM's own deadlock report repeats stacks of threads already seen;
+        // nothing after it belongs to the last thread.
+        if line.starts_with("Found one Java-level deadlock") {
+            break;
+        }
+
         // Attempt to extract JVM version from header lines
         if let Some(captures) = OPENJDK_JVM_VERSION_REGEX.captures(line) {
             if let (Some(jvm_name_match), Some(version_num_match)) = (captures.get(1), captures.get(2)) {
@@ -31,16 +43,23 @@ pub fn parse_jstack_output_openjdk(output: &str) -> Result<ThreadDump, String> {
                 if let Some(state_line) = current_state_line.take() {
                     thread.state = parse_thread_state(&state_line);
                 }
-                thread.category = determine_thread_category(&thread.frames);
+                categorize_thread(&mut thread);
                 threads.push(thread);
             }
 
             let name = line.split('"').nth(1).unwrap_or("").to_string();
+            let id = OPENJDK_TID_REGEX.captures(line).map(|c| c[1].to_string());
+            let cpu_ms = OPENJDK_CPU_REGEX.captures(line).and_then(|c| c[1].parse().ok());
             current_thread = Some(NormalizedThread {
                 name,
+                id,
                 state: "UNKNOWN".to_string(), // Will be parsed from the state line
+                cpu_ms,
                 category: ThreadCategory::Unknown, // Will be determined after parsing frames
+                category_confidence: 0.0,
+                category_reason: String::new(),
                 frames: Vec::new(),
+                locks: Vec::new(),
             });
 
         } else if line.trim().starts_with("java.lang.Thread.State:") {
@@ -48,12 +67,9 @@ pub fn parse_jstack_output_openjdk(output: &str) -> Result<ThreadDump, String> {
 
         } else if let Some(ref mut thread) = current_thread {
             if line.trim().starts_with("at ") {
-                let frame_line = line.trim().to_string();
-                let category = categorize_frame(&frame_line);
-                thread.frames.push(CategorizedFrame {
-                    line: frame_line,
-                    category,
-                });
+                thread.frames.push(classifier.classify_frame(line.trim()));
+            } else if let Some(lock) = parse_lock_line(line.trim()) {
+                thread.locks.push(lock);
             }
         }
     }
@@ -63,17 +79,40 @@ pub fn parse_jstack_output_openjdk(output: &str) -> Result<ThreadDump, String> {
         if let Some(state_line) = current_state_line.take() {
             thread.state = parse_thread_state(&state_line);
         }
-        thread.category = determine_thread_category(&thread.frames);
+        categorize_thread(&mut thread);
         threads.push(thread);
     }
 
     Ok(ThreadDump {
         jvm_version, // Use the extracted JVM version
-        timestamp: Utc::now(), // Placeholder
+        timestamp: parse_dump_timestamp(output).unwrap_or_else(Utc::now),
         threads,
     })
 }
 
+pub(crate) fn parse_lock_line(line: &str) -> Option<LockInfo> {
+    if let Some(captures) = OPENJDK_LOCK_REGEX.captures(line) {
+        let action = match &captures[1] {
+            "locked" => LockAction::Locked,
+            "waiting to lock" => LockAction::WaitingToLock,
+            "waiting on" => LockAction::WaitingOn,
+            "parking to wait for" => LockAction::ParkingToWaitFor,
+            _ => LockAction::WaitingToReLock,
+        };
+        return Some(LockInfo {
+            action,
+            address: captures[2].to_string(),
+            class_name: captures[3].to_string(),
+        });
+    }
+    // Entries under "Locked ownable synchronizers:" (jstack -l)
+    OPENJDK_OWNABLE_SYNC_REGEX.captures(line).map(|captures| LockInfo {
+        action: LockAction::Locked,
+        address: captures[1].to_string(),
+        class_name: captures[2].to_string(),
+    })
+}
+
 fn parse_thread_state(state_line: &str) -> String {
     let parts: Vec<&str> = state_line.split_whitespace().collect();
     if parts.len() > 1 {
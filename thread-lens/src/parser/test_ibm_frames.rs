@@ -1,6 +1,11 @@
+#![cfg(test)]
+
 use crate::analyzer::is_application_frame;
+use crate::classifier::Classifier;
+use crate::models::{FrameCategory, ThreadDump};
+use crate::parser::{parse_jstack_output, parse_jstack_output_with};
+use std::collections::BTreeSet;
 use std::fs;
-use crate::parser::parse_jstack_output;
 
 #[test]
 fn test_is_application_frame_with_ibm_dump_data() {
@@ -31,55 +36,65 @@ fn test_is_application_frame_with_ibm_dump_data() {
     assert!(!is_application_frame(spark_frame, None));
 }
 
+/// The application frames of the IBM sample dumps, all in `ThreadAnalyzerApplication`.
+const IBM_APP_FRAMES: [&str; 3] = [
+    "at app//com.example.threadanalyzer.ThreadAnalyzerApplication.lambda$main$1(ThreadAnalyzerApplication.java:79)",
+    "at app//com.example.threadanalyzer.ThreadAnalyzerApplication.lambda$main$1(ThreadAnalyzerApplication.java:83)",
+    "at app//com.example.threadanalyzer.ThreadAnalyzerApplication.lambda$main$2(ThreadAnalyzerApplication.java:95)",
+];
+
+fn app_frames(dump: &ThreadDump, app_package_prefix: Option<&str>) -> BTreeSet<String> {
+    dump.threads
+        .iter()
+        .flat_map(|thread| &thread.frames)
+        .filter(|frame| is_application_frame(&frame.line, app_package_prefix))
+        .map(|frame| frame.line.clone())
+        .collect()
+}
+
 #[test]
 fn test_is_application_frame_with_actual_ibm_dump() {
     let dump_bytes = fs::read("src/test_data/ibm_thread_dump.jstack").expect("Unable to read ibm_thread_dump.jstack as bytes");
     let dump_content = String::from_utf8_lossy(&dump_bytes).to_string();
-    let threads = parse_jstack_output(&dump_content);
+    let dump = parse_jstack_output(&dump_content).expect("Failed to parse IBM dump");
 
     let app_package_prefix = Some("com.example.threadanalyzer");
-
-    let mut found_app_frame = false;
-    let mut found_spark_frame = false;
-    let mut found_jetty_frame = false;
-
-    for thread in threads {
-        for frame in thread.stack_trace {
-            if is_application_frame(&frame, app_package_prefix) {
-                println!("Found app frame: {}", frame);
-                found_app_frame = true;
-            } else if frame.contains("spark.") {
-                println!("Found spark frame: {}", frame);
-                found_spark_frame = true;
-            } else if frame.contains("jetty.") {
-                println!("Found jetty frame: {}", frame);
-                found_jetty_frame = true;
-            }
-        }
+    assert_eq!(app_frames(&dump, app_package_prefix), IBM_APP_FRAMES.iter().map(|line| line.to_string()).collect());
+
+    let frames: Vec<&str> = dump.threads.iter().flat_map(|thread| &thread.frames).map(|frame| frame.line.as_str()).collect();
+    let spark_frames: Vec<&str> = frames.iter().copied().filter(|line| line.contains("app//spark.")).collect();
+    let jetty_frames: Vec<&str> = frames.iter().copied().filter(|line| line.contains("app//org.eclipse.jetty.")).collect();
+    assert!(spark_frames.contains(&"at app//spark.Service.lambda$init$2(Service.java:644)"));
+    assert!(jetty_frames.contains(&"at app//org.eclipse.jetty.server.Server.join(Server.java:551)"));
+    for line in spark_frames.iter().chain(&jetty_frames) {
+        assert!(!is_application_frame(line, app_package_prefix), "{} is not application code", line);
     }
-
-    assert!(found_app_frame, "Should have found at least one application frame");
-    assert!(found_spark_frame, "Should have found at least one spark frame");
-    assert!(found_jetty_frame, "Should have found at least one jetty frame");
 }
 
 #[test]
 fn test_is_application_frame_with_minimal_ibm_dump() {
     let dump_content = fs::read_to_string("src/test_data/minimal_ibm_app_thread.jstack").expect("Unable to read minimal_ibm_app_thread.jstack");
-    let threads = parse_jstack_output(&dump_content);
+    let dump = parse_jstack_output(&dump_content).expect("Failed to parse minimal IBM dump");
 
     let app_package_prefix = Some("com.example.threadanalyzer");
+    assert_eq!(app_frames(&dump, app_package_prefix), IBM_APP_FRAMES.iter().map(|line| line.to_string()).collect());
+    assert!(app_frames(&dump, None).is_empty());
+}
 
-    let mut found_app_frame = false;
-
-    for thread in threads {
-        for frame in thread.stack_trace {
-            if is_application_frame(&frame, app_package_prefix) {
-                println!("Found app frame in minimal dump: {}", frame);
-                found_app_frame = true;
-            }
-        }
+#[test]
+fn test_app_packages_classify_ibm_dump_frames() {
+    let dump_bytes = fs::read("src/test_data/ibm_thread_dump.jstack").expect("Unable to read ibm_thread_dump.jstack as bytes");
+    let dump_content = String::from_utf8_lossy(&dump_bytes).to_string();
+    let classifier = Classifier::default().with_app_packages(vec!["com.example.threadanalyzer".to_string()]);
+    let dump = parse_jstack_output_with(&dump_content, &classifier).expect("Failed to parse IBM dump");
+
+    for frame in dump.threads.iter().flat_map(|thread| &thread.frames) {
+        let expected_application = is_application_frame(&frame.line, Some("com.example.threadanalyzer"));
+        assert_eq!(
+            frame.category == FrameCategory::Application,
+            expected_application,
+            "unexpected category for {}",
+            frame.line
+        );
     }
-
-    assert!(found_app_frame, "Should have found at least one application frame in minimal dump");
 }
@@ -3,8 +3,10 @@
 use super::ibm::parse_jstack_output_ibm;
 use super::openjdk::parse_jstack_output_openjdk;
 
-use super::{detect_jvm_vendor, parse_jstack_output};
-use crate::models::{FrameCategory, ThreadCategory};
+use super::{detect_jvm_vendor, looks_like_thread_dump, parse_jstack_output, split_dumps, JvmVendor};
+use crate::classifier::Classifier;
+use crate::models::{FrameCategory, LockAction, ThreadCategory};
+use std::fs;
 
 #[test]
 fn test_parse_jstack_output_ibm_sample() {
@@ -25,7 +27,7 @@ Full thread dump IBM Semeru Runtime Open Edition 17.0.8.0 (build 17.0.8+7, JRE 1
 	at java.lang.Object.wait(java.base@17.0.8/Native Method)
 "#;
 
-    let dump = parse_jstack_output_ibm(sample_dump).expect("Failed to parse IBM dump");
+    let dump = parse_jstack_output_ibm(sample_dump, &Classifier::default()).expect("Failed to parse IBM dump");
 
     assert_eq!(dump.threads.len(), 3);
     assert_eq!(dump.jvm_version, "IBM Semeru Runtime Open Edition 17.0.8.0");
@@ -93,7 +95,7 @@ Full thread dump OpenJDK 64-Bit Server VM (11.0.16+8 mixed mode, sharing):
 	at com.example.threadanalyzer.ThreadAnalyzerApplication.lambda$null$2(ThreadAnalyzerApplication.java:96)
 "#;
 
-    let dump = parse_jstack_output_openjdk(sample_dump).expect("Failed to parse OpenJDK dump");
+    let dump = parse_jstack_output_openjdk(sample_dump, &Classifier::default()).expect("Failed to parse OpenJDK dump");
 
     assert_eq!(dump.threads.len(), 3);
     assert_eq!(dump.jvm_version, "OpenJDK 64-Bit Server VM (11.0.16+8)");
@@ -102,7 +104,7 @@ Full thread dump OpenJDK 64-Bit Server VM (11.0.16+8 mixed mode, sharing):
     let main_thread = &dump.threads[0];
     assert_eq!(main_thread.name, "main");
     assert_eq!(main_thread.state, "RUNNABLE");
-    assert_eq!(main_thread.category, ThreadCategory::Jvm);
+    assert_eq!(main_thread.category, ThreadCategory::Application);
     assert_eq!(main_thread.frames.len(), 0);
 
     // Test Thread-0 (WAITING on object monitor -> BLOCKED)
@@ -131,3 +133,87 @@ Full thread dump OpenJDK 64-Bit Server VM (11.0.16+8 mixed mode, sharing):
         FrameCategory::Application
     );
 }
+
+#[test]
+fn test_parse_openj9_jstack_headers_and_locks() {
+    let content = fs::read_to_string("src/test_data/minimal_ibm_app_thread.jstack")
+        .expect("Unable to read minimal_ibm_app_thread.jstack");
+    assert_eq!(detect_jvm_vendor(&content), JvmVendor::IBM);
+
+    let dump = parse_jstack_output(&content).expect("Failed to parse OpenJ9 dump");
+    assert_eq!(dump.threads.len(), 4);
+    assert_eq!(dump.timestamp.to_rfc3339(), "2025-09-21T03:43:15.285796153+00:00");
+
+    let blocker = &dump.threads[0];
+    assert_eq!(blocker.name, "BlockerThread");
+    assert_eq!(blocker.id.as_deref(), Some("40"));
+    assert_eq!(blocker.state, "WAITING");
+    assert_eq!(blocker.locks.len(), 1);
+    assert_eq!(blocker.locks[0].action, LockAction::Locked);
+    assert_eq!(blocker.locks[0].address, "3b4b3daf");
+    assert_eq!(blocker.locks[0].class_name, "java.lang.Object");
+
+    let blocked = &dump.threads[1];
+    assert_eq!(blocked.state, "BLOCKED");
+    assert_eq!(blocked.locks[0].action, LockAction::WaitingToLock);
+    assert_eq!(blocked.locks[0].address, "3b4b3daf");
+}
+
+#[test]
+fn test_parse_openjdk_lock_annotations() {
+    let content = fs::read_to_string(
+        "src/test_data/java-app-openjdk11_20250921085315_deadlock/java-app-openjdk11_20250921155503576.jstack",
+    )
+    .expect("Unable to read OpenJDK 11 deadlock dump");
+    let dump = parse_jstack_output(&content).expect("Failed to parse OpenJDK dump");
+    assert_eq!(dump.timestamp.to_rfc3339(), "2025-09-21T15:55:03+00:00");
+
+    let finalizer = dump.threads.iter().find(|t| t.name == "Finalizer").unwrap();
+    assert_eq!(finalizer.id.as_deref(), Some("0x0000e7a0a410a000"));
+    assert_eq!(finalizer.locks[0].action, LockAction::WaitingOn);
+    assert_eq!(finalizer.locks[0].class_name, "java.lang.ref.ReferenceQueue$Lock");
+    assert_eq!(finalizer.locks[1].action, LockAction::WaitingToReLock);
+
+    let deadlocked = dump.threads.iter().find(|t| t.name == "DeadlockThread-1").unwrap();
+    assert_eq!(deadlocked.locks[0].action, LockAction::WaitingToLock);
+    assert_eq!(deadlocked.locks[1].action, LockAction::Locked);
+
+    // The JVM's "Found one Java-level deadlock" report must not add frames to the last thread
+    let last = dump.threads.last().unwrap();
+    assert!(last.frames.is_empty());
+}
+
+#[test]
+fn test_looks_like_thread_dump() {
+    for path in [
+        "src/test_data/ibm_thread_dump.jstack",
+        "src/test_data/java-app-openjdk11_20250921085315_deadlock/java-app-openjdk11_20250921155416927.jstack",
+    ] {
+        let content = fs::read_to_string(path).expect("Unable to read dump");
+        assert!(looks_like_thread_dump(&content), "{}", path);
+    }
+    assert!(!looks_like_thread_dump("# Support bundle\nhost: web-1\n"));
+    assert!(!looks_like_thread_dump("0SECTION       TITLE subcomponent dump routine\n"));
+}
+
+#[test]
+fn test_split_concatenated_dumps() {
+    let dir = "src/test_data/java-app-openjdk8_20250920201915_block";
+    let mut paths: Vec<_> = fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+    paths.sort();
+    let first = fs::read_to_string(&paths[0]).unwrap();
+    let second = fs::read_to_string(&paths[1]).unwrap();
+    let ibm = fs::read_to_string("src/test_data/ibm_thread_dump.jstack").unwrap();
+    let stream = format!("PID: 4242\n{}\n{}{}", first, second, ibm);
+
+    let dumps = split_dumps(&stream);
+    assert_eq!(dumps.len(), 3);
+    assert!(dumps[0].starts_with("PID: 4242\n"));
+    assert!(dumps[1].starts_with(second.lines().next().unwrap()));
+    let parsed: Vec<_> = dumps.iter().map(|dump| parse_jstack_output(dump).unwrap()).collect();
+    assert_eq!(parsed[0].threads.len(), parse_jstack_output(&first).unwrap().threads.len());
+    assert_eq!(parsed[2].threads.len(), parse_jstack_output(&ibm).unwrap().threads.len());
+    assert!(parsed[0].timestamp < parsed[1].timestamp);
+
+    assert_eq!(split_dumps(&first), vec![first.as_str()]);
+}
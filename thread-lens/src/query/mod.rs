@@ -0,0 +1,358 @@
+use crate::analyzer::in_time_order;
+use crate::analyzer::pools::pool_name;
+use crate::classifier::qualified_frame;
+use crate::models::{FrameCategory, LockAction, LockInfo, NormalizedThread, ThreadDump};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+mod parser;
+
+/// A thread property a query can test or count by. Frames are compared without
+/// their `at ` and module prefix, e.g. `com.mycorp.Cache.get(Cache.java:12)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Name,
+    Id,
+    State,
+    Category,
+    Pool,
+    /// Any frame of the stack.
+    Frame,
+    TopFrame,
+    /// Any application frame.
+    AppFrame,
+    TopAppFrame,
+    /// Libraries of the built-in catalog found on the stack.
+    Library,
+    /// Any lock the thread holds or waits for, by address or class.
+    Lock,
+    Holding,
+    WaitingOn,
+    /// Number of frames.
+    Depth,
+}
+
+impl Field {
+    pub const ALL: &'static [Field] = &[
+        Field::Name,
+        Field::Id,
+        Field::State,
+        Field::Category,
+        Field::Pool,
+        Field::Frame,
+        Field::TopFrame,
+        Field::AppFrame,
+        Field::TopAppFrame,
+        Field::Library,
+        Field::Lock,
+        Field::Holding,
+        Field::WaitingOn,
+        Field::Depth,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Field::Name => "name",
+            Field::Id => "id",
+            Field::State => "state",
+            Field::Category => "category",
+            Field::Pool => "pool",
+            Field::Frame => "frame",
+            Field::TopFrame => "top_frame",
+            Field::AppFrame => "app_frame",
+            Field::TopAppFrame => "top_app_frame",
+            Field::Library => "library",
+            Field::Lock => "lock",
+            Field::Holding => "holding",
+            Field::WaitingOn => "waiting_on",
+            Field::Depth => "depth",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Field> {
+        Field::ALL.iter().copied().find(|field| field.name().eq_ignore_ascii_case(name))
+    }
+
+    fn ignores_case(&self) -> bool {
+        matches!(self, Field::State | Field::Category)
+    }
+
+    fn locks<'a>(&self, thread: &'a NormalizedThread) -> impl Iterator<Item = &'a LockInfo> {
+        let field = *self;
+        thread.locks.iter().filter(move |lock| match field {
+            Field::Holding => lock.action == LockAction::Locked,
+            Field::WaitingOn => lock.action != LockAction::Locked,
+            _ => true,
+        })
+    }
+
+    /// The values a condition is tested against; a thread can have none
+    /// (`pool` of a thread outside any pool) or several (`frame`).
+    fn values(&self, thread: &NormalizedThread) -> Vec<String> {
+        let app_frames = || {
+            thread
+                .frames
+                .iter()
+                .filter(|frame| frame.category == FrameCategory::Application)
+                .map(|frame| qualified_frame(&frame.line).to_string())
+        };
+        match self {
+            Field::Name => vec![thread.name.clone()],
+            Field::Id => thread.id.iter().cloned().collect(),
+            Field::State => vec![thread.state.clone()],
+            Field::Category => vec![format!("{:?}", thread.category)],
+            Field::Pool => pool_name(&thread.name).into_iter().collect(),
+            Field::Frame => thread.frames.iter().map(|frame| qualified_frame(&frame.line).to_string()).collect(),
+            Field::TopFrame => thread.frames.first().map(|frame| qualified_frame(&frame.line).to_string()).into_iter().collect(),
+            Field::AppFrame => app_frames().collect(),
+            Field::TopAppFrame => app_frames().take(1).collect(),
+            Field::Library => thread.frames.iter().filter_map(|frame| frame.library.clone()).collect(),
+            Field::Lock | Field::Holding | Field::WaitingOn => self
+                .locks(thread)
+                .flat_map(|lock| [lock.address.clone(), lock.class_name.clone()])
+                .collect(),
+            Field::Depth => vec![thread.frames.len().to_string()],
+        }
+    }
+
+    /// The distinct values a thread is counted under by `count by`.
+    fn keys(&self, thread: &NormalizedThread) -> Vec<String> {
+        let values = match self {
+            Field::Lock | Field::Holding | Field::WaitingOn => self
+                .locks(thread)
+                .map(|lock| format!("<{}> (a {})", lock.address, lock.class_name))
+                .collect(),
+            _ => self.values(thread),
+        };
+        let mut keys: Vec<String> = Vec::new();
+        for value in values {
+            if !keys.contains(&value) {
+                keys.push(value);
+            }
+        }
+        if keys.is_empty() {
+            keys.push("(none)".to_string());
+        }
+        keys
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    /// Equal to a glob, e.g. `qtp*`.
+    Eq,
+    Ne,
+    /// Matches a regex anywhere in the value.
+    Match,
+    NotMatch,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Op::Eq => "=",
+            Op::Ne => "!=",
+            Op::Match => "~",
+            Op::NotMatch => "!~",
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Condition {
+    pub field: Field,
+    pub op: Op,
+    pub value: String,
+    pattern: Option<Regex>,
+    number: Option<usize>,
+}
+
+impl Condition {
+    /// For a field with several values, `=` and `~` hold when any value
+    /// matches, and `!=` and `!~` when none does.
+    fn matches(&self, thread: &NormalizedThread) -> bool {
+        let values = self.field.values(thread);
+        match (self.op, &self.pattern, self.number) {
+            (Op::Eq | Op::Match, Some(pattern), _) => values.iter().any(|value| pattern.is_match(value)),
+            (Op::Ne | Op::NotMatch, Some(pattern), _) => !values.iter().any(|value| pattern.is_match(value)),
+            (op, _, Some(number)) => values.iter().filter_map(|value| value.parse::<usize>().ok()).any(|value| match op {
+                Op::Lt => value < number,
+                Op::Le => value <= number,
+                Op::Gt => value > number,
+                _ => value >= number,
+            }),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Condition(Condition),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    pub fn matches(&self, thread: &NormalizedThread) -> bool {
+        match self {
+            Expr::Condition(condition) => condition.matches(thread),
+            Expr::Not(expr) => !expr.matches(thread),
+            Expr::And(left, right) => left.matches(thread) && right.matches(thread),
+            Expr::Or(left, right) => left.matches(thread) || right.matches(thread),
+        }
+    }
+}
+
+/// A parsed query: an optional filter, e.g. `state = BLOCKED and frame ~ cache`,
+/// optionally followed by `count` or `count by <field>, ...`.
+#[derive(Debug, Clone)]
+pub struct Query {
+    pub filter: Option<Expr>,
+    /// `None` lists the matching threads; an empty list counts them.
+    pub count: Option<Vec<Field>>,
+}
+
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Query, String> {
+        parser::parse(input)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MatchedThread {
+    pub timestamp: DateTime<Utc>,
+    pub thread: NormalizedThread,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueryGroup {
+    /// One value per `count by` field.
+    pub key: Vec<String>,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueryResult {
+    pub dumps: usize,
+    pub total_threads: usize,
+    pub matched: usize,
+    /// The matching threads in capture order, unless the query counts.
+    pub threads: Vec<MatchedThread>,
+    /// The `count by` fields, and the counts for each combination of their
+    /// values, largest first.
+    pub by: Vec<String>,
+    pub groups: Vec<QueryGroup>,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Query, String> {
+        input.parse()
+    }
+
+    pub fn matches(&self, thread: &NormalizedThread) -> bool {
+        self.filter.as_ref().is_none_or(|filter| filter.matches(thread))
+    }
+
+    /// Runs the query over every thread of every dump. A thread with several
+    /// values for a `count by` field is counted once under each of them.
+    pub fn run(&self, dumps: &[ThreadDump]) -> QueryResult {
+        let ordered = in_time_order(dumps);
+        let mut result = QueryResult {
+            dumps: ordered.len(),
+            total_threads: 0,
+            matched: 0,
+            threads: Vec::new(),
+            by: self.count.iter().flatten().map(|field| field.name().to_string()).collect(),
+            groups: Vec::new(),
+        };
+        let mut groups: BTreeMap<Vec<String>, usize> = BTreeMap::new();
+        for dump in &ordered {
+            result.total_threads += dump.threads.len();
+            for thread in dump.threads.iter().filter(|thread| self.matches(thread)) {
+                result.matched += 1;
+                match &self.count {
+                    None => result.threads.push(MatchedThread {
+                        timestamp: dump.timestamp,
+                        thread: thread.clone(),
+                    }),
+                    Some(fields) if !fields.is_empty() => {
+                        let mut keys: Vec<Vec<String>> = vec![Vec::new()];
+                        for field in fields {
+                            let values = field.keys(thread);
+                            keys = keys
+                                .into_iter()
+                                .flat_map(|key| {
+                                    values.iter().map(move |value| {
+                                        let mut key = key.clone();
+                                        key.push(value.clone());
+                                        key
+                                    })
+                                })
+                                .collect();
+                        }
+                        for key in keys {
+                            *groups.entry(key).or_insert(0) += 1;
+                        }
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        result.groups = groups.into_iter().map(|(key, count)| QueryGroup { key, count }).collect();
+        result.groups.sort_by_key(|group| std::cmp::Reverse(group.count));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_jstack_output;
+    use std::fs;
+
+    fn deadlock_dump() -> ThreadDump {
+        let content = fs::read_to_string("src/test_data/java-app-openjdk11_20250921085315_deadlock/java-app-openjdk11_20250921155448034.jstack").unwrap();
+        parse_jstack_output(&content).unwrap()
+    }
+
+    #[test]
+    fn test_query_filters_and_counts() {
+        let dumps = vec![deadlock_dump()];
+        let run = |query: &str| Query::parse(query).unwrap().run(&dumps);
+
+        let blocked = run(r#"state = blocked and frame ~ "acquireDeadlockLock1" and not name = "*-2""#);
+        assert!(blocked.matched > 0);
+        assert!(blocked.threads.iter().all(|matched| matched.thread.name == "DeadlockThread-1"));
+        assert_eq!(blocked.total_threads, dumps[0].threads.len());
+
+        let holders = run("holding = 0x00000000e2ed5c90 and depth >= 3");
+        assert_eq!(holders.matched, 1);
+
+        let by_state = run("count by state");
+        assert!(by_state.threads.is_empty());
+        assert_eq!(by_state.by, vec!["state"]);
+        assert_eq!(by_state.groups.iter().map(|group| group.count).sum::<usize>(), dumps[0].threads.len());
+        assert!(by_state.groups.windows(2).all(|pair| pair[0].count >= pair[1].count));
+
+        let by_lock = run("state = BLOCKED count by waiting_on");
+        assert!(by_lock
+            .groups
+            .iter()
+            .any(|group| group.key == vec!["<0x00000000e2ed5c90> (a java.lang.Object)".to_string()]));
+    }
+}
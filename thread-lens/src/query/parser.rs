@@ -0,0 +1,291 @@
+use super::{Condition, Expr, Field, Op, Query};
+use regex::Regex;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Str(String),
+    Op(Op),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Word(word) => format!("'{}'", word),
+            Token::Str(value) => format!("\"{}\"", value),
+            Token::Op(op) => format!("'{}'", op.symbol()),
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+            Token::Comma => "','".to_string(),
+        }
+    }
+}
+
+const SPECIAL: &[char] = &['(', ')', '=', '!', '~', '<', '>', ',', '"', '\''];
+
+/// Splits a query into tokens, each with the column (1-based) it starts at.
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let two: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+        let (token, len) = match (c, two.as_str()) {
+            (_, "!=") => (Token::Op(Op::Ne), 2),
+            (_, "!~") => (Token::Op(Op::NotMatch), 2),
+            (_, "<=") => (Token::Op(Op::Le), 2),
+            (_, ">=") => (Token::Op(Op::Ge), 2),
+            ('=', _) => (Token::Op(Op::Eq), 1),
+            ('~', _) => (Token::Op(Op::Match), 1),
+            ('<', _) => (Token::Op(Op::Lt), 1),
+            ('>', _) => (Token::Op(Op::Gt), 1),
+            ('(', _) => (Token::LParen, 1),
+            (')', _) => (Token::RParen, 1),
+            (',', _) => (Token::Comma, 1),
+            ('!', _) => return Err(format!("column {}: expected '!=' or '!~'", column)),
+            ('"' | '\'', _) => {
+                // A backslash only escapes the quote; others are kept for regexes.
+                let mut value = String::new();
+                let mut end = i + 1;
+                loop {
+                    match chars.get(end) {
+                        None => return Err(format!("column {}: unterminated string", column)),
+                        Some(&quote) if quote == c => break,
+                        Some('\\') if chars.get(end + 1) == Some(&c) => {
+                            value.push(c);
+                            end += 2;
+                        }
+                        Some(&other) => {
+                            value.push(other);
+                            end += 1;
+                        }
+                    }
+                }
+                (Token::Str(value), end + 1 - i)
+            }
+            _ => {
+                let len = chars[i..]
+                    .iter()
+                    .take_while(|c| !c.is_whitespace() && !SPECIAL.contains(c))
+                    .count();
+                (Token::Word(chars[i..i + len].iter().collect()), len)
+            }
+        };
+        tokens.push((column, token));
+        i += len;
+    }
+    Ok(tokens)
+}
+
+/// `qtp*` -> `^qtp.*$`
+fn glob_to_regex(glob: &str) -> String {
+    let mut pattern = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    pattern
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    end_column: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens.get(self.position).map_or(self.end_column, |(column, _)| *column)
+    }
+
+    fn error(&self, message: &str) -> String {
+        let found = self.peek().map_or("end of query".to_string(), Token::describe);
+        format!("column {}: {}, found {}", self.column(), message, found)
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.at_keyword(keyword);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        let found = self.peek() == Some(token);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn query(&mut self) -> Result<Query, String> {
+        let filter = if self.peek().is_none() || self.at_keyword("count") {
+            None
+        } else {
+            Some(self.or()?)
+        };
+        let count = if self.eat_keyword("count") {
+            let mut fields = Vec::new();
+            if self.eat_keyword("by") {
+                fields.push(self.field()?);
+                while self.eat(&Token::Comma) {
+                    fields.push(self.field()?);
+                }
+            }
+            Some(fields)
+        } else {
+            None
+        };
+        if self.peek().is_some() {
+            return Err(self.error("expected 'and', 'or' or 'count'"));
+        }
+        Ok(Query { filter, count })
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.eat_keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while self.eat_keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat_keyword("not") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat(&Token::LParen) {
+            let expr = self.or()?;
+            if !self.eat(&Token::RParen) {
+                return Err(self.error("expected ')'"));
+            }
+            return Ok(expr);
+        }
+        self.condition()
+    }
+
+    fn field(&mut self) -> Result<Field, String> {
+        let field = match self.peek() {
+            Some(Token::Word(word)) => Field::from_name(word),
+            _ => None,
+        };
+        match field {
+            Some(field) => {
+                self.position += 1;
+                Ok(field)
+            }
+            None => Err(self.error(&format!(
+                "expected a field ({})",
+                Field::ALL.iter().map(Field::name).collect::<Vec<_>>().join(", ")
+            ))),
+        }
+    }
+
+    fn condition(&mut self) -> Result<Expr, String> {
+        let field = self.field()?;
+        let op_column = self.column();
+        let op = match self.peek() {
+            Some(Token::Op(op)) => *op,
+            _ => return Err(self.error(&format!("expected an operator after '{}'", field.name()))),
+        };
+        self.position += 1;
+        let column = self.column();
+        let value = match self.peek() {
+            Some(Token::Word(value) | Token::Str(value)) => value.clone(),
+            _ => return Err(self.error(&format!("expected a value after '{}'", op.symbol()))),
+        };
+        self.position += 1;
+
+        let case = if field.ignores_case() { "(?i)" } else { "" };
+        let (pattern, number) = match op {
+            Op::Eq | Op::Ne => (Some(format!("{}{}", case, glob_to_regex(&value))), None),
+            Op::Match | Op::NotMatch => (Some(format!("{}{}", case, value)), None),
+            Op::Lt | Op::Le | Op::Gt | Op::Ge => {
+                if field != Field::Depth {
+                    return Err(format!("column {}: '{}' only applies to depth", op_column, op.symbol()));
+                }
+                let number = value
+                    .parse()
+                    .map_err(|_| format!("column {}: expected a number, found '{}'", column, value))?;
+                (None, Some(number))
+            }
+        };
+        let pattern = pattern
+            .map(|pattern| Regex::new(&pattern).map_err(|e| format!("column {}: invalid regex: {}", column, e)))
+            .transpose()?;
+        Ok(Expr::Condition(Condition {
+            field,
+            op,
+            value,
+            pattern,
+            number,
+        }))
+    }
+}
+
+pub(super) fn parse(input: &str) -> Result<Query, String> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        position: 0,
+        end_column: input.chars().count() + 1,
+    };
+    parser.query()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_precedence_and_errors() {
+        let query = parse(r#"state = BLOCKED and frame ~ "com\.mycorp\.cache" or not (pool = 'qtp*') count by pool, state"#).unwrap();
+        assert_eq!(query.count, Some(vec![Field::Pool, Field::State]));
+        // `and` binds tighter than `or`.
+        match query.filter {
+            Some(Expr::Or(left, right)) => {
+                assert!(matches!(*left, Expr::And(_, _)));
+                assert!(matches!(*right, Expr::Not(_)));
+            }
+            other => panic!("unexpected filter {:?}", other),
+        }
+
+        assert_eq!(parse("count by top_app_frame").unwrap().count, Some(vec![Field::TopAppFrame]));
+        assert!(parse("").unwrap().filter.is_none());
+
+        assert_eq!(parse("stat = BLOCKED").unwrap_err().split(',').next(), Some("column 1: expected a field (name"));
+        assert_eq!(parse("state = BLOCKED pool").unwrap_err(), "column 17: expected 'and', 'or' or 'count', found 'pool'");
+        assert_eq!(parse("name > 3").unwrap_err(), "column 6: '>' only applies to depth");
+        assert!(parse("frame ~ \"(\"").unwrap_err().starts_with("column 9: invalid regex"));
+        assert_eq!(parse("state = ").unwrap_err(), "column 9: expected a value after '=', found end of query");
+    }
+}